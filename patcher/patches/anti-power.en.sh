@@ -231,6 +231,16 @@ update_configs_only() {
     echo -e "\n[1/2] Updating Cascade Panel config..."
     if [ -f "$PATCHES_DIR/cascade-panel/config.json" ] && [ -d "$TARGET_DIR_1/cascade-panel" ]; then
         cp "$PATCHES_DIR/cascade-panel/config.json" "$TARGET_DIR_1/cascade-panel/"
+        if [ -d "$PATCHES_DIR/cascade-panel/vendor" ]; then
+            rm -rf "$TARGET_DIR_1/cascade-panel/vendor"
+            cp -r "$PATCHES_DIR/cascade-panel/vendor" "$TARGET_DIR_1/cascade-panel/"
+            echo "Synced cascade-panel/vendor offline assets"
+        fi
+        rm -rf "$TARGET_DIR_1/cascade-panel/user"
+        if [ -d "$PATCHES_DIR/cascade-panel/user" ]; then
+            cp -r "$PATCHES_DIR/cascade-panel/user" "$TARGET_DIR_1/cascade-panel/"
+            echo "Synced cascade-panel/user overlay"
+        fi
         echo "Updated cascade-panel/config.json"
     else
         echo "Warning: cascade-panel config missing or target dir not found"
@@ -239,12 +249,110 @@ update_configs_only() {
     echo -e "\n[2/2] Updating Manager config..."
     if [ -f "$PATCHES_DIR/manager-panel/config.json" ] && [ -d "$TARGET_DIR_2/manager-panel" ]; then
         cp "$PATCHES_DIR/manager-panel/config.json" "$TARGET_DIR_2/manager-panel/"
+        if [ -d "$PATCHES_DIR/manager-panel/vendor" ]; then
+            rm -rf "$TARGET_DIR_2/manager-panel/vendor"
+            cp -r "$PATCHES_DIR/manager-panel/vendor" "$TARGET_DIR_2/manager-panel/"
+            echo "Synced manager-panel/vendor offline assets"
+        fi
+        rm -rf "$TARGET_DIR_2/manager-panel/user"
+        if [ -d "$PATCHES_DIR/manager-panel/user" ]; then
+            cp -r "$PATCHES_DIR/manager-panel/user" "$TARGET_DIR_2/manager-panel/"
+            echo "Synced manager-panel/user overlay"
+        fi
         echo "Updated manager-panel/config.json"
     else
         echo "Warning: manager-panel config missing or target dir not found"
     fi
 }
 
+# File operation targets must be inside --app-path, or be the sibling app.asar / app.asar.bak or the install root's Info.plist
+is_allowed_target() {
+    case "$1" in
+        /*) ;;
+        *) return 1 ;;
+    esac
+    case "/$1/" in
+        */../*|*/./*) return 1 ;;
+    esac
+    local RESOURCES_DIR
+    RESOURCES_DIR="$(dirname "$APP_PATH")"
+    case "$1" in
+        "$APP_PATH"/*|"$RESOURCES_DIR/app.asar"|"$RESOURCES_DIR/app.asar.bak"|"$(dirname "$RESOURCES_DIR")/Info.plist")
+            return 0
+            ;;
+    esac
+    return 1
+}
+
+# Copy sources must be inside the staging dir (this script's dir), or be an allowed target (e.g. backing up app.asar)
+is_allowed_source() {
+    case "/$1/" in
+        */../*|*/./*) return 1 ;;
+    esac
+    case "$1" in
+        "$PATCHES_DIR"/*) return 0 ;;
+    esac
+    is_allowed_target "$1"
+}
+
+# Apply staged file operations (app.asar write-back, uninstall restore, rollback)
+# Each line of file-ops.txt is tab-separated: copy<TAB>source<TAB>target, remove<TAB>target or remove-dir<TAB>target
+apply_file_ops() {
+    echo -e "\nApplying staged file operations..."
+    OPS_FILE="$PATCHES_DIR/file-ops.txt"
+    if [ ! -f "$OPS_FILE" ]; then
+        echo "Error: Operation list not found: $OPS_FILE"
+        exit 1
+    fi
+
+    # Validate every operation first; nothing runs if any of them is rejected
+    while IFS=$'\t' read -r OP SOURCE TARGET; do
+        case "$OP" in
+            copy)
+                if ! is_allowed_source "$SOURCE" || ! is_allowed_target "$TARGET"; then
+                    echo "Error: Refusing file operation outside the install: $SOURCE -> $TARGET"
+                    exit 1
+                fi
+                ;;
+            remove|remove-dir)
+                if ! is_allowed_target "$SOURCE"; then
+                    echo "Error: Refusing file operation outside the install: $SOURCE"
+                    exit 1
+                fi
+                ;;
+            *)
+                echo "Error: Unknown file operation: $OP"
+                exit 1
+                ;;
+        esac
+    done < "$OPS_FILE"
+
+    while IFS=$'\t' read -r OP SOURCE TARGET; do
+        case "$OP" in
+            copy)
+                echo "Writing $TARGET"
+                mkdir -p "$(dirname "$TARGET")"
+                # Write a temp file in the target dir, then rename over the target (keeping its permissions)
+                if [ -e "$TARGET" ]; then
+                    cp -p "$TARGET" "$TARGET.anti-power.tmp"
+                    cat "$SOURCE" > "$TARGET.anti-power.tmp"
+                else
+                    cp "$SOURCE" "$TARGET.anti-power.tmp"
+                fi
+                mv -f "$TARGET.anti-power.tmp" "$TARGET"
+                ;;
+            remove)
+                echo "Removing $SOURCE"
+                rm -f "$SOURCE"
+                ;;
+            remove-dir)
+                echo "Removing $SOURCE"
+                rm -rf "$SOURCE"
+                ;;
+        esac
+    done < "$OPS_FILE"
+}
+
 case "$MODE" in
     uninstall)
         restore_cascade
@@ -253,6 +361,9 @@ case "$MODE" in
     update-config)
         update_configs_only
         ;;
+    apply-files)
+        apply_file_ops
+        ;;
     install|*)
         if [ "$CASCADE_ENABLED" = "true" ]; then
             install_cascade
@@ -231,6 +231,16 @@ update_configs_only() {
     echo -e "\n[1/2] 正在更新 Cascade Panel 配置..."
     if [ -f "$PATCHES_DIR/cascade-panel/config.json" ] && [ -d "$TARGET_DIR_1/cascade-panel" ]; then
         cp "$PATCHES_DIR/cascade-panel/config.json" "$TARGET_DIR_1/cascade-panel/"
+        if [ -d "$PATCHES_DIR/cascade-panel/vendor" ]; then
+            rm -rf "$TARGET_DIR_1/cascade-panel/vendor"
+            cp -r "$PATCHES_DIR/cascade-panel/vendor" "$TARGET_DIR_1/cascade-panel/"
+            echo "已同步 cascade-panel/vendor 离线资源"
+        fi
+        rm -rf "$TARGET_DIR_1/cascade-panel/user"
+        if [ -d "$PATCHES_DIR/cascade-panel/user" ]; then
+            cp -r "$PATCHES_DIR/cascade-panel/user" "$TARGET_DIR_1/cascade-panel/"
+            echo "已同步 cascade-panel/user 用户覆盖层"
+        fi
         echo "已更新 cascade-panel/config.json"
     else
         echo "警告: 未找到 cascade-panel 配置或目标目录不存在"
@@ -239,12 +249,110 @@ update_configs_only() {
     echo -e "\n[2/2] 正在更新 Manager 配置..."
     if [ -f "$PATCHES_DIR/manager-panel/config.json" ] && [ -d "$TARGET_DIR_2/manager-panel" ]; then
         cp "$PATCHES_DIR/manager-panel/config.json" "$TARGET_DIR_2/manager-panel/"
+        if [ -d "$PATCHES_DIR/manager-panel/vendor" ]; then
+            rm -rf "$TARGET_DIR_2/manager-panel/vendor"
+            cp -r "$PATCHES_DIR/manager-panel/vendor" "$TARGET_DIR_2/manager-panel/"
+            echo "已同步 manager-panel/vendor 离线资源"
+        fi
+        rm -rf "$TARGET_DIR_2/manager-panel/user"
+        if [ -d "$PATCHES_DIR/manager-panel/user" ]; then
+            cp -r "$PATCHES_DIR/manager-panel/user" "$TARGET_DIR_2/manager-panel/"
+            echo "已同步 manager-panel/user 用户覆盖层"
+        fi
         echo "已更新 manager-panel/config.json"
     else
         echo "警告: 未找到 manager-panel 配置或目标目录不存在"
     fi
 }
 
+# 文件操作的目标只能位于 --app-path 内，或为同级的 app.asar、app.asar.bak 与安装根目录的 Info.plist
+is_allowed_target() {
+    case "$1" in
+        /*) ;;
+        *) return 1 ;;
+    esac
+    case "/$1/" in
+        */../*|*/./*) return 1 ;;
+    esac
+    local RESOURCES_DIR
+    RESOURCES_DIR="$(dirname "$APP_PATH")"
+    case "$1" in
+        "$APP_PATH"/*|"$RESOURCES_DIR/app.asar"|"$RESOURCES_DIR/app.asar.bak"|"$(dirname "$RESOURCES_DIR")/Info.plist")
+            return 0
+            ;;
+    esac
+    return 1
+}
+
+# 复制源只能位于暂存目录 (本脚本所在目录) 内，或为允许的目标 (如备份 app.asar)
+is_allowed_source() {
+    case "/$1/" in
+        */../*|*/./*) return 1 ;;
+    esac
+    case "$1" in
+        "$PATCHES_DIR"/*) return 0 ;;
+    esac
+    is_allowed_target "$1"
+}
+
+# 执行暂存的文件操作 (app.asar 写回、卸载恢复、回滚)
+# file-ops.txt 每行以制表符分隔: copy<TAB>源文件<TAB>目标、remove<TAB>目标 或 remove-dir<TAB>目标
+apply_file_ops() {
+    echo -e "\n正在执行暂存的文件操作..."
+    OPS_FILE="$PATCHES_DIR/file-ops.txt"
+    if [ ! -f "$OPS_FILE" ]; then
+        echo "错误: 找不到操作列表: $OPS_FILE"
+        exit 1
+    fi
+
+    # 先校验全部操作，任何一项不符合时都不执行
+    while IFS=$'\t' read -r OP SOURCE TARGET; do
+        case "$OP" in
+            copy)
+                if ! is_allowed_source "$SOURCE" || ! is_allowed_target "$TARGET"; then
+                    echo "错误: 拒绝安装目录以外的文件操作: $SOURCE -> $TARGET"
+                    exit 1
+                fi
+                ;;
+            remove|remove-dir)
+                if ! is_allowed_target "$SOURCE"; then
+                    echo "错误: 拒绝安装目录以外的文件操作: $SOURCE"
+                    exit 1
+                fi
+                ;;
+            *)
+                echo "错误: 未知的文件操作: $OP"
+                exit 1
+                ;;
+        esac
+    done < "$OPS_FILE"
+
+    while IFS=$'\t' read -r OP SOURCE TARGET; do
+        case "$OP" in
+            copy)
+                echo "正在写入 $TARGET"
+                mkdir -p "$(dirname "$TARGET")"
+                # 先写目标目录中的临时文件，再重命名覆盖 (保持原文件权限)
+                if [ -e "$TARGET" ]; then
+                    cp -p "$TARGET" "$TARGET.anti-power.tmp"
+                    cat "$SOURCE" > "$TARGET.anti-power.tmp"
+                else
+                    cp "$SOURCE" "$TARGET.anti-power.tmp"
+                fi
+                mv -f "$TARGET.anti-power.tmp" "$TARGET"
+                ;;
+            remove)
+                echo "正在删除 $SOURCE"
+                rm -f "$SOURCE"
+                ;;
+            remove-dir)
+                echo "正在删除 $SOURCE"
+                rm -rf "$SOURCE"
+                ;;
+        esac
+    done < "$OPS_FILE"
+}
+
 case "$MODE" in
     uninstall)
         restore_cascade
@@ -253,6 +361,9 @@ case "$MODE" in
     update-config)
         update_configs_only
         ;;
+    apply-files)
+        apply_file_ops
+        ;;
     install|*)
         if [ "$CASCADE_ENABLED" = "true" ]; then
             install_cascade
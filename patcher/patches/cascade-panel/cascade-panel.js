@@ -1,9 +1,12 @@
 /**
  * Anti-Power 补丁入口
  * 根据配置文件动态加载功能模块
+ *
+ * 注意：constants.js 在首次导入时读取 CDN 覆盖地址，
+ * 因此所有功能模块都需在应用配置后再动态导入。
  */
 
-import { loadStyle } from './utils.js';
+const TABLE_FIX_HREF = './cascade-panel/table-fix.css';
 
 const DEFAULT_CONFIG = {
     mermaid: true,
@@ -67,12 +70,91 @@ const applyFontSize = (userConfig) => {
 /**
  * 动态加载表格修复样式
  *
+ * 已加载过时只切换启用状态，供配置热重载关闭后再次开启。
+ *
  * @returns {void}
  */
 const loadTableFix = () => {
-    void loadStyle('./cascade-panel/table-fix.css').catch((error) => {
-        console.warn('[Cascade] 表格样式加载失败:', error);
-    });
+    const existing = document.querySelector(`link[href="${TABLE_FIX_HREF}"]`);
+    if (existing) {
+        existing.disabled = false;
+        return;
+    }
+    void import('./utils.js')
+        .then(({ loadStyle }) => loadStyle(TABLE_FIX_HREF))
+        .catch((error) => {
+            console.warn('[Cascade] 表格样式加载失败:', error);
+        });
+};
+
+/**
+ * 应用第三方库地址配置
+ *
+ * 离线渲染时 config.json 中包含本地 vendor 地址，
+ * 写入全局变量供 constants.js 读取（不覆盖已有的手动设置）。
+ *
+ * @param {Object} userConfig - 用户配置对象
+ * @returns {void}
+ */
+const applyRenderUrls = (userConfig) => {
+    if (userConfig.katexBaseUrl && !window.CASCADE_KATEX_BASE_URL) {
+        window.CASCADE_KATEX_BASE_URL = userConfig.katexBaseUrl;
+    }
+    if (userConfig.mathjaxUrl && !window.CASCADE_MATHJAX_URL) {
+        window.CASCADE_MATHJAX_URL = userConfig.mathjaxUrl;
+    }
+    if (userConfig.mermaidUrl && !window.CASCADE_MERMAID_URL) {
+        window.CASCADE_MERMAID_URL = userConfig.mermaidUrl;
+    }
+};
+
+/**
+ * 加载用户覆盖层
+ *
+ * 覆盖层由补丁程序从用户配置目录部署到 user/，
+ * 在内置功能之后加载，便于覆盖默认样式。
+ *
+ * @param {Object} userConfig - 用户配置对象
+ * @returns {void}
+ */
+const loadUserOverlay = (userConfig) => {
+    if (userConfig.userCss) {
+        void import('./utils.js')
+            .then(({ loadStyle }) => loadStyle('./cascade-panel/user/sidebar.css'))
+            .catch((error) => {
+                console.warn('[Cascade] 用户样式加载失败:', error);
+            });
+    }
+    if (userConfig.userJs) {
+        void import('./user/sidebar.js').catch((error) => {
+            console.warn('[Cascade] 用户脚本加载失败:', error);
+        });
+    }
+};
+
+/**
+ * 重新读取配置并应用
+ *
+ * 补丁程序更新配置后通过调试端口调用，无需重启 Antigravity，
+ * 对话内容保持不变。第三方库地址与用户覆盖层仍需重新加载页面。
+ *
+ * @returns {Promise<Object>} 新的配置对象
+ */
+const reloadConfig = async () => {
+    const config = await loadConfig();
+    window.__CASCADE_CONFIG__ = config;
+
+    applyFontSize(config);
+    if (config.tableColor) {
+        loadTableFix();
+    } else {
+        const tableFix = document.querySelector(`link[href="${TABLE_FIX_HREF}"]`);
+        if (tableFix) tableFix.disabled = true;
+    }
+
+    const { applyConfig } = await import('./scan.js');
+    await applyConfig(config);
+    return config;
 };
 
 /**
@@ -87,6 +169,7 @@ const loadTableFix = () => {
     window.__CASCADE_CONFIG__ = config;
 
     applyFontSize(config);
+    applyRenderUrls(config);
 
     // 表格颜色修复（CSS 动态加载）
     if (config.tableColor) {
@@ -96,4 +179,8 @@ const loadTableFix = () => {
     // 启动扫描模块，传入配置
     const { start } = await import('./scan.js');
     start(config);
+
+    loadUserOverlay(config);
+
+    window.__CASCADE_RELOAD_CONFIG__ = reloadConfig;
 })();
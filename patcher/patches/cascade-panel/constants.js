@@ -7,6 +7,7 @@
  * - 通用语言集合
  *
  * 支持通过全局变量覆盖 CDN 地址以适应离线或镜像环境。
+ * 启用离线渲染时，入口脚本会根据 config.json 将其指向 vendor/ 下的本地副本。
  */
 
 // 内容扫描的选择器，控制功能覆盖的 DOM 范围
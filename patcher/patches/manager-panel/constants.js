@@ -9,6 +9,7 @@
  * - 按钮样式类名
  *
  * 支持通过全局变量覆盖 CDN 地址以适应离线或镜像环境。
+ * 启用离线渲染时，入口脚本会根据 config.json 将其指向 vendor/ 下的本地副本。
  */
 
 // Manager 内容区选择器（基于 DOM 分析结果）
@@ -128,6 +128,73 @@ const applyMaxWidth = (userConfig) => {
     root.style.setProperty('--manager-panel-max-width-ratio', String(clamped));
 };
 
+/**
+ * 应用第三方库地址配置
+ *
+ * 离线渲染时 config.json 中包含本地 vendor 地址，
+ * 写入全局变量供 constants.js 读取（不覆盖已有的手动设置）。
+ *
+ * @param {Object} userConfig - 用户配置对象
+ * @returns {void}
+ */
+const applyRenderUrls = (userConfig) => {
+    if (userConfig.katexBaseUrl && !window.MANAGER_KATEX_BASE_URL) {
+        window.MANAGER_KATEX_BASE_URL = userConfig.katexBaseUrl;
+    }
+    if (userConfig.mathjaxUrl && !window.MANAGER_MATHJAX_URL) {
+        window.MANAGER_MATHJAX_URL = userConfig.mathjaxUrl;
+    }
+    if (userConfig.mermaidUrl && !window.MANAGER_MERMAID_URL) {
+        window.MANAGER_MERMAID_URL = userConfig.mermaidUrl;
+    }
+};
+
+/**
+ * 加载用户覆盖层
+ *
+ * 覆盖层由补丁程序从用户配置目录部署到 user/，
+ * 在内置功能之后加载，便于覆盖默认样式。
+ *
+ * @param {Object} userConfig - 用户配置对象
+ * @returns {Promise<void>}
+ */
+const loadUserOverlay = async (userConfig) => {
+    if (userConfig.userCss) {
+        try {
+            await loadStyle('user/manager.css');
+        } catch (err) {
+            console.warn('[Manager Panel] 用户样式加载失败:', err);
+        }
+    }
+    if (userConfig.userJs) {
+        try {
+            await import('./user/manager.js');
+        } catch (err) {
+            console.warn('[Manager Panel] 用户脚本加载失败:', err);
+        }
+    }
+};
+
+/**
+ * 重新读取配置并应用
+ *
+ * 补丁程序更新配置后通过调试端口调用，无需重启 Antigravity，
+ * 对话内容保持不变。第三方库地址与用户覆盖层仍需重新加载页面。
+ *
+ * @returns {Promise<Object>} 新的配置对象
+ */
+const reloadConfig = async () => {
+    const config = await loadConfig();
+    window.__MANAGER_CONFIG__ = config;
+
+    applyFontSize(config);
+    applyMaxWidth(config);
+
+    const { applyConfig } = await import('./scan.js');
+    await applyConfig(config);
+    return config;
+};
+
 /**
  * 补丁入口
  *
@@ -151,10 +218,15 @@ const applyMaxWidth = (userConfig) => {
 
     applyFontSize(config);
     applyMaxWidth(config);
+    applyRenderUrls(config);
 
     // 启动扫描
     const { start } = await import('./scan.js');
     start(config);
 
+    await loadUserOverlay(config);
+
+    window.__MANAGER_RELOAD_CONFIG__ = reloadConfig;
+
     console.log('[Manager Panel] 补丁已启动', config);
 })();
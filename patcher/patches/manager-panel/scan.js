@@ -9,12 +9,16 @@
  *
  * 扫描策略：
  * - 使用 MutationObserver 监听 DOM 变更
+ *
+ * 功能模块按配置动态加载：未启用的模块不会被安装器部署（见 modules.json）。
  */
 
 import { CONTENT_SELECTOR, SECTION_SELECTOR } from './constants.js';
-import { ensureContentCopyButton, addFeedbackCopyButtons } from './copy.js';
-import { renderMath } from './math.js';
-import { scanMermaid } from './mermaid.js';
+
+let ensureContentCopyButton = () => {};
+let addFeedbackCopyButtons = () => {};
+let renderMath = async () => {};
+let scanMermaid = () => {};
 
 /**
  * 功能配置
@@ -25,6 +29,47 @@ let config = {
     copyButton: true,
 };
 
+/**
+ * 按配置加载功能模块
+ *
+ * 加载失败的模块视为未启用，不影响其他功能。
+ *
+ * @returns {Promise<void>}
+ */
+const loadFeatureModules = async () => {
+    const load = async (enabled, path) => {
+        if (!enabled) return null;
+        try {
+            return await import(path);
+        } catch (err) {
+            console.warn(`[Manager Panel] 功能模块加载失败: ${path}`, err);
+            return null;
+        }
+    };
+
+    const [copy, math, mermaid] = await Promise.all([
+        load(config.copyButton, './copy.js'),
+        load(config.math, './math.js'),
+        load(config.mermaid, './mermaid.js'),
+    ]);
+
+    if (copy) {
+        ({ ensureContentCopyButton, addFeedbackCopyButtons } = copy);
+    } else {
+        config.copyButton = false;
+    }
+    if (math) {
+        ({ renderMath } = math);
+    } else {
+        config.math = false;
+    }
+    if (mermaid) {
+        ({ scanMermaid } = mermaid);
+    } else {
+        config.mermaid = false;
+    }
+};
+
 /**
  * 扫描根节点
  *
@@ -165,17 +210,39 @@ const init = () => {
     console.log('[Manager Panel] 扫描模块已启动');
 };
 
+/**
+ * 应用新的配置
+ *
+ * 配置热重载时由入口调用：加载新启用的功能模块并重新扫描。
+ * 已渲染的内容不会撤销，关闭的功能只对之后的内容生效。
+ *
+ * @param {Object} [userConfig={}] - 用户配置
+ * @returns {Promise<void>}
+ */
+export const applyConfig = async (userConfig = {}) => {
+    config = { ...config, ...userConfig };
+
+    await loadFeatureModules();
+
+    scanClassic(getRoot());
+    if (config.copyButton) {
+        addFeedbackCopyButtons();
+    }
+};
+
 /**
  * 模块入口
  *
  * 接收配置并启动扫描模块。
  *
  * @param {Object} [userConfig={}] - 用户配置
- * @returns {void}
+ * @returns {Promise<void>}
  */
-export const start = (userConfig = {}) => {
+export const start = async (userConfig = {}) => {
     config = { ...config, ...userConfig };
 
+    await loadFeatureModules();
+
     if (document.readyState === 'loading') {
         document.addEventListener('DOMContentLoaded', init);
     } else {
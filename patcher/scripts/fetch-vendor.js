@@ -0,0 +1,158 @@
+/**
+ * 下载离线渲染所需的第三方库到 patches/vendor.
+ *
+ * build.rs 会将 patches/vendor 下的文件 (含字体) 嵌入补丁程序,
+ * 启用 "离线渲染" 后安装器会把它们部署到 cascade-panel/vendor 与 manager-panel/vendor.
+ *
+ * 使用方法 (需要 Node.js 18+):
+ *   npm run vendor:fetch                  按 vendor.lock 下载并校验, vendor.lock 不存在时失败
+ *   npm run vendor:fetch -- --update-lock 首次锁定或升级版本后重新生成 vendor.lock
+ *
+ * 每个文件都与 jsDelivr 公布的 SHA-256 比对; vendor.lock (sha256sum 格式) 锁定文件列表与哈希,
+ * build.rs 构建时按它校验. 生成后请提交 vendor.lock.
+ *
+ * 版本需与 cascade-panel/constants.js、manager-panel/constants.js 中的 CDN 版本保持一致.
+ */
+
+import { createHash } from 'node:crypto';
+import { mkdir, readFile, rm, writeFile } from 'node:fs/promises';
+import { dirname, join } from 'node:path';
+import { fileURLToPath } from 'node:url';
+
+const VENDOR_DIR = join(dirname(fileURLToPath(import.meta.url)), '..', 'patches', 'vendor');
+const LOCK_PATH = join(VENDOR_DIR, 'vendor.lock');
+
+// 每个库: npm 包名@版本, 需要的文件前缀, 以及在 vendor/ 下的目标目录
+const PACKAGES = [
+    {
+        name: 'katex',
+        version: '0.16.9',
+        target: 'katex',
+        root: '/dist',
+        include: [
+            /^\/dist\/katex\.min\.css$/,
+            /^\/dist\/katex\.min\.js$/,
+            /^\/dist\/contrib\/auto-render\.min\.js$/,
+            /^\/dist\/fonts\/.+\.woff2$/,
+        ],
+    },
+    {
+        name: 'mathjax',
+        version: '3.2.2',
+        target: 'mathjax',
+        root: '/es5',
+        include: [/^\/es5\/tex-mml-chtml\.js$/, /^\/es5\/output\/chtml\/fonts\/woff-v2\/.+\.woff$/],
+    },
+    {
+        name: 'mermaid',
+        version: '10.9.0',
+        target: 'mermaid',
+        root: '/dist',
+        include: [/^\/dist\/mermaid\.min\.js$/],
+    },
+];
+
+/**
+ * 获取 npm 包的文件清单 (jsDelivr data API)
+ *
+ * @param {string} name - 包名
+ * @param {string} version - 版本号
+ * @returns {Promise<{ name: string, hash: string }[]>} 以 / 开头的文件路径与 base64 编码的 SHA-256
+ */
+const listFiles = async (name, version) => {
+    const res = await fetch(`https://data.jsdelivr.com/v1/packages/npm/${name}@${version}?structure=flat`);
+    if (!res.ok) {
+        throw new Error(`获取 ${name}@${version} 文件清单失败: ${res.status}`);
+    }
+    const data = await res.json();
+    return data.files.map((file) => ({ name: file.name, hash: file.hash }));
+};
+
+/**
+ * 读取 vendor.lock
+ *
+ * @returns {Promise<Map<string, string> | null>} 相对 vendor/ 的路径 -> 十六进制 SHA-256, 不存在时为 null
+ */
+const readLock = async () => {
+    let content;
+    try {
+        content = await readFile(LOCK_PATH, 'utf8');
+    } catch {
+        return null;
+    }
+    const lock = new Map();
+    for (const line of content.split('\n').filter((line) => line.trim())) {
+        const [hash, path] = line.split('  ');
+        lock.set(path, hash);
+    }
+    return lock;
+};
+
+/**
+ * 下载单个文件
+ *
+ * @param {string} url - 文件地址
+ * @param {string} dest - 本地路径
+ * @returns {Promise<string>} 内容的十六进制 SHA-256
+ */
+const download = async (url, dest) => {
+    const res = await fetch(url);
+    if (!res.ok) {
+        throw new Error(`下载失败 ${url}: ${res.status}`);
+    }
+    const content = Buffer.from(await res.arrayBuffer());
+    await mkdir(dirname(dest), { recursive: true });
+    await writeFile(dest, content);
+    return createHash('sha256').update(content).digest('hex');
+};
+
+async function main() {
+    const updateLock = process.argv.includes('--update-lock');
+    const lock = updateLock ? null : await readLock();
+    if (!updateLock && !lock) {
+        // 不从本次下载的内容生成锁定文件, 否则 CI 下载什么就锁定什么
+        throw new Error(`缺少 ${LOCK_PATH}, 请提交仓库中的 vendor.lock, 首次锁定时使用 --update-lock`);
+    }
+    const fetched = new Map();
+
+    for (const pkg of PACKAGES) {
+        const targetDir = join(VENDOR_DIR, pkg.target);
+        await rm(targetDir, { recursive: true, force: true });
+
+        const files = (await listFiles(pkg.name, pkg.version)).filter((file) =>
+            pkg.include.some((re) => re.test(file.name))
+        );
+        console.log(`📦 ${pkg.name}@${pkg.version}: ${files.length} 个文件`);
+
+        for (const file of files) {
+            const url = `https://cdn.jsdelivr.net/npm/${pkg.name}@${pkg.version}${file.name}`;
+            const path = `${pkg.target}${file.name.slice(pkg.root.length)}`;
+            const hash = await download(url, join(VENDOR_DIR, path));
+            if (Buffer.from(hash, 'hex').toString('base64') !== file.hash) {
+                throw new Error(`${path} 与 jsDelivr 公布的哈希不一致`);
+            }
+            if (lock && lock.get(path) !== hash) {
+                throw new Error(`${path} 与 vendor.lock 不一致, 升级版本时使用 --update-lock`);
+            }
+            fetched.set(path, hash);
+        }
+    }
+
+    if (lock) {
+        const missing = [...lock.keys()].filter((path) => !fetched.has(path));
+        if (missing.length) {
+            throw new Error(`vendor.lock 中的文件未下载: ${missing.join(', ')}`);
+        }
+    } else {
+        const lines = [...fetched.keys()].sort().map((path) => `${fetched.get(path)}  ${path}\n`);
+        await writeFile(LOCK_PATH, lines.join(''));
+        console.log(`🔒 已生成 ${LOCK_PATH}，请提交该文件`);
+    }
+
+    console.log(`✅ 已写入 ${VENDOR_DIR}，重新构建补丁程序后生效`);
+}
+
+main().catch((error) => {
+    console.error('❌', error.message);
+    process.exit(1);
+});
@@ -0,0 +1,104 @@
+/**
+ * 补丁包制作工具: 生成签名密钥, 填写 pack.json 的文件清单并签名.
+ *
+ * 使用方法 (需要 Node.js 18+):
+ *   node scripts/pack-tool.js keygen <私钥输出路径>
+ *   node scripts/pack-tool.js manifest <补丁包目录>
+ *   node scripts/pack-tool.js sign <补丁包目录> <私钥路径>
+ *
+ * keygen 输出的公钥需要在安装器的 "补丁来源" 中添加为受信任公钥.
+ * 修改 pack.json 或任何补丁文件后都需要重新执行 manifest 与 sign.
+ */
+
+import { createHash, createPrivateKey, createPublicKey, generateKeyPairSync, sign } from 'node:crypto';
+import { readdir, readFile, writeFile } from 'node:fs/promises';
+import { join, relative, sep } from 'node:path';
+
+const MANIFEST_FILE = 'pack.json';
+const SIGNATURE_FILE = 'pack.json.sig';
+
+/**
+ * 提取 ed25519 公钥的 32 字节原始值 (base64)
+ *
+ * @param {import('node:crypto').KeyObject} publicKey - 公钥
+ * @returns {string} base64 编码的公钥
+ */
+const rawPublicKey = (publicKey) => {
+    const der = publicKey.export({ type: 'spki', format: 'der' });
+    return der.subarray(der.length - 32).toString('base64');
+};
+
+/**
+ * 递归列出目录下的文件 (相对路径, / 分隔)
+ *
+ * @param {string} root - 补丁包目录
+ * @param {string} [dir] - 当前目录
+ * @returns {Promise<string[]>} 文件列表
+ */
+const listFiles = async (root, dir = root) => {
+    const files = [];
+    for (const entry of await readdir(dir, { withFileTypes: true })) {
+        const path = join(dir, entry.name);
+        if (entry.isDirectory()) {
+            files.push(...(await listFiles(root, path)));
+        } else if (entry.isFile()) {
+            files.push(relative(root, path).split(sep).join('/'));
+        }
+    }
+    return files;
+};
+
+const keygen = async (keyPath) => {
+    const { publicKey, privateKey } = generateKeyPairSync('ed25519');
+    await writeFile(keyPath, privateKey.export({ type: 'pkcs8', format: 'pem' }), { mode: 0o600 });
+    console.log(`🔑 私钥已写入 ${keyPath}`);
+    console.log(`公钥: ${rawPublicKey(publicKey)}`);
+};
+
+const manifest = async (packDir) => {
+    const manifestPath = join(packDir, MANIFEST_FILE);
+    const pack = JSON.parse(await readFile(manifestPath, 'utf8'));
+
+    const paths = (await listFiles(packDir))
+        .filter((path) => path !== MANIFEST_FILE && path !== SIGNATURE_FILE)
+        .sort();
+    pack.files = [];
+    for (const path of paths) {
+        const content = await readFile(join(packDir, path));
+        pack.files.push({
+            path,
+            sha256: createHash('sha256').update(content).digest('hex'),
+            size: content.length,
+        });
+    }
+
+    await writeFile(manifestPath, `${JSON.stringify(pack, null, 4)}\n`);
+    console.log(`📦 已写入 ${pack.files.length} 个文件到 ${manifestPath}`);
+};
+
+const signPack = async (packDir, keyPath) => {
+    const privateKey = createPrivateKey(await readFile(keyPath));
+    const content = await readFile(join(packDir, MANIFEST_FILE));
+    const signature = sign(null, content, privateKey).toString('base64');
+
+    await writeFile(join(packDir, SIGNATURE_FILE), `${signature}\n`);
+    console.log(`✅ 已签名, 公钥: ${rawPublicKey(createPublicKey(privateKey))}`);
+};
+
+async function main() {
+    const [command, ...args] = process.argv.slice(2);
+    if (command === 'keygen' && args.length === 1) {
+        await keygen(args[0]);
+    } else if (command === 'manifest' && args.length === 1) {
+        await manifest(args[0]);
+    } else if (command === 'sign' && args.length === 2) {
+        await signPack(args[0], args[1]);
+    } else {
+        throw new Error('用法: pack-tool.js keygen <key> | manifest <dir> | sign <dir> <key>');
+    }
+}
+
+main().catch((error) => {
+    console.error('❌', error.message);
+    process.exit(1);
+});
@@ -1,13 +1,38 @@
 //! 构建脚本
 //!
 //! 在编译时将补丁文件嵌入到二进制中
+//! 所有文件均以字节形式嵌入，较大的文件在构建时压缩
+//! 同时生成补丁集清单（版本号、各文件哈希与整体摘要）
 
+use flate2::{write::GzEncoder, Compression};
+use sha2::{Digest, Sha256};
 use std::{
     collections::HashSet,
     env, fs,
+    io::Write,
     path::{Path, PathBuf},
 };
 
+#[path = "src/embed_exclude.rs"]
+mod embed_exclude;
+
+use embed_exclude::{is_excluded, read_exclude_list, EXCLUDE_FILE};
+
+/// 超过该大小的文件在构建时进行 gzip 压缩
+const COMPRESS_THRESHOLD: usize = 32 * 1024;
+
+/// 离线渲染必需的第三方库文件 (相对 patches/vendor)
+const REQUIRED_VENDOR_FILES: &[&str] = &[
+    "katex/katex.min.css",
+    "katex/katex.min.js",
+    "katex/contrib/auto-render.min.js",
+    "mathjax/tex-mml-chtml.js",
+    "mermaid/mermaid.min.js",
+];
+
+/// 第三方库的哈希锁定文件 (sha256sum 格式，由 scripts/fetch-vendor.js 生成)
+const VENDOR_LOCK: &str = "vendor.lock";
+
 /// 构建入口
 fn main() {
     tauri_build::build();
@@ -15,15 +40,26 @@ fn main() {
     let manifest_dir =
         PathBuf::from(env::var("CARGO_MANIFEST_DIR").expect("Missing CARGO_MANIFEST_DIR"));
     let patches_dir = manifest_dir.join("..").join("patches");
-    let exclude_path = patches_dir.join(".embed-exclude.txt");
+    let exclude_path = patches_dir.join(EXCLUDE_FILE);
 
     let exclude = read_exclude_list(&exclude_path);
     let mut files = Vec::new();
     collect_patch_files(&patches_dir, &patches_dir, &exclude, &mut files);
     files.sort();
+    if !verify_vendor(&patches_dir.join("vendor"), &files) {
+        // 不完整的第三方库不嵌入，补丁程序关闭离线渲染
+        files.retain(|rel| !rel.starts_with("vendor/"));
+    }
 
-    let output = render_embedded_list(&files);
     let out_dir = PathBuf::from(env::var("OUT_DIR").expect("Missing OUT_DIR"));
+    let compressed_dir = out_dir.join("compressed");
+    let entries: Vec<EmbedEntry> = files
+        .iter()
+        .map(|rel| prepare_entry(&patches_dir, &compressed_dir, rel))
+        .collect();
+
+    let mut output = render_embedded_list(&entries);
+    output.push_str(&render_manifest(&entries));
     let out_path = out_dir.join("embedded_patches.rs");
     fs::write(&out_path, output).expect("Failed to write embedded patches list");
 
@@ -31,28 +67,55 @@ fn main() {
     println!("cargo:rerun-if-changed={}", exclude_path.display());
 }
 
-/// 读取排除列表文件
-/// 返回不需要嵌入的文件路径集合
-fn read_exclude_list(path: &Path) -> HashSet<String> {
-    let mut entries = HashSet::new();
-    // 排除列表文件本身不需要嵌入
-    entries.insert(".embed-exclude.txt".to_string());
+/// 校验第三方库: 必需文件齐全，vendor.lock 中的文件与记录的 SHA-256 一致，
+/// 且嵌入的 vendor 文件都已记录。缺少必需文件时给出警告并返回 false，
+/// 缺少 vendor.lock 或文件不一致时构建失败
+fn verify_vendor(vendor_dir: &Path, files: &[String]) -> bool {
+    let missing: Vec<&str> = REQUIRED_VENDOR_FILES
+        .iter()
+        .copied()
+        .filter(|rel| !vendor_dir.join(rel).is_file())
+        .collect();
+    if !missing.is_empty() {
+        println!(
+            "cargo:warning=Offline rendering assets are missing from patches/vendor ({}); offline rendering is disabled in this build. Run `npm run vendor:fetch` in patcher/",
+            missing.join(", ")
+        );
+        return false;
+    }
 
-    let content = match fs::read_to_string(path) {
-        Ok(content) => content,
-        Err(_) => return entries,
+    let lock_path = vendor_dir.join(VENDOR_LOCK);
+    println!("cargo:rerun-if-changed={}", lock_path.display());
+    let Ok(lock) = fs::read_to_string(&lock_path) else {
+        panic!(
+            "patches/vendor/{} is missing. Run `npm run vendor:fetch` in patcher/ and commit the generated lock file",
+            VENDOR_LOCK
+        );
     };
 
-    for line in content.lines() {
-        let trimmed = line.trim();
-        // 跳过空行和注释
-        if trimmed.is_empty() || trimmed.starts_with('#') {
-            continue;
+    let mut locked = HashSet::new();
+    for line in lock.lines().filter(|line| !line.trim().is_empty()) {
+        let (hash, rel) = line
+            .split_once("  ")
+            .unwrap_or_else(|| panic!("Invalid line in {}: {}", VENDOR_LOCK, line));
+        let content = fs::read(vendor_dir.join(rel)).unwrap_or_else(|_| {
+            panic!("Vendor file listed in {} is missing: {}", VENDOR_LOCK, rel)
+        });
+        if to_hex(&Sha256::digest(&content)) != hash {
+            panic!(
+                "Vendor file does not match {}: {}. Run `npm run vendor:fetch` in patcher/",
+                VENDOR_LOCK, rel
+            );
         }
-        entries.insert(trimmed.replace('\\', "/"));
+        locked.insert(rel.to_string());
     }
 
-    entries
+    for rel in files.iter().filter_map(|rel| rel.strip_prefix("vendor/")) {
+        if !locked.contains(rel) {
+            panic!("Vendor file is not listed in {}: {}", VENDOR_LOCK, rel);
+        }
+    }
+    true
 }
 
 /// 递归收集补丁文件
@@ -103,31 +166,108 @@ fn normalize_path(path: &Path) -> String {
         .join("/")
 }
 
-/// 检查路径是否在排除列表中
-fn is_excluded(path: &str, exclude: &HashSet<String>) -> bool {
-    // 精确匹配
-    if exclude.contains(path) {
-        return true;
+/// 待嵌入的文件
+struct EmbedEntry {
+    /// 相对 patches 目录的路径
+    rel: String,
+    /// 原始内容的 SHA-256（十六进制）
+    sha256: String,
+    /// 原始内容大小（字节）
+    size: usize,
+    /// 是否为 gzip 压缩内容（压缩结果位于 OUT_DIR/compressed）
+    compressed: bool,
+}
+
+/// 准备单个嵌入文件
+/// 超过阈值且压缩后更小的文件写入 OUT_DIR/compressed 并嵌入压缩结果
+fn prepare_entry(patches_dir: &Path, compressed_dir: &Path, rel: &str) -> EmbedEntry {
+    let source = patches_dir.join(rel);
+    let content = fs::read(&source).expect("Failed to read patch file");
+    let sha256 = to_hex(&Sha256::digest(&content));
+    let size = content.len();
+
+    if content.len() > COMPRESS_THRESHOLD {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+        encoder
+            .write_all(&content)
+            .expect("Failed to compress patch file");
+        let compressed = encoder.finish().expect("Failed to compress patch file");
+
+        if compressed.len() < content.len() {
+            let target = compressed_dir.join(format!("{}.gz", rel));
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent).expect("Failed to create compressed dir");
+            }
+            fs::write(&target, compressed).expect("Failed to write compressed patch file");
+            return EmbedEntry {
+                rel: rel.to_string(),
+                sha256,
+                size,
+                compressed: true,
+            };
+        }
     }
 
-    // 目录前缀匹配
-    exclude.iter().any(|entry| {
-        let entry = entry.trim_end_matches('/');
-        path == entry || path.starts_with(&format!("{}/", entry))
-    })
+    EmbedEntry {
+        rel: rel.to_string(),
+        sha256,
+        size,
+        compressed: false,
+    }
 }
 
 /// 生成嵌入文件列表的 Rust 代码
-fn render_embedded_list(files: &[String]) -> String {
+fn render_embedded_list(entries: &[EmbedEntry]) -> String {
     let mut output = String::new();
     output.push_str("// Auto-generated by build.rs. Do not edit manually.\n");
-    output.push_str("pub const EMBEDDED_FILES: &[(&str, &str)] = &[\n");
-    for rel in files {
+    output.push_str("pub const EMBEDDED_FILES: &[EmbeddedFile] = &[\n");
+    for entry in entries {
+        let source = if entry.compressed {
+            format!(
+                "concat!(env!(\"OUT_DIR\"), \"/compressed/{}.gz\")",
+                entry.rel
+            )
+        } else {
+            format!(
+                "concat!(env!(\"CARGO_MANIFEST_DIR\"), \"/../patches/{}\")",
+                entry.rel
+            )
+        };
         output.push_str(&format!(
-            "    (\"{}\", include_str!(concat!(env!(\"CARGO_MANIFEST_DIR\"), \"/../patches/{}\"))),\n",
-            rel, rel
+            "    EmbeddedFile {{ path: \"{}\", content: include_bytes!({}), compressed: {}, sha256: \"{}\", size: {} }},\n",
+            entry.rel, source, entry.compressed, entry.sha256, entry.size
         ));
     }
     output.push_str("];\n");
     output
 }
+
+/// 生成补丁集清单常量
+/// 整体摘要按路径顺序对 "路径\0哈希\n" 计算 SHA-256，任一文件变化都会改变摘要
+fn render_manifest(entries: &[EmbedEntry]) -> String {
+    let mut hasher = Sha256::new();
+    for entry in entries {
+        hasher.update(entry.rel.as_bytes());
+        hasher.update([0]);
+        hasher.update(entry.sha256.as_bytes());
+        hasher.update(b"\n");
+    }
+    let digest = to_hex(&hasher.finalize());
+    let version = env::var("CARGO_PKG_VERSION").expect("Missing CARGO_PKG_VERSION");
+
+    let mut output = String::new();
+    output.push_str(&format!(
+        "pub const PATCH_SET_VERSION: &str = \"{}\";\n",
+        version
+    ));
+    output.push_str(&format!(
+        "pub const PATCH_SET_DIGEST: &str = \"{}\";\n",
+        digest
+    ));
+    output
+}
+
+/// 字节转十六进制字符串
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
@@ -0,0 +1,425 @@
+//! AppImage 支持
+//!
+//! AppImage 是只读的 squashfs 镜像，补丁操作时先解包到应用数据目录，
+//! 按普通安装目录修改后重新打包 (需要 appimagetool)，或在 AppImage 旁生成
+//! 启动解包目录的启动脚本。原始 AppImage 保留到卸载
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::UNIX_EPOCH;
+
+use super::config;
+use super::doctor::command_exists;
+use super::oplog;
+
+/// 解包目录中记录来源 AppImage 的文件
+const SOURCE_FILE: &str = "source.json";
+
+/// AppImage 运行时解包出的目录名
+const EXTRACT_DIR: &str = "squashfs-root";
+
+/// 重新打包使用的工具
+const APPIMAGETOOL: &str = "appimagetool";
+
+fn other(message: impl Into<String>) -> io::Error {
+    io::Error::other(message.into())
+}
+
+/// 重新打包后 AppImage 被替换为其他内容，原文件备份仍在
+#[derive(Debug)]
+pub(super) struct Replaced {
+    pub backup: PathBuf,
+}
+
+impl std::fmt::Display for Replaced {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "AppImage was replaced, backup kept at {}",
+            self.backup.display()
+        )
+    }
+}
+
+impl std::error::Error for Replaced {}
+
+impl From<Replaced> for io::Error {
+    fn from(replaced: Replaced) -> Self {
+        io::Error::other(replaced)
+    }
+}
+
+/// 补丁写回 AppImage 的方式
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum Output {
+    /// 尚未写回
+    None,
+    /// 用 appimagetool 重新打包并替换原文件，原文件保存为 .bak
+    Repacked,
+    /// 在 AppImage 旁生成启动解包目录的脚本
+    Launcher,
+}
+
+/// 解包目录的来源
+#[derive(Debug, Serialize, Deserialize)]
+struct Source {
+    appimage: PathBuf,
+    len: u64,
+    modified: u128,
+    /// 解包或写回时 AppImage 内容的 SHA-256，大小或修改时间变化时用于确认是否被替换
+    #[serde(default)]
+    sha256: String,
+    output: Output,
+}
+
+impl Source {
+    fn read(dir: &Path) -> Option<Self> {
+        let content = fs::read(dir.join(SOURCE_FILE)).ok()?;
+        serde_json::from_slice(&content).ok()
+    }
+
+    fn write(&self, dir: &Path) -> io::Result<()> {
+        let content = serde_json::to_vec_pretty(self).map_err(|e| other(e.to_string()))?;
+        fs::write(dir.join(SOURCE_FILE), content)
+    }
+
+    /// 与 AppImage 当前状态一致 (未被替换为其他版本)
+    fn matches(&self, appimage: &Path) -> bool {
+        file_stamp(appimage).is_ok_and(|stamp| stamp == (self.len, self.modified))
+    }
+
+    /// AppImage 仍是记录的内容: 大小和修改时间一致，或仅修改时间变化而内容哈希一致
+    fn is_current(&self, appimage: &Path) -> io::Result<bool> {
+        Ok(self.matches(appimage) || file_sha256(appimage)? == self.sha256)
+    }
+}
+
+fn file_sha256(path: &Path) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+fn file_stamp(path: &Path) -> io::Result<(u64, u128)> {
+    let metadata = fs::metadata(path)?;
+    let modified = metadata
+        .modified()?
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_nanos())
+        .unwrap_or_default();
+    Ok((metadata.len(), modified))
+}
+
+/// 判断是否为 AppImage (type 2: ELF 文件头偏移 8 处为 "AI\x02")
+pub(super) fn is_appimage(path: &Path) -> bool {
+    if !cfg!(target_os = "linux") || !path.is_file() {
+        return false;
+    }
+    let mut magic = [0u8; 11];
+    File::open(path)
+        .and_then(|mut file| file.read_exact(&mut magic))
+        .is_ok_and(|()| magic[..4] == *b"\x7fELF" && magic[8..] == *b"AI\x02")
+}
+
+/// AppImage 的解包目录 (应用数据目录/appimage/<AppImage 路径哈希>)
+fn work_dir(appimage: &Path) -> PathBuf {
+    let digest = Sha256::digest(appimage.to_string_lossy().as_bytes());
+    let name: String = digest[..8]
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    config::get_app_data_dir().join("appimage").join(name)
+}
+
+/// 已解包且与 AppImage 当前状态一致的安装根目录，不解包
+pub(super) fn current_root(appimage: &Path) -> Option<PathBuf> {
+    let appimage = fs::canonicalize(appimage).ok()?;
+    let dir = work_dir(&appimage);
+    let root = dir.join(EXTRACT_DIR);
+    (root.is_dir() && Source::read(&dir).is_some_and(|source| source.matches(&appimage)))
+        .then_some(root)
+}
+
+/// 解包后的 Antigravity 安装根目录，AppImage 未解包或已被替换时重新解包
+/// 仅在安装、卸载等写入操作中调用
+pub(super) fn extracted_root(appimage: &Path) -> io::Result<PathBuf> {
+    if let Some(root) = current_root(appimage) {
+        return Ok(root);
+    }
+    let appimage = fs::canonicalize(appimage)?;
+    let dir = work_dir(&appimage);
+    let root = dir.join(EXTRACT_DIR);
+
+    // 仅修改时间变化 (复制、touch) 时内容仍一致，更新记录后继续使用解包目录
+    if let Some(mut source) = Source::read(&dir).filter(|_| root.is_dir()) {
+        let sha256 = file_sha256(&appimage)?;
+        if source.sha256 == sha256 {
+            (source.len, source.modified) = file_stamp(&appimage)?;
+            source.write(&dir)?;
+            return Ok(root);
+        }
+        // AppImage 被替换为其他版本，原文件备份不再对应，交给用户处理而不是删除
+        let backup = backup_path(&appimage);
+        if source.output == Output::Repacked && backup.exists() {
+            return Err(Replaced { backup }.into());
+        }
+    }
+    if dir.exists() {
+        fs::remove_dir_all(&dir)?;
+    }
+    fs::create_dir_all(&dir)?;
+    if let Err(e) = extract(&appimage, &dir) {
+        let _ = fs::remove_dir_all(&dir);
+        return Err(e);
+    }
+
+    let (len, modified) = file_stamp(&appimage)?;
+    let sha256 = file_sha256(&appimage)?;
+    Source {
+        appimage,
+        len,
+        modified,
+        sha256,
+        output: Output::None,
+    }
+    .write(&dir)?;
+    Ok(root)
+}
+
+/// 解包 squashfs: 优先使用 AppImage 自带的 --appimage-extract，
+/// 不可执行时使用 unsquashfs 从 ELF 运行时之后的偏移解包
+fn extract(appimage: &Path, dir: &Path) -> io::Result<()> {
+    let status = Command::new(appimage)
+        .arg("--appimage-extract")
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+    if status.is_ok_and(|status| status.success()) && dir.join(EXTRACT_DIR).is_dir() {
+        return Ok(());
+    }
+
+    let _ = fs::remove_dir_all(dir.join(EXTRACT_DIR));
+    let offset = payload_offset(appimage)?;
+    let output = Command::new("unsquashfs")
+        .arg("-no-progress")
+        .arg("-offset")
+        .arg(offset.to_string())
+        .arg("-dest")
+        .arg(dir.join(EXTRACT_DIR))
+        .arg(appimage)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| other(format!("unsquashfs: {}", e)))?;
+    if !output.status.success() {
+        return Err(other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// squashfs 的起始位置: ELF 运行时的节头表之后
+fn payload_offset(appimage: &Path) -> io::Result<u64> {
+    let mut header = [0u8; 64];
+    File::open(appimage)?.read_exact(&mut header)?;
+    let u16_at = |at: usize| u16::from_le_bytes([header[at], header[at + 1]]) as u64;
+    let u32_at = |at: usize| {
+        u32::from_le_bytes([header[at], header[at + 1], header[at + 2], header[at + 3]]) as u64
+    };
+    let (section_offset, entry_size, count) = match header[4] {
+        1 => (u32_at(0x20), u16_at(0x2E), u16_at(0x30)),
+        2 => (
+            u32_at(0x28) | (u32_at(0x2C) << 32),
+            u16_at(0x3A),
+            u16_at(0x3C),
+        ),
+        _ => return Err(other("unsupported ELF class")),
+    };
+    Ok(section_offset + entry_size * count)
+}
+
+/// 解包目录对应的 AppImage，普通安装返回 None
+fn source_of(antigravity_root: &Path) -> Option<(PathBuf, Source)> {
+    if antigravity_root.file_name()? != EXTRACT_DIR {
+        return None;
+    }
+    let dir = antigravity_root.parent()?;
+    Source::read(dir).map(|source| (dir.to_path_buf(), source))
+}
+
+fn backup_path(appimage: &Path) -> PathBuf {
+    let mut name = appimage.file_name().unwrap_or_default().to_os_string();
+    name.push(".bak");
+    appimage.with_file_name(name)
+}
+
+/// 启动脚本: AppImage 同目录下的 <文件名>-patched
+fn launcher_path(appimage: &Path) -> PathBuf {
+    let stem = appimage
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    appimage.with_file_name(format!("{}-patched", stem))
+}
+
+/// 将解包目录中的补丁写回: 有 appimagetool 时重新打包替换原文件，否则生成启动脚本
+/// 普通安装不做处理
+pub(super) fn commit(antigravity_root: &Path) -> io::Result<()> {
+    let Some((dir, mut source)) = source_of(antigravity_root) else {
+        return Ok(());
+    };
+    let appimage = source.appimage.clone();
+
+    source.output = if command_exists(APPIMAGETOOL) {
+        repack(antigravity_root, &appimage)?;
+        let _ = fs::remove_file(launcher_path(&appimage));
+        Output::Repacked
+    } else {
+        write_launcher(antigravity_root, &appimage)?;
+        Output::Launcher
+    };
+    (source.len, source.modified) = file_stamp(&appimage)?;
+    source.sha256 = file_sha256(&appimage)?;
+    source.write(&dir)
+}
+
+fn repack(antigravity_root: &Path, appimage: &Path) -> io::Result<()> {
+    let backup = backup_path(appimage);
+    if !backup.exists() {
+        fs::copy(appimage, &backup)?;
+        oplog::file("backup", &backup);
+    }
+
+    let temp_path = appimage.with_file_name(format!(
+        ".{}.tmp",
+        appimage.file_name().unwrap_or_default().to_string_lossy()
+    ));
+    let output = Command::new(APPIMAGETOOL)
+        .arg("--no-appstream")
+        .arg(antigravity_root)
+        .arg(&temp_path)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| other(format!("{}: {}", APPIMAGETOOL, e)))?;
+    oplog::output(&String::from_utf8_lossy(&output.stderr));
+    if !output.status.success() || !temp_path.is_file() {
+        let _ = fs::remove_file(&temp_path);
+        return Err(other(format!(
+            "{} exited with {}",
+            APPIMAGETOOL, output.status
+        )));
+    }
+
+    // 保持原文件的权限
+    let permissions = fs::metadata(appimage)?.permissions();
+    fs::set_permissions(&temp_path, permissions)?;
+    fs::rename(&temp_path, appimage)?;
+    oplog::file("write", appimage);
+    Ok(())
+}
+
+fn write_launcher(antigravity_root: &Path, appimage: &Path) -> io::Result<()> {
+    let launcher = launcher_path(appimage);
+    let app_run = antigravity_root.join("AppRun");
+    let content = format!(
+        "#!/bin/sh\n# Anti-Power: 已打补丁的 Antigravity (原文件: {})\nexec '{}' \"$@\"\n",
+        appimage.display(),
+        app_run.display().to_string().replace('\'', "'\\''")
+    );
+    fs::write(&launcher, content)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&launcher, fs::Permissions::from_mode(0o755))?;
+    }
+    oplog::file("write", &launcher);
+    Ok(())
+}
+
+/// 卸载时恢复原始 AppImage 并删除启动脚本与解包目录，普通安装返回 false
+/// AppImage 已被替换为其他版本时不恢复备份，保留两个文件并返回 Replaced
+pub(super) fn restore_original(antigravity_root: &Path) -> io::Result<bool> {
+    let Some((dir, source)) = source_of(antigravity_root) else {
+        return Ok(false);
+    };
+    let appimage = &source.appimage;
+
+    let backup = backup_path(appimage);
+    if backup.is_file() {
+        if appimage.exists() && !source.is_current(appimage)? {
+            return Err(Replaced { backup }.into());
+        }
+        fs::rename(&backup, appimage)?;
+        oplog::file("restore", appimage);
+    }
+    let launcher = launcher_path(appimage);
+    if launcher.is_file() {
+        fs::remove_file(&launcher)?;
+        oplog::file("delete", &launcher);
+    }
+    fs::remove_dir_all(&dir)?;
+    oplog::file("delete", &dir);
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::test_support::TempDir;
+
+    /// 模拟已重新打包的 AppImage: 当前文件、.bak 备份与解包目录
+    fn repacked(temp: &TempDir) -> (PathBuf, PathBuf) {
+        let appimage = temp.0.join("Antigravity.AppImage");
+        fs::write(&appimage, b"patched").unwrap();
+        fs::write(backup_path(&appimage), b"original").unwrap();
+
+        let dir = temp.0.join("work");
+        let root = dir.join(EXTRACT_DIR);
+        fs::create_dir_all(&root).unwrap();
+        let (len, modified) = file_stamp(&appimage).unwrap();
+        Source {
+            appimage: appimage.clone(),
+            len,
+            modified,
+            sha256: file_sha256(&appimage).unwrap(),
+            output: Output::Repacked,
+        }
+        .write(&dir)
+        .unwrap();
+        (appimage, root)
+    }
+
+    #[test]
+    fn restore_original_puts_back_backup() {
+        let temp = TempDir::new("appimage-restore");
+        let (appimage, root) = repacked(&temp);
+
+        assert!(restore_original(&root).unwrap());
+        assert_eq!(fs::read(&appimage).unwrap(), b"original");
+        assert!(!backup_path(&appimage).exists());
+        assert!(!root.exists());
+    }
+
+    #[test]
+    fn restore_original_keeps_replaced_appimage() {
+        let temp = TempDir::new("appimage-replaced");
+        let (appimage, root) = repacked(&temp);
+        fs::write(&appimage, b"upgraded version").unwrap();
+
+        let error = restore_original(&root).unwrap_err();
+        assert!(error
+            .get_ref()
+            .is_some_and(|inner| inner.downcast_ref::<Replaced>().is_some()));
+        assert_eq!(fs::read(&appimage).unwrap(), b"upgraded version");
+        assert_eq!(fs::read(backup_path(&appimage)).unwrap(), b"original");
+        assert!(root.is_dir());
+    }
+}
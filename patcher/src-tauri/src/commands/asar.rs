@@ -0,0 +1,681 @@
+//! app.asar 归档支持
+//!
+//! 部分 Electron 构建将 resources/app 打包为 app.asar。补丁操作时将补丁涉及的
+//! 路径解包到应用数据目录下的缓存目录，按普通目录修改后重新打包写回归档，
+//! 同时更新文件完整性信息和 Info.plist 中的头部哈希。首次修改前保留原始归档，
+//! 卸载时直接恢复。写回安装目录的操作由 patch 模块执行，目录不可写时提权。
+//! 状态、配置读取与诊断等只读操作通过 Resources 直接读取归档，不解包
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use super::atomic::FileOp;
+use super::config;
+use super::paths;
+
+/// 归档文件名
+pub(super) const ARCHIVE_NAME: &str = "app.asar";
+
+/// 原始归档的备份文件名
+const BACKUP_NAME: &str = "app.asar.bak";
+
+/// 解包的路径 (相对于归档根目录)，补丁和还原点只访问这些路径
+const MOUNT_PATHS: &[&str] = &[
+    "extensions/antigravity",
+    "out/vs/code/electron-browser/workbench",
+    "product.json",
+    "package.json",
+];
+
+/// 解包目录中记录来源归档状态的文件
+const STAMP_FILE: &str = ".asar-source.json";
+
+/// 文件完整性信息的分块大小 (与 @electron/asar 一致)
+const BLOCK_SIZE: usize = 4 * 1024 * 1024;
+
+fn invalid_data(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+/// 已打开的 asar 归档
+pub(super) struct Archive {
+    path: PathBuf,
+    /// 头部 JSON 的根节点 ({ "files": { ... } })
+    header: Value,
+    /// 头部 JSON 原文，用于计算头部哈希
+    raw_header: String,
+    /// 文件数据起始位置
+    data_offset: u64,
+}
+
+impl Archive {
+    /// 读取归档头部
+    /// 格式: [4][头部长度] 之后为 pickle 编码的 JSON 字符串，文件数据紧随其后
+    pub(super) fn open(path: &Path) -> io::Result<Self> {
+        let mut file = File::open(path)?;
+        let mut prefix = [0u8; 16];
+        file.read_exact(&mut prefix)?;
+        let word = |index: usize| {
+            u32::from_le_bytes([
+                prefix[index],
+                prefix[index + 1],
+                prefix[index + 2],
+                prefix[index + 3],
+            ]) as u64
+        };
+        let header_size = word(4);
+        let json_size = word(12);
+        if word(0) != 4 || json_size + 8 > header_size {
+            return Err(invalid_data(format!(
+                "{} is not an asar archive",
+                path.display()
+            )));
+        }
+
+        let mut json = vec![0u8; json_size as usize];
+        file.read_exact(&mut json)?;
+        let raw_header = String::from_utf8(json).map_err(|e| invalid_data(e.to_string()))?;
+        let header: Value =
+            serde_json::from_str(&raw_header).map_err(|e| invalid_data(e.to_string()))?;
+        if !header.get("files").is_some_and(Value::is_object) {
+            return Err(invalid_data("asar header has no files"));
+        }
+        Ok(Self {
+            path: path.to_path_buf(),
+            header,
+            raw_header,
+            data_offset: 8 + header_size,
+        })
+    }
+
+    /// 查找条目 (路径使用 / 分隔)
+    pub(super) fn entry(&self, path: &str) -> Option<&Value> {
+        path.split('/')
+            .filter(|part| !part.is_empty())
+            .try_fold(&self.header, |node, part| node.get("files")?.get(part))
+    }
+
+    pub(super) fn contains(&self, path: &str) -> bool {
+        self.entry(path).is_some()
+    }
+
+    /// 按头部中的偏移直接读取文件内容，解包到 app.asar.unpacked 的文件从该目录读取
+    pub(super) fn read(&self, path: &str) -> io::Result<Vec<u8>> {
+        let entry = self.entry(path).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} not found in {}", path, self.path.display()),
+            )
+        })?;
+        if is_unpacked(entry) {
+            return fs::read(self.path.with_extension("asar.unpacked").join(path));
+        }
+        if !is_packed_file(entry) {
+            return Err(invalid_data(format!("{} is not a file", path)));
+        }
+
+        let (offset, size) = file_range(entry)?;
+        let mut file = File::open(&self.path)?;
+        file.seek(SeekFrom::Start(self.data_offset + offset))?;
+        let mut content = Vec::with_capacity(size as usize);
+        file.take(size).read_to_end(&mut content)?;
+        Ok(content)
+    }
+
+    /// 将归档中的路径解包到目标位置，解包到 app.asar.unpacked 的文件和链接不解包
+    fn extract(&self, path: &str, dest: &Path) -> io::Result<()> {
+        let Some(entry) = self.entry(path) else {
+            return Ok(());
+        };
+        let mut file = File::open(&self.path)?;
+        self.extract_entry(&mut file, entry, dest)
+    }
+
+    fn extract_entry(&self, file: &mut File, entry: &Value, dest: &Path) -> io::Result<()> {
+        if let Some(children) = entry.get("files").and_then(Value::as_object) {
+            fs::create_dir_all(dest)?;
+            for (name, child) in children {
+                self.extract_entry(file, child, &dest.join(name))?;
+            }
+            return Ok(());
+        }
+        if entry.get("link").is_some() || is_unpacked(entry) {
+            return Ok(());
+        }
+
+        let (offset, size) = file_range(entry)?;
+        file.seek(SeekFrom::Start(self.data_offset + offset))?;
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut output = File::create(dest)?;
+        io::copy(&mut Read::by_ref(file).take(size), &mut output)?;
+
+        #[cfg(unix)]
+        if entry.get("executable").and_then(Value::as_bool) == Some(true) {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(dest, fs::Permissions::from_mode(0o755))?;
+        }
+        Ok(())
+    }
+
+    /// 用解包目录中的内容替换 MOUNT_PATHS 后写出新归档
+    fn pack(&self, staging: &Path, dest: &Path) -> io::Result<String> {
+        let mut header = self.header.clone();
+        let mut staged = BTreeMap::new();
+        for path in MOUNT_PATHS {
+            let original = take_entry(&mut header, path);
+            let source = staging.join(path);
+            if let Some(entry) = staged_entry(&source, original.as_ref(), path, &mut staged)? {
+                insert_entry(&mut header, path, entry);
+            }
+        }
+
+        // 按头部顺序重新分配数据偏移
+        let mut sources = Vec::new();
+        let mut offset = 0u64;
+        assign_offsets(&mut header, "", &staged, &mut offset, &mut sources)?;
+
+        let json = serde_json::to_string(&header).map_err(|e| invalid_data(e.to_string()))?;
+        let padded = json.len().div_ceil(4) * 4;
+        let mut output = BufWriter::new(File::create(dest)?);
+        for word in [4, padded + 8, padded + 4, json.len()] {
+            output.write_all(&(word as u32).to_le_bytes())?;
+        }
+        output.write_all(json.as_bytes())?;
+        output.write_all(&vec![0u8; padded - json.len()])?;
+
+        let mut original = File::open(&self.path)?;
+        for source in sources {
+            match source {
+                DataSource::Original { offset, size } => {
+                    original.seek(SeekFrom::Start(self.data_offset + offset))?;
+                    let copied =
+                        io::copy(&mut Read::by_ref(&mut original).take(size), &mut output)?;
+                    if copied != size {
+                        return Err(invalid_data("asar archive is truncated"));
+                    }
+                }
+                DataSource::Staged(path) => {
+                    io::copy(&mut File::open(path)?, &mut output)?;
+                }
+            }
+        }
+        output
+            .into_inner()
+            .map_err(|e| e.into_error())?
+            .sync_all()?;
+        Ok(json)
+    }
+}
+
+/// 新归档中文件数据的来源
+enum DataSource {
+    Original { offset: u64, size: u64 },
+    Staged(PathBuf),
+}
+
+fn is_unpacked(entry: &Value) -> bool {
+    entry.get("unpacked").and_then(Value::as_bool) == Some(true)
+}
+
+fn is_packed_file(entry: &Value) -> bool {
+    entry.get("files").is_none() && entry.get("link").is_none() && !is_unpacked(entry)
+}
+
+fn file_range(entry: &Value) -> io::Result<(u64, u64)> {
+    let offset = entry
+        .get("offset")
+        .and_then(Value::as_str)
+        .and_then(|offset| offset.parse().ok());
+    let size = entry.get("size").and_then(Value::as_u64);
+    offset
+        .zip(size)
+        .ok_or_else(|| invalid_data("asar entry has no offset"))
+}
+
+/// 从头部移除条目并返回
+fn take_entry(header: &mut Value, path: &str) -> Option<Value> {
+    let (parent, name) = match path.rsplit_once('/') {
+        Some((parent, name)) => (Some(parent), name),
+        None => (None, path),
+    };
+    let mut node = header;
+    for part in parent.into_iter().flat_map(|parent| parent.split('/')) {
+        node = node.get_mut("files")?.get_mut(part)?;
+    }
+    node.get_mut("files")?.as_object_mut()?.remove(name)
+}
+
+/// 写入条目，缺少的上级目录一并创建
+fn insert_entry(header: &mut Value, path: &str, entry: Value) {
+    let (parent, name) = match path.rsplit_once('/') {
+        Some((parent, name)) => (Some(parent), name),
+        None => (None, path),
+    };
+    let mut node = header;
+    for part in parent.into_iter().flat_map(|parent| parent.split('/')) {
+        node = &mut node["files"][part];
+    }
+    node["files"][name] = entry;
+}
+
+/// 由解包目录生成条目，保留原条目中的链接、未打包文件和可执行标记
+fn staged_entry(
+    source: &Path,
+    original: Option<&Value>,
+    path: &str,
+    staged: &mut BTreeMap<String, PathBuf>,
+) -> io::Result<Option<Value>> {
+    if source.is_dir() {
+        let mut files = Map::new();
+        let original_files = original
+            .and_then(|entry| entry.get("files"))
+            .and_then(Value::as_object);
+        for (name, entry) in original_files.into_iter().flatten() {
+            if !is_packed_file(entry) && entry.get("files").is_none() {
+                files.insert(name.clone(), entry.clone());
+            }
+        }
+        let mut names: Vec<_> = fs::read_dir(source)?
+            .map(|entry| entry.map(|entry| entry.file_name()))
+            .collect::<io::Result<_>>()?;
+        names.sort();
+        for name in names {
+            let name = name.to_string_lossy().to_string();
+            let child_original = original_files.and_then(|files| files.get(&name));
+            let child_path = format!("{}/{}", path, name);
+            if let Some(entry) =
+                staged_entry(&source.join(&name), child_original, &child_path, staged)?
+            {
+                files.insert(name, entry);
+            }
+        }
+        let mut entry = json!({ "files": files });
+        if original.is_some_and(is_unpacked) {
+            entry["unpacked"] = Value::Bool(true);
+        }
+        return Ok(Some(entry));
+    }
+
+    if !source.is_file() {
+        // 解包时跳过的链接和未打包文件保持原样
+        return Ok(original.filter(|entry| !is_packed_file(entry)).cloned());
+    }
+
+    let (size, integrity) = file_integrity(source)?;
+    let mut entry = json!({
+        "size": size,
+        "offset": "0",
+        "integrity": integrity,
+    });
+    if original.and_then(|entry| entry.get("executable")) == Some(&Value::Bool(true)) {
+        entry["executable"] = Value::Bool(true);
+    }
+    staged.insert(path.to_string(), source.to_path_buf());
+    Ok(Some(entry))
+}
+
+/// 计算文件大小和 SHA256 完整性信息 (整体哈希和 4 MB 分块哈希)
+fn file_integrity(path: &Path) -> io::Result<(u64, Value)> {
+    let mut file = File::open(path)?;
+    let mut whole = Sha256::new();
+    let mut blocks = Vec::new();
+    let mut buffer = vec![0u8; BLOCK_SIZE];
+    let mut size = 0u64;
+    loop {
+        let mut filled = 0;
+        while filled < BLOCK_SIZE {
+            let read = file.read(&mut buffer[filled..])?;
+            if read == 0 {
+                break;
+            }
+            filled += read;
+        }
+        if filled == 0 && size > 0 {
+            break;
+        }
+        whole.update(&buffer[..filled]);
+        blocks.push(hex(&Sha256::digest(&buffer[..filled])));
+        size += filled as u64;
+        if filled < BLOCK_SIZE {
+            break;
+        }
+    }
+    Ok((
+        size,
+        json!({
+            "algorithm": "SHA256",
+            "hash": hex(&whole.finalize()),
+            "blockSize": BLOCK_SIZE,
+            "blocks": blocks,
+        }),
+    ))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// 按头部顺序为打包文件分配偏移，并记录数据来源
+fn assign_offsets(
+    node: &mut Value,
+    path: &str,
+    staged: &BTreeMap<String, PathBuf>,
+    offset: &mut u64,
+    sources: &mut Vec<DataSource>,
+) -> io::Result<()> {
+    if let Some(children) = node.get_mut("files").and_then(Value::as_object_mut) {
+        for (name, child) in children.iter_mut() {
+            let child_path = if path.is_empty() {
+                name.clone()
+            } else {
+                format!("{}/{}", path, name)
+            };
+            assign_offsets(child, &child_path, staged, offset, sources)?;
+        }
+        return Ok(());
+    }
+    if !is_packed_file(node) {
+        return Ok(());
+    }
+
+    let size = match staged.get(path) {
+        Some(source) => {
+            sources.push(DataSource::Staged(source.clone()));
+            node.get("size").and_then(Value::as_u64).unwrap_or_default()
+        }
+        None => {
+            let (original, size) = file_range(node)?;
+            sources.push(DataSource::Original {
+                offset: original,
+                size,
+            });
+            size
+        }
+    };
+    node["offset"] = Value::String(offset.to_string());
+    *offset += size;
+    Ok(())
+}
+
+/// 解包目录对应的归档状态，归档变化后重新解包
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Stamp {
+    archive: PathBuf,
+    len: u64,
+    modified: u128,
+}
+
+impl Stamp {
+    fn of(archive: &Path) -> io::Result<Self> {
+        let metadata = fs::metadata(archive)?;
+        let modified = metadata
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_nanos())
+            .unwrap_or_default();
+        Ok(Self {
+            archive: archive.to_path_buf(),
+            len: metadata.len(),
+            modified,
+        })
+    }
+
+    fn read(mount_dir: &Path) -> Option<Self> {
+        let content = fs::read(mount_dir.join(STAMP_FILE)).ok()?;
+        serde_json::from_slice(&content).ok()
+    }
+
+    fn write(&self, mount_dir: &Path) -> io::Result<()> {
+        let content = serde_json::to_vec_pretty(self).map_err(|e| invalid_data(e.to_string()))?;
+        fs::write(mount_dir.join(STAMP_FILE), content)
+    }
+}
+
+/// 归档的解包目录 (应用数据目录/asar/<归档路径哈希>)
+fn mount_dir(archive: &Path) -> PathBuf {
+    let digest = Sha256::digest(archive.to_string_lossy().as_bytes());
+    config::get_app_data_dir()
+        .join("asar")
+        .join(hex(&digest[..8]))
+}
+
+/// 补丁操作使用的 resources/app 目录
+/// app.asar 布局返回解包目录，fresh 为 true 时重新解包 (修改前调用，丢弃上次未写回的改动)
+pub(super) fn mount(antigravity_root: &Path, fresh: bool) -> io::Result<PathBuf> {
+    let Some(archive_path) = paths::resources_archive(antigravity_root) else {
+        return Ok(paths::resources_app_root(antigravity_root));
+    };
+    let dir = mount_dir(&archive_path);
+    let stamp = Stamp::of(&archive_path)?;
+    if !fresh && Stamp::read(&dir).as_ref() == Some(&stamp) {
+        return Ok(dir);
+    }
+
+    if dir.exists() {
+        fs::remove_dir_all(&dir)?;
+    }
+    let archive = Archive::open(&archive_path)?;
+    for path in MOUNT_PATHS {
+        archive.extract(path, &dir.join(path))?;
+    }
+    stamp.write(&dir)?;
+    Ok(dir)
+}
+
+/// 只读访问的 resources/app，路径相对于 resources/app 并使用 / 分隔
+/// app.asar 布局直接从归档读取，不解包；状态、配置读取与诊断使用，修改前仍需 mount
+pub(super) enum Resources {
+    Dir(PathBuf),
+    Archive(Archive),
+}
+
+impl Resources {
+    /// 打开安装的 resources/app，归档无法读取时退回普通布局的路径
+    pub(super) fn open(antigravity_root: &Path) -> Self {
+        paths::resources_archive(antigravity_root)
+            .and_then(|archive| Archive::open(&archive).ok())
+            .map(Self::Archive)
+            .unwrap_or_else(|| Self::Dir(paths::resources_app_root(antigravity_root)))
+    }
+
+    pub(super) fn read(&self, path: &str) -> io::Result<Vec<u8>> {
+        match self {
+            Self::Dir(root) => fs::read(root.join(path)),
+            Self::Archive(archive) => archive.read(path),
+        }
+    }
+
+    pub(super) fn read_to_string(&self, path: &str) -> io::Result<String> {
+        String::from_utf8(self.read(path)?).map_err(|e| invalid_data(e.to_string()))
+    }
+
+    /// Antigravity 版本号 (package.json 的 version 字段)
+    pub(super) fn antigravity_version(&self) -> Option<String> {
+        paths::parse_antigravity_version(&self.read("package.json").ok()?)
+    }
+
+    pub(super) fn exists(&self, path: &str) -> bool {
+        match self {
+            Self::Dir(root) => root.join(path).exists(),
+            Self::Archive(archive) => archive.contains(path),
+        }
+    }
+
+    /// 用于提示的完整路径，归档内的文件显示为 app.asar/<路径>
+    pub(super) fn display_path(&self, path: &str) -> PathBuf {
+        match self {
+            Self::Dir(root) => root.join(path),
+            Self::Archive(archive) => archive.path.join(path),
+        }
+    }
+}
+
+/// 暂存的新归档 (应用数据目录/asar/<归档路径哈希>.staged)
+fn staged_path(archive: &Path) -> PathBuf {
+    mount_dir(archive).with_extension("staged")
+}
+
+/// 将解包目录的改动打包为暂存归档，返回写回安装目录需要的文件操作
+/// 普通布局返回空列表。执行后调用 finish_commit
+pub(super) fn commit_ops(antigravity_root: &Path) -> io::Result<Vec<FileOp>> {
+    let Some(archive_path) = paths::resources_archive(antigravity_root) else {
+        return Ok(Vec::new());
+    };
+    let dir = mount_dir(&archive_path);
+    let archive = Archive::open(&archive_path)?;
+    let mut ops = Vec::new();
+
+    // 未打补丁的归档作为原始版本备份 (Antigravity 更新后刷新)
+    let backup_path = archive_path.with_file_name(BACKUP_NAME);
+    if !backup_path.exists() || is_pristine(&archive) {
+        ops.push(FileOp::Copy {
+            from: archive_path.clone(),
+            to: backup_path,
+        });
+    }
+
+    let staged = staged_path(&archive_path);
+    let header = match archive.pack(&dir, &staged) {
+        Ok(header) => header,
+        Err(e) => {
+            let _ = fs::remove_file(&staged);
+            return Err(e);
+        }
+    };
+    ops.push(FileOp::Copy {
+        from: staged,
+        to: archive_path,
+    });
+    ops.extend(integrity_op(antigravity_root, &header));
+    Ok(ops)
+}
+
+/// 删除暂存归档，写回成功时记录新归档的状态
+pub(super) fn finish_commit(antigravity_root: &Path, committed: bool) -> io::Result<()> {
+    let Some(archive_path) = paths::resources_archive(antigravity_root) else {
+        return Ok(());
+    };
+    let _ = fs::remove_file(staged_path(&archive_path));
+    if committed {
+        Stamp::of(&archive_path)?.write(&mount_dir(&archive_path))?;
+    }
+    Ok(())
+}
+
+/// 卸载时恢复原始归档的文件操作，没有备份时返回 None
+pub(super) fn restore_ops(antigravity_root: &Path) -> io::Result<Option<Vec<FileOp>>> {
+    let Some(archive_path) = paths::resources_archive(antigravity_root) else {
+        return Ok(None);
+    };
+    let backup_path = archive_path.with_file_name(BACKUP_NAME);
+    if !backup_path.is_file() {
+        return Ok(None);
+    }
+
+    let original = Archive::open(&backup_path)?;
+    let mut ops = vec![FileOp::Copy {
+        from: backup_path.clone(),
+        to: archive_path,
+    }];
+    ops.extend(integrity_op(antigravity_root, &original.raw_header));
+    ops.push(FileOp::Remove(backup_path));
+    Ok(Some(ops))
+}
+
+/// 归档中没有补丁留下的入口备份
+fn is_pristine(archive: &Archive) -> bool {
+    !archive.contains("extensions/antigravity/cascade-panel.html.bak")
+        && !archive
+            .contains("out/vs/code/electron-browser/workbench/workbench-jetski-agent.html.bak")
+}
+
+/// 更新 macOS Info.plist 中 ElectronAsarIntegrity 记录的头部哈希
+/// 没有该记录或哈希未变化时返回 None；Windows 的完整性信息位于可执行文件资源中，无法更新
+fn integrity_op(antigravity_root: &Path, header: &str) -> Option<FileOp> {
+    let plist_path = antigravity_root.join("Info.plist");
+    let content = fs::read_to_string(&plist_path).ok()?;
+    let start = content
+        .find("<key>Resources/app.asar</key>")
+        .and_then(|start| {
+            content[start..]
+                .find("<key>hash</key>")
+                .map(|at| start + at)
+        })
+        .and_then(|start| content[start..].find("<string>").map(|at| start + at + 8))?;
+    let end = content[start..].find("</string>").map(|at| start + at)?;
+
+    let hash = hex(&Sha256::digest(header.as_bytes()));
+    if content[start..end] == hash {
+        return None;
+    }
+    let updated = format!("{}{}{}", &content[..start], hash, &content[end..]);
+    Some(FileOp::Write {
+        path: plist_path,
+        content: updated.into_bytes(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::test_support::TempDir;
+
+    /// 按 asar 格式写出只包含给定文件的归档
+    fn write_archive(path: &Path, files: &[(&str, &[u8])]) {
+        let mut header = json!({ "files": {} });
+        let mut data = Vec::new();
+        for (name, content) in files {
+            let entry = json!({ "size": content.len(), "offset": data.len().to_string() });
+            insert_entry(&mut header, name, entry);
+            data.extend_from_slice(content);
+        }
+        let json = header.to_string();
+        let padded = json.len().div_ceil(4) * 4;
+        let header_size = (8 + padded) as u32;
+
+        let mut bytes = Vec::new();
+        for word in [4, header_size, header_size - 4, json.len() as u32] {
+            bytes.extend_from_slice(&word.to_le_bytes());
+        }
+        bytes.extend_from_slice(json.as_bytes());
+        bytes.resize(8 + header_size as usize, 0);
+        bytes.extend_from_slice(&data);
+        fs::write(path, bytes).unwrap();
+    }
+
+    #[test]
+    fn resources_read_archive_without_mounting() {
+        let dir = TempDir::new("asar-resources");
+        let archive_path = dir.0.join(ARCHIVE_NAME);
+        write_archive(
+            &archive_path,
+            &[
+                ("package.json", br#"{"version":"1.16.5"}"#),
+                ("extensions/antigravity/cascade-panel/config.json", b"{}"),
+            ],
+        );
+
+        let resources = Resources::Archive(Archive::open(&archive_path).unwrap());
+        assert_eq!(resources.antigravity_version().as_deref(), Some("1.16.5"));
+        assert!(resources.exists("extensions/antigravity/cascade-panel"));
+        assert_eq!(
+            resources
+                .read_to_string("extensions/antigravity/cascade-panel/config.json")
+                .unwrap(),
+            "{}"
+        );
+        assert_eq!(
+            resources.read("product.json").unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+        assert!(resources.read("extensions/antigravity").is_err());
+    }
+}
@@ -0,0 +1,98 @@
+//! 原子写入
+//!
+//! 先写同目录下的临时文件并 fsync，再重命名覆盖目标文件，
+//! 写入中途失败或断电时目标文件保持原样
+
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// 原子地写入文件
+pub(super) fn write_atomic(path: &Path, content: impl AsRef<[u8]>) -> io::Result<()> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "missing file name"))?;
+    let temp_path = path.with_file_name(format!(".{}.tmp", file_name.to_string_lossy()));
+
+    let result = File::create(&temp_path)
+        .and_then(|mut file| {
+            file.write_all(content.as_ref())?;
+            file.sync_all()
+        })
+        .and_then(|()| fs::rename(&temp_path, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result?;
+
+    // 同步目录项，保证重命名落盘
+    #[cfg(unix)]
+    if let Some(parent) = path.parent() {
+        if let Ok(dir) = File::open(parent) {
+            let _ = dir.sync_all();
+        }
+    }
+
+    Ok(())
+}
+
+/// 原子地复制文件，目标存在时保持其权限
+pub(super) fn copy_atomic(from: &Path, to: &Path) -> io::Result<()> {
+    let file_name = to
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "missing file name"))?;
+    let temp_path = to.with_file_name(format!(".{}.tmp", file_name.to_string_lossy()));
+    let permissions = fs::metadata(to).ok().map(|metadata| metadata.permissions());
+
+    let result = fs::copy(from, &temp_path)
+        .and_then(|_| match permissions {
+            Some(permissions) => fs::set_permissions(&temp_path, permissions),
+            None => Ok(()),
+        })
+        .and_then(|()| File::open(&temp_path)?.sync_all())
+        .and_then(|()| fs::rename(&temp_path, to));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+/// 写入安装目录的文件操作
+/// 目标目录不可写时 (如 /Applications) 由提权脚本按相同语义执行
+#[derive(Debug)]
+pub(super) enum FileOp {
+    /// 原子写入内容
+    Write { path: PathBuf, content: Vec<u8> },
+    /// 原子复制文件
+    Copy { from: PathBuf, to: PathBuf },
+    /// 删除文件
+    Remove(PathBuf),
+    /// 删除目录及其内容
+    RemoveDir(PathBuf),
+}
+
+impl FileOp {
+    /// 被修改的文件
+    pub(super) fn target(&self) -> &Path {
+        match self {
+            FileOp::Write { path, .. } => path,
+            FileOp::Copy { to, .. } => to,
+            FileOp::Remove(path) | FileOp::RemoveDir(path) => path,
+        }
+    }
+
+    /// 以当前用户身份直接执行，写入时创建缺失的父目录
+    pub(super) fn apply(&self) -> io::Result<()> {
+        if let FileOp::Write { path, .. } | FileOp::Copy { to: path, .. } = self {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+        }
+        match self {
+            FileOp::Write { path, content } => write_atomic(path, content),
+            FileOp::Copy { from, to } => copy_atomic(from, to),
+            FileOp::Remove(path) => fs::remove_file(path),
+            FileOp::RemoveDir(path) => fs::remove_dir_all(path),
+        }
+    }
+}
@@ -0,0 +1,181 @@
+//! Chrome DevTools Protocol 客户端
+//!
+//! 通过 --remote-debugging-port 的 HTTP 接口列出页面，
+//! 并通过 WebSocket 在页面中执行表达式 (只支持本机的 ws:// 地址)
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant};
+use tungstenite::{Message, WebSocket};
+
+/// 单次连接与读写的超时时间
+const IO_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 调试目标 (页面、webview 等)
+#[derive(Debug, Clone, Deserialize)]
+pub(super) struct CdpTarget {
+    #[serde(default)]
+    pub url: String,
+    #[serde(rename = "webSocketDebuggerUrl")]
+    pub ws_url: Option<String>,
+}
+
+/// 读取 /json/version，用于判断调试端口是否可用
+pub(super) fn browser_version(host: &str, port: u16) -> Result<Value, String> {
+    http_get_json(host, port, "/json/version")
+}
+
+/// 通过浏览器级 WebSocket 读取调试端口所属 Chromium 的进程 ID (SystemInfo.getProcessInfo)
+pub(super) fn browser_process_ids(host: &str, port: u16) -> Result<Vec<u32>, String> {
+    let version = browser_version(host, port)?;
+    let ws_url = version
+        .get("webSocketDebuggerUrl")
+        .and_then(Value::as_str)
+        .ok_or("missing webSocketDebuggerUrl")?;
+    let info = CdpSession::connect(ws_url)?.call("SystemInfo.getProcessInfo", json!({}))?;
+    Ok(info
+        .get("processInfo")
+        .and_then(Value::as_array)
+        .map(|processes| {
+            processes
+                .iter()
+                .filter_map(|process| process.get("id")?.as_u64()?.try_into().ok())
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
+/// 读取 /json/list
+pub(super) fn list_targets(host: &str, port: u16) -> Result<Vec<CdpTarget>, String> {
+    let value = http_get_json(host, port, "/json/list")?;
+    serde_json::from_value(value).map_err(|e| e.to_string())
+}
+
+fn connect(host: &str, port: u16) -> Result<TcpStream, String> {
+    let addr = (host, port)
+        .to_socket_addrs()
+        .map_err(|e| e.to_string())?
+        .next()
+        .ok_or_else(|| format!("cannot resolve {}", host))?;
+    let stream = TcpStream::connect_timeout(&addr, IO_TIMEOUT).map_err(|e| e.to_string())?;
+    stream
+        .set_read_timeout(Some(IO_TIMEOUT))
+        .and_then(|()| stream.set_write_timeout(Some(IO_TIMEOUT)))
+        .map_err(|e| e.to_string())?;
+    Ok(stream)
+}
+
+/// DevTools HTTP 接口的响应带 Content-Length，读到连接关闭即可
+fn http_get_json(host: &str, port: u16, path: &str) -> Result<Value, String> {
+    let mut stream = connect(host, port)?;
+    write!(
+        stream,
+        "GET {} HTTP/1.1\r\nHost: {}:{}\r\nConnection: close\r\n\r\n",
+        path, host, port
+    )
+    .map_err(|e| e.to_string())?;
+
+    let mut response = Vec::new();
+    stream
+        .read_to_end(&mut response)
+        .map_err(|e| e.to_string())?;
+    let split = response
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .ok_or("invalid HTTP response")?;
+    let head = String::from_utf8_lossy(&response[..split]);
+    let status = head.lines().next().unwrap_or_default();
+    if status.split_whitespace().nth(1) != Some("200") {
+        return Err(format!("{} -> {}", path, status));
+    }
+    serde_json::from_slice(&response[split + 4..]).map_err(|e| e.to_string())
+}
+
+/// 与单个调试目标的 WebSocket 会话
+pub(super) struct CdpSession {
+    socket: WebSocket<TcpStream>,
+    next_id: u64,
+}
+
+impl CdpSession {
+    pub(super) fn connect(ws_url: &str) -> Result<Self, String> {
+        let authority = ws_url
+            .strip_prefix("ws://")
+            .and_then(|rest| rest.split('/').next())
+            .ok_or_else(|| format!("unsupported WebSocket URL: {}", ws_url))?;
+        let (host, port) = authority
+            .rsplit_once(':')
+            .and_then(|(host, port)| Some((host, port.parse::<u16>().ok()?)))
+            .unwrap_or((authority, 80));
+        let stream = connect(host, port)?;
+        let (socket, _) = tungstenite::client(ws_url, stream).map_err(|e| e.to_string())?;
+        Ok(Self { socket, next_id: 1 })
+    }
+
+    /// 发送命令并等待对应 id 的响应，期间的事件消息会被忽略
+    pub(super) fn call(&mut self, method: &str, params: Value) -> Result<Value, String> {
+        let id = self.next_id;
+        self.next_id += 1;
+        let request = json!({ "id": id, "method": method, "params": params });
+        self.socket
+            .send(Message::Text(request.to_string()))
+            .map_err(|e| e.to_string())?;
+
+        let started = Instant::now();
+        while started.elapsed() < IO_TIMEOUT {
+            let text = match self.socket.read().map_err(|e| e.to_string())? {
+                Message::Text(text) => text,
+                Message::Close(_) => return Err("connection closed".to_string()),
+                _ => continue,
+            };
+            let Ok(mut response) = serde_json::from_str::<Value>(&text) else {
+                continue;
+            };
+            if response.get("id").and_then(Value::as_u64) != Some(id) {
+                continue;
+            }
+            if let Some(error) = response.get("error") {
+                let message = error.get("message").and_then(Value::as_str);
+                return Err(message.unwrap_or("CDP error").to_string());
+            }
+            return Ok(response
+                .get_mut("result")
+                .map(Value::take)
+                .unwrap_or(Value::Null));
+        }
+        Err(format!("{} timed out", method))
+    }
+
+    /// 在页面中执行表达式并返回结果值 (支持 Promise)
+    pub(super) fn evaluate(&mut self, expression: &str) -> Result<Value, String> {
+        let mut result = self.call(
+            "Runtime.evaluate",
+            json!({
+                "expression": expression,
+                "returnByValue": true,
+                "awaitPromise": true,
+            }),
+        )?;
+        if let Some(details) = result.get("exceptionDetails") {
+            let text = details
+                .pointer("/exception/description")
+                .or_else(|| details.get("text"))
+                .and_then(Value::as_str)
+                .unwrap_or("exception");
+            return Err(text.to_string());
+        }
+        Ok(result
+            .pointer_mut("/result/value")
+            .map(Value::take)
+            .unwrap_or(Value::Null))
+    }
+}
+
+impl Drop for CdpSession {
+    fn drop(&mut self) {
+        let _ = self.socket.close(None);
+        let _ = self.socket.flush();
+    }
+}
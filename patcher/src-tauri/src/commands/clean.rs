@@ -3,6 +3,7 @@
 //! 提供对话缓存清理功能
 
 use super::i18n::{self, CommandError};
+use super::oplog;
 
 type CleanResult<T> = Result<T, CommandError>;
 
@@ -44,7 +45,7 @@ fn apply_vars(template: String, vars: &[(&str, String)]) -> String {
 }
 
 /// 清理目标配置
-#[derive(serde::Deserialize)]
+#[derive(serde::Deserialize, serde::Serialize)]
 pub struct CleanTargets {
     pub antigravity: bool,
     pub gemini: bool,
@@ -67,7 +68,11 @@ pub fn run_anti_clean(
     locale: Option<String>,
 ) -> Result<String, String> {
     let locale_ref = locale.as_deref();
-    run_anti_clean_internal(force, targets, locale_ref).map_err(|err| err.to_message(locale_ref))
+    let config = serde_json::json!({ "force": force, "targets": &targets });
+    oplog::record("clean", None, Some(config), || {
+        run_anti_clean_internal(force, targets, locale_ref)
+            .map_err(|err| err.to_message(locale_ref))
+    })
 }
 
 fn run_anti_clean_internal(
@@ -168,7 +173,8 @@ fn run_anti_clean_unix(
     // 清理临时脚本
     let _ = fs::remove_file(&script_path);
 
-    // 检查执行结果
+    // 检查执行结果，失败时同样记录脚本输出
+    oplog::output(&format!("{}\n{}", stdout, stderr));
     if !output.status.success() {
         if stderr.is_empty() {
             return Err(CommandError::from(stdout));
@@ -392,7 +398,9 @@ fn run_anti_clean_windows(
     }
 
     output_lines.push(format!("\n{}", clean_text(locale, "cleanBackend.done")));
-    Ok(output_lines.join("\n"))
+    let output = output_lines.join("\n");
+    oplog::output(&output);
+    Ok(output)
 }
 
 /// Windows: 目录定位
@@ -495,6 +503,7 @@ fn backup_file(
             e
         )
     })?;
+    oplog::file("backup", &backup_path);
 
     output_lines.push(apply_vars(
         clean_text(locale, "cleanBackend.logs.backup"),
@@ -524,6 +533,7 @@ fn clean_db(
     }
 
     let (before, after) = sqlite_clean_and_count(db_path, locale)?;
+    oplog::file("clean", db_path);
 
     let name = db_path
         .file_name()
@@ -662,6 +672,7 @@ fn clean_dir_contents(
                     e
                 )
             })?;
+            oplog::file("delete", &item_path);
         } else {
             std::fs::remove_file(&item_path).map_err(|e| {
                 format!(
@@ -671,6 +682,7 @@ fn clean_dir_contents(
                     e
                 )
             })?;
+            oplog::file("delete", &item_path);
         }
     }
 
@@ -700,6 +712,7 @@ fn clean_file(
             e
         )
     })?;
+    oplog::file("delete", path);
 
     output_lines.push(format!(
         "{}: {}",
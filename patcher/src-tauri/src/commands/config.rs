@@ -3,10 +3,18 @@
 //! 处理应用配置的读取和保存
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
 
+use super::atomic::write_atomic;
+use super::features::FeatureConfig;
 use super::i18n::CommandError;
+use super::migrations::{self, APP_CONFIG_VERSION};
+use super::profiles::Profile;
 
 type ConfigResult<T> = Result<T, CommandError>;
 
@@ -15,68 +23,294 @@ fn config_with(_locale: Option<&str>, key: &'static str, vars: &[(&str, String)]
 }
 
 /// 应用配置
-#[derive(Debug, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(default)]
 pub struct AppConfig {
+    /// 配置文件版本，保存时写入当前版本
+    #[serde(rename = "schemaVersion")]
+    pub schema_version: u64,
+
     /// Antigravity 安装路径
     #[serde(rename = "antigravityPath")]
     pub antigravity_path: Option<String>,
 
-    /// 功能开关
-    pub features: FeatureFlags,
+    /// 侧边栏功能配置
+    pub features: FeatureConfig,
+
+    /// 命名的配置方案
+    pub profiles: Vec<Profile>,
+
+    /// 当前启用的配置方案名称
+    #[serde(rename = "activeProfile")]
+    pub active_profile: Option<String>,
+
+    /// 受信任的补丁包签名公钥
+    #[serde(rename = "trustedKeys")]
+    pub trusted_keys: Vec<TrustedKey>,
+
+    /// 允许安装的未签名补丁包 (补丁包 ID -> pack.json 的 SHA-256)
+    /// 补丁包内容变化后需要重新确认
+    #[serde(rename = "unsignedPackOverrides")]
+    pub unsigned_pack_overrides: BTreeMap<String, String>,
+
+    /// 保留的还原点数量，未设置时使用默认值
+    #[serde(rename = "restorePointLimit")]
+    pub restore_point_limit: Option<usize>,
+
+    /// Antigravity 远程调试端口，用于运行时验证与配置热重载，未设置时使用 9222
+    #[serde(rename = "debugPort")]
+    pub debug_port: Option<u16>,
+
+    /// 影子安装: 当前用户无法写入的安装改为修补应用数据目录中的用户副本 (仅 Linux，只读安装自动启用)
+    #[serde(rename = "shadowInstall")]
+    pub shadow_install: bool,
 }
 
-/// 功能开关
-#[derive(Debug, Serialize, Deserialize)]
-#[serde(default)]
-pub struct FeatureFlags {
-    pub mermaid: bool,
-    pub math: bool,
-    #[serde(rename = "copyButton")]
-    pub copy_button: bool,
-    #[serde(rename = "tableColor")]
-    pub table_color: bool,
-    #[serde(rename = "fontSizeEnabled")]
-    pub font_size_enabled: bool,
-    #[serde(rename = "fontSize")]
-    pub font_size: f32,
-}
-
-impl Default for FeatureFlags {
-    fn default() -> Self {
-        Self {
-            mermaid: true,
-            math: true,
-            copy_button: true,
-            table_color: true,
-            font_size_enabled: true,
-            font_size: 20.0,
+/// 补丁包签名公钥
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrustedKey {
+    /// 显示名称 (发布者)
+    pub name: String,
+    /// ed25519 公钥 (base64)
+    #[serde(rename = "publicKey")]
+    pub public_key: String,
+}
+
+/// 指定配置目录的命令行参数 (--config-dir <目录> 或 --config-dir=<目录>)
+const CONFIG_DIR_ARG: &str = "--config-dir";
+
+/// 指定配置目录的环境变量
+const CONFIG_DIR_ENV: &str = "ANTI_POWER_CONFIG_DIR";
+
+/// 便携模式标记文件，位于可执行文件同目录
+const PORTABLE_MARKER: &str = "anti-power.portable";
+
+/// 便携模式下的数据目录名 (与可执行文件同目录)
+const PORTABLE_DIR: &str = "anti-power-data";
+
+/// 配置位置来源
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConfigMode {
+    /// 系统默认的用户配置目录
+    Default,
+    /// 命令行参数或环境变量指定
+    Override,
+    /// 便携模式
+    Portable,
+}
+
+/// 配置与数据目录
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigLocation {
+    pub mode: ConfigMode,
+    #[serde(rename = "configDir")]
+    pub config_dir: PathBuf,
+    #[serde(rename = "dataDir")]
+    pub data_dir: PathBuf,
+}
+
+/// 解析配置位置，优先级: 命令行参数 > 环境变量 > 便携模式 > 系统默认
+/// 指定目录或便携模式时配置、备份、日志和补丁包都放在同一目录下
+fn resolve_location() -> ConfigLocation {
+    let shared = |mode, dir: PathBuf| ConfigLocation {
+        mode,
+        config_dir: dir.clone(),
+        data_dir: dir,
+    };
+
+    if let Some(dir) = config_dir_arg(env::args().skip(1)) {
+        return shared(ConfigMode::Override, dir);
+    }
+    if let Some(dir) = env::var_os(CONFIG_DIR_ENV).filter(|dir| !dir.is_empty()) {
+        return shared(ConfigMode::Override, PathBuf::from(dir));
+    }
+    if let Some(exe_dir) = env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf))
+    {
+        if exe_dir.join(PORTABLE_MARKER).is_file() {
+            return shared(ConfigMode::Portable, exe_dir.join(PORTABLE_DIR));
+        }
+    }
+
+    ConfigLocation {
+        mode: ConfigMode::Default,
+        config_dir: dirs::config_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("anti-power"),
+        data_dir: dirs::data_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("anti-power"),
+    }
+}
+
+fn config_dir_arg(mut args: impl Iterator<Item = String>) -> Option<PathBuf> {
+    while let Some(arg) = args.next() {
+        if arg == CONFIG_DIR_ARG {
+            return args.next().map(PathBuf::from);
+        }
+        if let Some(dir) = arg
+            .strip_prefix(CONFIG_DIR_ARG)
+            .and_then(|rest| rest.strip_prefix('='))
+        {
+            return Some(PathBuf::from(dir));
         }
     }
+    None
+}
+
+fn location() -> &'static ConfigLocation {
+    static LOCATION: OnceLock<ConfigLocation> = OnceLock::new();
+    LOCATION.get_or_init(resolve_location)
+}
+
+/// 获取配置与数据目录
+#[tauri::command]
+pub fn get_config_location() -> ConfigLocation {
+    location().clone()
+}
+
+/// 获取应用配置目录
+fn get_app_config_dir() -> PathBuf {
+    location().config_dir.clone()
+}
+
+/// 获取应用数据目录 (补丁包等较大的数据)
+pub(super) fn get_app_data_dir() -> PathBuf {
+    location().data_dir.clone()
 }
 
 /// 获取配置文件路径
 fn get_config_path() -> PathBuf {
-    dirs::config_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join("anti-power")
-        .join("config.json")
+    get_app_config_dir().join("config.json")
 }
 
-/// 读取配置, 失败时回退到默认值
+/// 获取用户覆盖层目录
+/// 存放 sidebar.css / sidebar.js / manager.css / manager.js，安装时随补丁部署
+pub(super) fn get_overlay_dir() -> PathBuf {
+    get_app_config_dir().join("overlay")
+}
+
+/// 获取用户覆盖层目录 (不存在时创建)
 #[tauri::command]
-pub fn get_config() -> AppConfig {
-    let config_path = get_config_path();
+pub fn get_user_overlay_dir(locale: Option<String>) -> Result<String, String> {
+    let overlay_dir = get_overlay_dir();
+    fs::create_dir_all(&overlay_dir).map_err(|e| {
+        config_with(
+            locale.as_deref(),
+            "configBackend.errors.createOverlayDirFailed",
+            &[("detail", e.to_string())],
+        )
+        .to_message(locale.as_deref())
+    })?;
+    Ok(overlay_dir.to_string_lossy().to_string())
+}
 
-    if config_path.exists() {
-        if let Ok(content) = fs::read_to_string(&config_path) {
-            if let Ok(config) = serde_json::from_str(&content) {
-                return config;
-            }
+/// 配置加载结果
+#[derive(Debug, Serialize)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub enum ConfigLoad {
+    /// 配置文件不存在
+    Missing,
+    /// 读取成功
+    Ok { config: AppConfig },
+    /// 配置文件无法读取或解析，已保留副本
+    Corrupt {
+        detail: String,
+        /// 损坏文件的副本路径，复制失败时为空
+        #[serde(rename = "backupPath")]
+        backup_path: Option<String>,
+    },
+}
+
+impl ConfigLoad {
+    /// 取得配置，文件缺失或损坏时使用默认值
+    pub(super) fn into_config(self) -> AppConfig {
+        match self {
+            ConfigLoad::Ok { config } => config,
+            ConfigLoad::Missing | ConfigLoad::Corrupt { .. } => AppConfig::default(),
         }
     }
+}
+
+/// 读取配置并返回加载状态
+/// 损坏的配置文件会保留带时间戳的副本，避免下次保存时被覆盖丢失
+#[tauri::command]
+pub fn get_config() -> ConfigLoad {
+    load_config()
+}
+
+/// 读取配置, 文件缺失或损坏时回退到默认值
+pub(super) fn read_config() -> AppConfig {
+    load_config().into_config()
+}
+
+/// 读取配置用于修改后保存
+/// 配置文件损坏时拒绝，避免默认值覆盖用户原有的配置
+pub(super) fn load_config_for_update(locale: Option<&str>) -> ConfigResult<AppConfig> {
+    match load_config() {
+        ConfigLoad::Missing => Ok(AppConfig::default()),
+        ConfigLoad::Ok { config } => Ok(config),
+        ConfigLoad::Corrupt { detail, .. } => Err(config_with(
+            locale,
+            "configBackend.errors.configCorrupt",
+            &[("detail", detail)],
+        )),
+    }
+}
+
+fn load_config() -> ConfigLoad {
+    let config_path = get_config_path();
+
+    let content = match fs::read(&config_path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return ConfigLoad::Missing,
+        Err(e) => return corrupt(&config_path, e.to_string()),
+    };
+
+    // 旧版本配置只在内存中按迁移链升级，下次保存时备份旧文件并写回
+    let parsed = serde_json::from_slice(&content)
+        .map_err(|e| e.to_string())
+        .and_then(|mut value| {
+            migrations::migrate_app_config(&mut value)?;
+            serde_json::from_value::<AppConfig>(value).map_err(|e| e.to_string())
+        });
+    match parsed {
+        Ok(config) => ConfigLoad::Ok { config },
+        Err(detail) => corrupt(&config_path, detail),
+    }
+}
 
-    AppConfig::default()
+fn corrupt(config_path: &Path, detail: String) -> ConfigLoad {
+    ConfigLoad::Corrupt {
+        detail,
+        backup_path: preserve_corrupt_file(config_path)
+            .map(|path| path.to_string_lossy().to_string()),
+    }
+}
+
+/// 保留损坏的配置文件副本 (内容相同的副本只保留一份)
+fn preserve_corrupt_file(config_path: &Path) -> Option<PathBuf> {
+    let content = fs::read(config_path).ok()?;
+    let config_dir = config_path.parent()?;
+
+    let existing = fs::read_dir(config_dir).ok()?.flatten().find(|entry| {
+        let name = entry.file_name().to_string_lossy().to_string();
+        name.starts_with("config.corrupt-")
+            && fs::read(entry.path()).is_ok_and(|existing| existing == content)
+    });
+    if let Some(entry) = existing {
+        return Some(entry.path());
+    }
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let backup_path = config_dir.join(format!("config.corrupt-{}.json", timestamp));
+    fs::write(&backup_path, content).ok()?;
+    Some(backup_path)
 }
 
 /// 保存配置
@@ -86,9 +320,29 @@ pub fn save_config(config: AppConfig, locale: Option<String>) -> Result<(), Stri
     save_config_internal(config, locale_ref).map_err(|err| err.to_message(locale_ref))
 }
 
-fn save_config_internal(config: AppConfig, locale: Option<&str>) -> ConfigResult<()> {
+pub(super) fn save_config_internal(
+    mut config: AppConfig,
+    locale: Option<&str>,
+) -> ConfigResult<()> {
     let config_path = get_config_path();
 
+    // 版本不同的配置在覆盖前备份: 旧版本保留迁移前的原文件，新版本安装器写入的配置在降级使用时不丢失新字段
+    if let Some(version) = fs::read(&config_path)
+        .ok()
+        .and_then(|content| serde_json::from_slice(&content).ok())
+        .map(|value| migrations::file_version(&value))
+        .filter(|version| *version != APP_CONFIG_VERSION)
+    {
+        migrations::backup_file(&config_path, "config", version).map_err(|e| {
+            config_with(
+                locale,
+                "configBackend.errors.backupConfigFailed",
+                &[("detail", e.to_string())],
+            )
+        })?;
+    }
+    config.schema_version = APP_CONFIG_VERSION;
+
     // 确保配置目录存在
     if let Some(parent) = config_path.parent() {
         fs::create_dir_all(parent).map_err(|e| {
@@ -108,7 +362,7 @@ fn save_config_internal(config: AppConfig, locale: Option<&str>) -> ConfigResult
         )
     })?;
 
-    fs::write(&config_path, content).map_err(|e| {
+    write_atomic(&config_path, content).map_err(|e| {
         config_with(
             locale,
             "configBackend.errors.saveConfigFailed",
@@ -2,8 +2,12 @@
 //!
 //! 自动检测 Antigravity 安装路径
 //! - Windows: 注册表查询 + 常见路径扫描
-//! - macOS/Linux: 标准路径探测，未命中时返回 None
+//! - macOS: 标准路径探测，未命中时返回 None
+//! - Linux: 按安装发现 (标准路径、.desktop 启动项、PATH、AppImage、Snap) 取第一个可打补丁的安装
 
+use super::appimage;
+#[cfg(target_os = "linux")]
+use super::discovery::{self, Patchability};
 use super::paths;
 use std::path::{Path, PathBuf};
 
@@ -42,6 +46,10 @@ pub fn normalize_antigravity_path(path: String) -> Option<String> {
 }
 
 fn normalize_path(path: &Path) -> Option<String> {
+    // AppImage 保留文件路径，补丁操作时再解包
+    if appimage::is_appimage(path) {
+        return path.to_str().map(|s| s.to_string());
+    }
     paths::normalize_antigravity_root(path)
         .and_then(|normalized| normalized.to_str().map(|s| s.to_string()))
 }
@@ -159,38 +167,11 @@ fn detect_macos() -> Option<String> {
     None
 }
 
-// Linux 实现
+// Linux 实现: 取安装发现结果中第一个可以打补丁的安装
 #[cfg(target_os = "linux")]
 fn detect_linux() -> Option<String> {
-    let standard_paths = [
-        "/usr/share/antigravity",
-        "/usr/share/Antigravity",
-        "/usr/local/share/antigravity",
-        "/opt/antigravity",
-        "/opt/Antigravity",
-        "/usr/lib/antigravity",
-        "/usr/lib64/antigravity",
-    ];
-
-    for path_str in standard_paths {
-        if let Some(normalized) = normalize_path(&PathBuf::from(path_str)) {
-            return Some(normalized);
-        }
-    }
-
-    if let Some(data_dir) = dirs::data_dir() {
-        let user_path = data_dir.join("antigravity");
-        if let Some(normalized) = normalize_path(&user_path) {
-            return Some(normalized);
-        }
-    }
-
-    if let Some(local_data) = dirs::data_local_dir() {
-        let user_path = local_data.join("antigravity");
-        if let Some(normalized) = normalize_path(&user_path) {
-            return Some(normalized);
-        }
-    }
-
-    None
+    discovery::discover()
+        .into_iter()
+        .find(|install| install.patchable != Patchability::Unsupported)
+        .map(|install| install.path)
 }
@@ -0,0 +1,428 @@
+//! 开发同步模块
+//!
+//! 仅在开发构建中可用：监视 patches 目录下的面板文件，
+//! 保存后立即复制到已安装补丁的测试 Antigravity 中，无需反复点击安装
+//! 与安装相同，跳过嵌入排除列表中的文件和未启用的功能模块；源文件删除后同时从安装目录移除
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime};
+
+use super::features::Panel;
+use super::i18n::CommandError;
+use super::modules::{self, MODULES_FILE};
+use super::patch::resolve_antigravity_root;
+use super::paths;
+use crate::embed_exclude::{self, EXCLUDE_FILE};
+use crate::embedded;
+
+type DevSyncResult<T> = Result<T, CommandError>;
+
+/// 轮询间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// 开发同步状态
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DevSyncStatus {
+    pub running: bool,
+    /// 测试安装路径
+    pub target: Option<String>,
+    /// 监视的 patches 目录
+    pub patches_dir: Option<String>,
+    /// 已同步的文件次数
+    pub synced_count: u64,
+    /// 最近一次同步的文件
+    pub last_synced: Vec<String>,
+    pub last_error: Option<String>,
+}
+
+struct DevSyncHandle {
+    stop: Arc<AtomicBool>,
+    thread: JoinHandle<()>,
+}
+
+static HANDLE: Mutex<Option<DevSyncHandle>> = Mutex::new(None);
+
+static STATUS: Mutex<DevSyncStatus> = Mutex::new(DevSyncStatus {
+    running: false,
+    target: None,
+    patches_dir: None,
+    synced_count: 0,
+    last_synced: Vec::new(),
+    last_error: None,
+});
+
+fn dev_sync_text(_locale: Option<&str>, key: &'static str) -> CommandError {
+    CommandError::key(key)
+}
+
+fn dev_sync_with(
+    _locale: Option<&str>,
+    key: &'static str,
+    vars: &[(&str, String)],
+) -> CommandError {
+    CommandError::key_with(key, vars)
+}
+
+/// 开始开发同步
+/// 目标安装需先完成一次补丁安装
+#[tauri::command]
+pub fn start_dev_sync(path: String, locale: Option<String>) -> Result<DevSyncStatus, String> {
+    start_dev_sync_internal(&path, locale.clone()).map_err(|err| err.to_message(locale.as_deref()))
+}
+
+/// 停止开发同步
+#[tauri::command]
+pub fn stop_dev_sync() -> DevSyncStatus {
+    stop_running();
+    let mut status = lock_status();
+    status.running = false;
+    status.clone()
+}
+
+/// 获取开发同步状态
+#[tauri::command]
+pub fn get_dev_sync_status() -> DevSyncStatus {
+    lock_status().clone()
+}
+
+fn start_dev_sync_internal(path: &str, locale: Option<String>) -> DevSyncResult<DevSyncStatus> {
+    let locale_ref = locale.as_deref();
+
+    if !cfg!(debug_assertions) {
+        return Err(dev_sync_text(
+            locale_ref,
+            "devSyncBackend.errors.debugBuildOnly",
+        ));
+    }
+
+    let patches_dir = embedded::find_patches_dir()
+        .ok_or_else(|| dev_sync_text(locale_ref, "patchBackend.errors.patchesDirNotFound"))?;
+    let antigravity_root = resolve_antigravity_root(path, locale_ref)?;
+    // 归档需要整体重新打包，不适合逐个文件同步
+    if paths::resources_archive(&antigravity_root).is_some() {
+        return Err(dev_sync_text(
+            locale_ref,
+            "devSyncBackend.errors.asarUnsupported",
+        ));
+    }
+    let resources_root = paths::resources_app_root(&antigravity_root);
+
+    // 只同步到已安装补丁的目录，避免产生不完整的安装
+    let installed = install_target(&resources_root, "cascade-panel/config.json")
+        .is_some_and(|config_path| config_path.exists());
+    if !installed {
+        return Err(dev_sync_text(
+            locale_ref,
+            "devSyncBackend.errors.notInstalled",
+        ));
+    }
+
+    stop_running();
+
+    let snapshot = scan_patch_files(&patches_dir, &resources_root);
+    {
+        let mut status = lock_status();
+        *status = DevSyncStatus {
+            running: true,
+            target: Some(antigravity_root.to_string_lossy().to_string()),
+            patches_dir: Some(patches_dir.to_string_lossy().to_string()),
+            synced_count: 0,
+            last_synced: Vec::new(),
+            last_error: None,
+        };
+    }
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let thread_stop = Arc::clone(&stop);
+    let thread = thread::spawn(move || {
+        watch_loop(patches_dir, resources_root, snapshot, thread_stop, locale);
+    });
+
+    *HANDLE.lock().unwrap_or_else(|e| e.into_inner()) = Some(DevSyncHandle { stop, thread });
+
+    Ok(lock_status().clone())
+}
+
+fn lock_status() -> std::sync::MutexGuard<'static, DevSyncStatus> {
+    STATUS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// 停止正在运行的同步线程并等待其退出
+fn stop_running() {
+    let handle = HANDLE.lock().unwrap_or_else(|e| e.into_inner()).take();
+    if let Some(handle) = handle {
+        handle.stop.store(true, Ordering::Relaxed);
+        let _ = handle.thread.join();
+    }
+}
+
+/// 轮询 patches 目录，将修改时间变化的文件复制到目标安装，不再需要同步的文件从目标安装删除
+fn watch_loop(
+    patches_dir: PathBuf,
+    resources_root: PathBuf,
+    mut snapshot: HashMap<String, SystemTime>,
+    stop: Arc<AtomicBool>,
+    locale: Option<String>,
+) {
+    while !stop.load(Ordering::Relaxed) {
+        thread::sleep(POLL_INTERVAL);
+
+        let current = scan_patch_files(&patches_dir, &resources_root);
+        let changed: Vec<String> = current
+            .iter()
+            .filter(|(relative_path, modified)| snapshot.get(*relative_path) != Some(modified))
+            .map(|(relative_path, _)| relative_path.clone())
+            .collect();
+        let removed: Vec<String> = snapshot
+            .keys()
+            .filter(|relative_path| !current.contains_key(*relative_path))
+            .cloned()
+            .collect();
+        snapshot = current;
+
+        if changed.is_empty() && removed.is_empty() {
+            continue;
+        }
+
+        let mut synced = Vec::new();
+        let mut last_error = None;
+        for relative_path in changed {
+            match sync_file(&patches_dir, &resources_root, &relative_path) {
+                Ok(true) => synced.push(relative_path),
+                Ok(false) => {}
+                Err(err) => last_error = Some(err.to_message(locale.as_deref())),
+            }
+        }
+        for relative_path in removed {
+            match remove_file(&resources_root, &relative_path) {
+                Ok(true) => synced.push(relative_path),
+                Ok(false) => {}
+                Err(err) => last_error = Some(err.to_message(locale.as_deref())),
+            }
+        }
+
+        let mut status = lock_status();
+        status.synced_count += synced.len() as u64;
+        if !synced.is_empty() {
+            status.last_synced = synced;
+        }
+        if last_error.is_some() {
+            status.last_error = last_error;
+        }
+    }
+}
+
+/// 收集需要同步的补丁文件及其修改时间
+/// 键为相对 patches 目录、以 / 分隔的路径
+/// 跳过嵌入排除列表中的文件 (如 config.json，由"更新配置"负责) 与目标安装未启用的功能模块
+fn scan_patch_files(patches_dir: &Path, resources_root: &Path) -> HashMap<String, SystemTime> {
+    let mut files = HashMap::new();
+
+    for entry in ["cascade-panel.html", "workbench-jetski-agent.html"] {
+        if let Some(modified) = modified_time(&patches_dir.join(entry)) {
+            files.insert(entry.to_string(), modified);
+        }
+    }
+
+    for panel in [Panel::Cascade, Panel::Manager] {
+        collect_dir(
+            &patches_dir.join(panel.dir_name()),
+            panel.dir_name(),
+            &mut files,
+        );
+    }
+
+    let exclude = embed_exclude::read_exclude_list(&patches_dir.join(EXCLUDE_FILE));
+    let excluded_modules = excluded_module_files(patches_dir, resources_root);
+    files.retain(|relative_path, _| {
+        !embed_exclude::is_excluded(relative_path, &exclude)
+            && !excluded_modules.contains(relative_path)
+    });
+    files
+}
+
+/// 按目标安装的面板配置计算未启用的功能模块文件
+/// modules.json 或已安装的 config.json 无法读取时不排除
+fn excluded_module_files(patches_dir: &Path, resources_root: &Path) -> BTreeSet<String> {
+    let mut excluded = BTreeSet::new();
+    for panel in [Panel::Cascade, Panel::Manager] {
+        let manifest_path = format!("{}/{}", panel.dir_name(), MODULES_FILE);
+        let Ok(manifest) = fs::read(patches_dir.join(&manifest_path)) else {
+            continue;
+        };
+        let Some(options) = fs::read(panel.install_dir(resources_root).join("config.json"))
+            .ok()
+            .and_then(|content| serde_json::from_slice::<Map<String, Value>>(&content).ok())
+        else {
+            continue;
+        };
+        if let Ok(files) =
+            modules::excluded_files(&[(manifest_path, manifest)], panel, &options, None)
+        {
+            excluded.extend(files);
+        }
+    }
+    excluded
+}
+
+fn collect_dir(dir: &Path, prefix: &str, files: &mut HashMap<String, SystemTime>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let relative_path = format!("{}/{}", prefix, entry.file_name().to_string_lossy());
+        if path.is_dir() {
+            collect_dir(&path, &relative_path, files);
+        } else if let Some(modified) = modified_time(&path) {
+            files.insert(relative_path, modified);
+        }
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+/// 补丁文件在安装目录中的对应位置
+fn install_target(resources_root: &Path, relative_path: &str) -> Option<PathBuf> {
+    let extensions_dir = resources_root.join("extensions").join("antigravity");
+    let workbench_dir = resources_root
+        .join("out")
+        .join("vs")
+        .join("code")
+        .join("electron-browser")
+        .join("workbench");
+
+    if relative_path == "cascade-panel.html" || relative_path.starts_with("cascade-panel/") {
+        Some(extensions_dir.join(relative_path))
+    } else if relative_path == "workbench-jetski-agent.html"
+        || relative_path.starts_with("manager-panel/")
+    {
+        Some(workbench_dir.join(relative_path))
+    } else {
+        None
+    }
+}
+
+/// 同步单个文件，跳过的文件返回 false
+fn sync_file(
+    patches_dir: &Path,
+    resources_root: &Path,
+    relative_path: &str,
+) -> DevSyncResult<bool> {
+    let Some(target) = install_target(resources_root, relative_path) else {
+        return Ok(false);
+    };
+
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent).map_err(|e| {
+            dev_sync_with(
+                None,
+                "devSyncBackend.errors.copyFailed",
+                &[("detail", format!("{:?}: {}", parent, e))],
+            )
+        })?;
+    }
+
+    fs::copy(patches_dir.join(relative_path), &target).map_err(|e| {
+        dev_sync_with(
+            None,
+            "devSyncBackend.errors.copyFailed",
+            &[("detail", format!("{}: {}", relative_path, e))],
+        )
+    })?;
+
+    Ok(true)
+}
+
+/// 从目标安装删除不再同步的文件，目标不存在时返回 false
+fn remove_file(resources_root: &Path, relative_path: &str) -> DevSyncResult<bool> {
+    let Some(target) = install_target(resources_root, relative_path) else {
+        return Ok(false);
+    };
+    match fs::remove_file(&target) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(dev_sync_with(
+            None,
+            "devSyncBackend.errors.removeFailed",
+            &[("detail", format!("{}: {}", relative_path, e))],
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::test_support::TempDir;
+
+    fn write(path: &Path, content: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn scan_skips_excluded_and_disabled_module_files() {
+        let temp = TempDir::new("scan");
+        let patches_dir = temp.0.join("patches");
+        let resources_root = temp.0.join("resources");
+        write(
+            &patches_dir.join(EXCLUDE_FILE),
+            "# comment\ncascade-panel/config.json\n",
+        );
+        write(
+            &patches_dir.join("cascade-panel/modules.json"),
+            r#"{"modules":{"core":{"files":["core.js"]},"math":{"feature":"math","files":["math.js"],"requires":["core"]}}}"#,
+        );
+        for file in ["core.js", "math.js", "config.json"] {
+            write(&patches_dir.join("cascade-panel").join(file), "");
+        }
+        write(
+            &Panel::Cascade
+                .install_dir(&resources_root)
+                .join("config.json"),
+            r#"{"math":false}"#,
+        );
+
+        let mut files: Vec<String> = scan_patch_files(&patches_dir, &resources_root)
+            .into_keys()
+            .collect();
+        files.sort();
+        assert_eq!(
+            files,
+            ["cascade-panel/core.js", "cascade-panel/modules.json"]
+        );
+
+        write(
+            &Panel::Cascade
+                .install_dir(&resources_root)
+                .join("config.json"),
+            r#"{"math":true}"#,
+        );
+        assert!(
+            scan_patch_files(&patches_dir, &resources_root).contains_key("cascade-panel/math.js")
+        );
+    }
+
+    #[test]
+    fn remove_file_deletes_installed_copy() {
+        let temp = TempDir::new("remove");
+        let target = Panel::Cascade.install_dir(&temp.0).join("math.js");
+        write(&target, "");
+
+        assert!(remove_file(&temp.0, "cascade-panel/math.js").unwrap());
+        assert!(!target.exists());
+        assert!(!remove_file(&temp.0, "cascade-panel/math.js").unwrap());
+        assert!(!remove_file(&temp.0, "unknown.js").unwrap());
+    }
+}
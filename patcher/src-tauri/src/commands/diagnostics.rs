@@ -0,0 +1,358 @@
+//! 诊断包导出模块
+//!
+//! 将系统信息、检测到的安装、补丁状态与校验结果、应用配置、面板配置、
+//! product.json 摘录和最近的操作日志打包为 zip，供反馈问题时附上；
+//! 用户目录与用户名会被替换
+
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+
+use super::asar::Resources;
+use super::config;
+use super::detect::detect_antigravity_path;
+use super::doctor::run_doctor;
+use super::features::Panel;
+use super::i18n::CommandError;
+use super::oplog::get_operation_history;
+use super::patch::{resolve_antigravity_root, CHECKSUMS_TO_REMOVE, MANIFEST_FILE};
+use super::paths;
+use crate::embedded::{self, PatchManifest};
+
+type DiagnosticsResult<T> = Result<T, CommandError>;
+
+/// 诊断包中包含的操作记录数
+const OPERATION_HISTORY_LIMIT: usize = 100;
+
+/// product.json 中与补丁相关的字段
+const PRODUCT_FIELDS: &[&str] = &[
+    "nameShort",
+    "nameLong",
+    "applicationName",
+    "version",
+    "commit",
+    "date",
+    "quality",
+];
+
+fn diagnostics_with(
+    _locale: Option<&str>,
+    key: &'static str,
+    vars: &[(&str, String)],
+) -> CommandError {
+    CommandError::key_with(key, vars)
+}
+
+/// 面板文件与已安装清单的校验结果
+#[derive(Debug, Serialize)]
+struct PanelVerification {
+    panel: &'static str,
+    installed: bool,
+    /// 已安装的补丁集版本
+    version: Option<String>,
+    digest: Option<String>,
+    pack: Option<String>,
+    /// 清单中存在但磁盘上缺失的文件
+    missing: Vec<String>,
+    /// 内容与清单不一致的文件
+    modified: Vec<String>,
+}
+
+/// 导出诊断包
+/// path 为当前选择的安装路径 (可为空)，dest_path 为 zip 文件保存位置
+#[tauri::command]
+pub fn export_diagnostics(
+    path: Option<String>,
+    dest_path: String,
+    locale: Option<String>,
+) -> Result<String, String> {
+    let locale_ref = locale.as_deref();
+    export_diagnostics_internal(path, Path::new(&dest_path), locale_ref)
+        .map_err(|err| err.to_message(locale_ref))?;
+    Ok(dest_path)
+}
+
+fn export_diagnostics_internal(
+    path: Option<String>,
+    dest_path: &Path,
+    locale: Option<&str>,
+) -> DiagnosticsResult<()> {
+    let redactor = Redactor::new();
+    let mut entries: Vec<(&str, String)> = Vec::new();
+    let pretty = |value: &Value| serde_json::to_string_pretty(value).unwrap_or_default();
+
+    entries.push(("system.json", pretty(&system_info())));
+
+    let detected = detect_antigravity_path();
+    let selected = path
+        .as_deref()
+        .and_then(|path| resolve_antigravity_root(path, locale).ok());
+    entries.push((
+        "installs.json",
+        pretty(&installs_info(detected.as_deref(), selected.as_deref())),
+    ));
+
+    let doctor = run_doctor(path.clone(), locale.map(str::to_string));
+    entries.push((
+        "doctor.json",
+        pretty(&serde_json::to_value(doctor).unwrap_or_default()),
+    ));
+
+    if let Some(root) = &selected {
+        let resources = Resources::open(root);
+        let verification: Vec<PanelVerification> = [Panel::Cascade, Panel::Manager]
+            .into_iter()
+            .map(|panel| verify_panel(&resources, panel))
+            .collect();
+        entries.push((
+            "patch-status.json",
+            pretty(&serde_json::to_value(verification).unwrap_or_default()),
+        ));
+        for (name, panel) in [
+            ("panel-configs/cascade-panel.json", Panel::Cascade),
+            ("panel-configs/manager-panel.json", Panel::Manager),
+        ] {
+            if let Ok(content) = resources.read_to_string(&panel.relative_path("config.json")) {
+                entries.push((name, content));
+            }
+        }
+        entries.push(("product.json", pretty(&product_excerpt(&resources))));
+    }
+
+    entries.push(("app-config.json", pretty(&sanitized_app_config())));
+
+    let history: Vec<String> = get_operation_history(Some(OPERATION_HISTORY_LIMIT))
+        .iter()
+        .filter_map(|entry| serde_json::to_string(entry).ok())
+        .collect();
+    entries.push(("operations.jsonl", history.join("\n")));
+
+    write_bundle(dest_path, &entries, &redactor).map_err(|e| {
+        diagnostics_with(
+            locale,
+            "diagnosticsBackend.errors.writeFailed",
+            &[("detail", e.to_string())],
+        )
+    })
+}
+
+fn system_info() -> Value {
+    let patch_set = embedded::manifest(|_| false);
+    json!({
+        "os": std::env::consts::OS,
+        "family": std::env::consts::FAMILY,
+        "arch": std::env::consts::ARCH,
+        "osVersion": os_version(),
+        "installerVersion": env!("CARGO_PKG_VERSION"),
+        "patchSet": { "version": patch_set.version, "digest": patch_set.digest },
+        "configLocation": config::get_config_location(),
+    })
+}
+
+/// 读取操作系统版本，失败时返回 None
+fn os_version() -> Option<String> {
+    #[cfg(target_os = "linux")]
+    {
+        let content = fs::read_to_string("/etc/os-release").ok()?;
+        content
+            .lines()
+            .find_map(|line| line.strip_prefix("PRETTY_NAME="))
+            .map(|name| name.trim_matches('"').to_string())
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let output = std::process::Command::new("sw_vers")
+            .arg("-productVersion")
+            .output()
+            .ok()?;
+        Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+        let output = std::process::Command::new("cmd")
+            .args(["/C", "ver"])
+            .creation_flags(CREATE_NO_WINDOW)
+            .output()
+            .ok()?;
+        Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+    {
+        None
+    }
+}
+
+fn installs_info(detected: Option<&str>, selected: Option<&Path>) -> Value {
+    let describe = |root: &Path| {
+        let resources = Resources::open(root);
+        json!({
+            "path": root,
+            "version": resources.antigravity_version(),
+            "asar": paths::resources_archive(root).is_some(),
+            "patched": resources.exists(&Panel::Cascade.relative_path("config.json")),
+        })
+    };
+    json!({
+        "detected": detected.map(|path| describe(Path::new(path))),
+        "selected": selected.map(describe),
+    })
+}
+
+/// 按已安装的清单校验面板文件
+fn verify_panel(resources: &Resources, panel: Panel) -> PanelVerification {
+    let manifest: Option<PatchManifest> = resources
+        .read(&panel.relative_path(MANIFEST_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_slice(&content).ok());
+
+    let mut verification = PanelVerification {
+        panel: panel.dir_name(),
+        installed: resources.exists(&panel.relative_path("config.json")),
+        version: None,
+        digest: None,
+        pack: None,
+        missing: Vec::new(),
+        modified: Vec::new(),
+    };
+    let Some(manifest) = manifest else {
+        return verification;
+    };
+
+    for file in &manifest.files {
+        match resources.read(&format!("{}/{}", panel.base_dir(), file.path)) {
+            Ok(bytes) if embedded::sha256_hex(&bytes) == file.sha256 => {}
+            Ok(_) => verification.modified.push(file.path.clone()),
+            Err(_) => verification.missing.push(file.path.clone()),
+        }
+    }
+    verification.version = Some(manifest.version);
+    verification.digest = Some(manifest.digest);
+    verification.pack = manifest.pack;
+    verification
+}
+
+/// product.json 中与补丁相关的字段及校验和条目
+fn product_excerpt(resources: &Resources) -> Value {
+    let Some(product) = resources
+        .read("product.json")
+        .ok()
+        .and_then(|content| serde_json::from_slice::<Value>(&content).ok())
+    else {
+        return Value::Null;
+    };
+
+    let mut excerpt: Map<String, Value> = PRODUCT_FIELDS
+        .iter()
+        .filter_map(|field| {
+            product
+                .get(*field)
+                .map(|value| (field.to_string(), value.clone()))
+        })
+        .collect();
+    let checksums: Map<String, Value> = CHECKSUMS_TO_REMOVE
+        .iter()
+        .map(|key| {
+            let value = product
+                .get("checksums")
+                .and_then(|checksums| checksums.get(*key))
+                .cloned()
+                .unwrap_or(Value::Null);
+            (key.to_string(), value)
+        })
+        .collect();
+    excerpt.insert("checksums".to_string(), Value::Object(checksums));
+    excerpt.insert(
+        "hasChecksums".to_string(),
+        Value::Bool(product.get("checksums").is_some()),
+    );
+    Value::Object(excerpt)
+}
+
+/// 应用配置 (不含补丁包签名公钥与未签名补丁包的哈希)
+fn sanitized_app_config() -> Value {
+    let mut config = serde_json::to_value(config::read_config()).unwrap_or_default();
+    if let Some(map) = config.as_object_mut() {
+        for key in ["trustedKeys", "unsignedPackOverrides"] {
+            if let Some(value) = map.get_mut(key) {
+                let count = match value {
+                    Value::Array(items) => items.len(),
+                    Value::Object(items) => items.len(),
+                    _ => 0,
+                };
+                *value = json!({ "count": count });
+            }
+        }
+    }
+    config
+}
+
+fn write_bundle(
+    dest_path: &Path,
+    entries: &[(&str, String)],
+    redactor: &Redactor,
+) -> std::io::Result<()> {
+    let file = File::create(dest_path)?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+
+    for (name, content) in entries {
+        zip.start_file(*name, options)?;
+        zip.write_all(redactor.redact(content).as_bytes())?;
+    }
+    zip.finish()?;
+    Ok(())
+}
+
+/// 替换用户目录与用户名
+struct Redactor {
+    /// (原文, 替换文本)，按长度从长到短
+    replacements: Vec<(String, &'static str)>,
+}
+
+impl Redactor {
+    fn new() -> Self {
+        let mut replacements = Vec::new();
+
+        if let Some(home) = dirs::home_dir() {
+            let home = home.to_string_lossy().to_string();
+            // JSON 中 Windows 路径的反斜杠会被转义
+            replacements.push((home.replace('\\', "\\\\"), "~"));
+            replacements.push((home.replace('\\', "/"), "~"));
+            replacements.push((home, "~"));
+        }
+
+        let user = std::env::var("USER")
+            .or_else(|_| std::env::var("USERNAME"))
+            .ok()
+            .or_else(|| {
+                dirs::home_dir().and_then(|home| {
+                    home.file_name()
+                        .map(|name| name.to_string_lossy().to_string())
+                })
+            });
+        // 过短的用户名容易误伤普通文本
+        if let Some(user) = user.filter(|user| user.chars().count() >= 3) {
+            replacements.push((user, "<user>"));
+        }
+
+        replacements.retain(|(from, _)| !from.is_empty());
+        replacements.sort_by_key(|(from, _)| std::cmp::Reverse(from.len()));
+        replacements.dedup_by(|a, b| a.0 == b.0);
+        Self { replacements }
+    }
+
+    fn redact(&self, text: &str) -> String {
+        self.replacements
+            .iter()
+            .fold(text.to_string(), |text, (from, to)| text.replace(from, to))
+    }
+}
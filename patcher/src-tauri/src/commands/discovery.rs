@@ -0,0 +1,465 @@
+//! 安装发现
+//!
+//! 列出本机所有可找到的 Antigravity 安装及其能否打补丁
+//! Linux 依次查找: 标准路径、XDG .desktop 启动项、PATH 中的 antigravity、AppImage、Flatpak、Snap
+//! 其他平台只返回自动检测到的路径
+
+// AppImage、Flatpak、Snap 等结果只在 Linux 上构造
+#![cfg_attr(not(target_os = "linux"), allow(dead_code))]
+
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::appimage;
+use super::asar::Resources;
+use super::config;
+use super::patch;
+use super::paths;
+use super::shadow;
+
+/// 发现方式
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DiscoverySource {
+    /// 标准安装路径
+    Standard,
+    /// XDG .desktop 启动项的 Exec
+    DesktopEntry,
+    /// PATH 中的 antigravity (解析符号链接)
+    Path,
+    /// 常见目录中的 AppImage
+    AppImage,
+    /// Flatpak 应用目录
+    Flatpak,
+    /// Snap 挂载目录
+    Snap,
+}
+
+/// 打包格式
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum InstallFormat {
+    /// 普通目录 (deb/rpm/tar.gz、Windows/macOS 安装)
+    Directory,
+    AppImage,
+    Flatpak,
+    Snap,
+    /// Nix store 中的安装
+    Nix,
+}
+
+/// 能否打补丁
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Patchability {
+    /// 直接修改安装目录
+    Direct,
+    /// 需要管理员权限
+    Privileged,
+    /// 只能修补用户副本 (影子安装)
+    Shadow,
+    /// 解包后修补 (AppImage)
+    Extract,
+    /// 不支持
+    Unsupported,
+}
+
+/// 发现的安装
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscoveredInstall {
+    /// 安装根目录，AppImage 为文件路径
+    pub path: String,
+    pub source: DiscoverySource,
+    pub format: InstallFormat,
+    pub patchable: Patchability,
+    pub version: Option<String>,
+}
+
+impl DiscoveredInstall {
+    /// 普通目录形式的安装
+    fn directory(root: &Path, source: DiscoverySource) -> Self {
+        let canonical = fs::canonicalize(root).unwrap_or_else(|_| root.to_path_buf());
+        let format = if canonical.starts_with("/nix/store") {
+            InstallFormat::Nix
+        } else if canonical.starts_with("/snap") {
+            InstallFormat::Snap
+        } else {
+            InstallFormat::Directory
+        };
+        Self {
+            path: root.display().to_string(),
+            source,
+            format,
+            patchable: directory_patchability(root),
+            version: Resources::open(root).antigravity_version(),
+        }
+    }
+
+    fn appimage(path: &Path, source: DiscoverySource) -> Self {
+        Self {
+            path: path.display().to_string(),
+            source,
+            format: InstallFormat::AppImage,
+            patchable: Patchability::Extract,
+            version: None,
+        }
+    }
+}
+
+/// 目录安装的补丁方式: 影子安装优先，其次看 resources 目录是否可写
+fn directory_patchability(root: &Path) -> Patchability {
+    if shadow::is_enabled(root) {
+        return Patchability::Shadow;
+    }
+    let resources_dir = paths::resources_app_root(root)
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| root.to_path_buf());
+    match patch::can_write_dir(&resources_dir, None) {
+        Ok(true) => Patchability::Direct,
+        _ => Patchability::Privileged,
+    }
+}
+
+/// 列出本机发现的所有 Antigravity 安装
+#[tauri::command]
+pub fn discover_antigravity_installs() -> Vec<DiscoveredInstall> {
+    discover()
+}
+
+/// 发现结果按查找顺序排列，同一安装只保留第一次发现
+pub(super) fn discover() -> Vec<DiscoveredInstall> {
+    let mut installs = Vec::new();
+
+    #[cfg(target_os = "linux")]
+    {
+        linux::standard(&mut installs);
+        linux::desktop_entries(&mut installs);
+        linux::path_binaries(&mut installs);
+        linux::appimages(&mut installs);
+        linux::flatpak(&mut installs);
+        linux::snap(&mut installs);
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    if let Some(path) = super::detect::detect_antigravity_path() {
+        installs.push(DiscoveredInstall::directory(
+            Path::new(&path),
+            DiscoverySource::Standard,
+        ));
+    }
+
+    // 去重，并排除本程序自己的影子副本与 AppImage 解包目录
+    let data_dir = config::get_app_data_dir();
+    let mut seen = HashSet::new();
+    installs.retain(|install| {
+        let path = PathBuf::from(&install.path);
+        let canonical = fs::canonicalize(&path).unwrap_or(path);
+        !canonical.starts_with(&data_dir) && seen.insert(canonical)
+    });
+    installs
+}
+
+/// 将命中的路径 (安装目录、其中的文件或 AppImage) 转换为发现结果
+fn resolve(path: &Path, source: DiscoverySource) -> Option<DiscoveredInstall> {
+    if appimage::is_appimage(path) {
+        return Some(DiscoveredInstall::appimage(path, source));
+    }
+    paths::normalize_antigravity_root(path).map(|root| DiscoveredInstall::directory(&root, source))
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use super::*;
+
+    /// 发行版包与手动解压的标准位置
+    const STANDARD_PATHS: &[&str] = &[
+        "/usr/share/antigravity",
+        "/usr/share/Antigravity",
+        "/usr/local/share/antigravity",
+        "/opt/antigravity",
+        "/opt/Antigravity",
+        "/usr/lib/antigravity",
+        "/usr/lib64/antigravity",
+    ];
+
+    /// Flatpak 包内可能的安装位置 (相对 files 目录)
+    const FLATPAK_LAYOUTS: &[&str] = &[
+        "",
+        "antigravity",
+        "extra",
+        "extra/antigravity",
+        "share/antigravity",
+        "lib/antigravity",
+    ];
+
+    /// Snap 包内可能的安装位置 (相对 current 目录)
+    const SNAP_LAYOUTS: &[&str] = &[
+        "",
+        "antigravity",
+        "usr/share/antigravity",
+        "usr/lib/antigravity",
+        "opt/antigravity",
+    ];
+
+    fn contains_antigravity(name: &str) -> bool {
+        name.to_ascii_lowercase().contains("antigravity")
+    }
+
+    pub(super) fn standard(installs: &mut Vec<DiscoveredInstall>) {
+        let user_paths = [dirs::data_dir(), dirs::data_local_dir()]
+            .into_iter()
+            .flatten()
+            .map(|dir| dir.join("antigravity"));
+        installs.extend(
+            STANDARD_PATHS
+                .iter()
+                .map(PathBuf::from)
+                .chain(user_paths)
+                .filter_map(|path| resolve(&path, DiscoverySource::Standard)),
+        );
+    }
+
+    /// XDG 数据目录: $XDG_DATA_HOME、$XDG_DATA_DIRS 与 Flatpak 导出目录
+    fn xdg_data_dirs() -> Vec<PathBuf> {
+        let mut dirs: Vec<PathBuf> = dirs::data_dir().into_iter().collect();
+        let system = std::env::var("XDG_DATA_DIRS")
+            .ok()
+            .filter(|value| !value.is_empty())
+            .unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+        dirs.extend(std::env::split_paths(&system));
+        dirs.push(PathBuf::from("/var/lib/flatpak/exports/share"));
+        if let Some(data_dir) = dirs::data_dir() {
+            dirs.push(data_dir.join("flatpak").join("exports").join("share"));
+        }
+        dirs
+    }
+
+    /// 解析 applications 目录中文件名或 Name 含 antigravity 的 .desktop 启动项
+    pub(super) fn desktop_entries(installs: &mut Vec<DiscoveredInstall>) {
+        for dir in xdg_data_dirs() {
+            let Ok(entries) = fs::read_dir(dir.join("applications")) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.extension().is_none_or(|ext| ext != "desktop") {
+                    continue;
+                }
+                let Ok(content) = fs::read_to_string(&path) else {
+                    continue;
+                };
+                let file_name = entry.file_name().to_string_lossy().to_string();
+                let name = desktop_value(&content, "Name").unwrap_or_default();
+                if !contains_antigravity(&file_name) && !contains_antigravity(&name) {
+                    continue;
+                }
+                let Some(exec) = desktop_value(&content, "Exec") else {
+                    continue;
+                };
+                if let Some(install) = resolve_exec(&exec) {
+                    installs.push(install);
+                }
+            }
+        }
+    }
+
+    /// [Desktop Entry] 段中的键值
+    fn desktop_value(content: &str, key: &str) -> Option<String> {
+        let mut in_entry = false;
+        for line in content.lines().map(str::trim) {
+            if line.starts_with('[') {
+                in_entry = line == "[Desktop Entry]";
+                continue;
+            }
+            if !in_entry {
+                continue;
+            }
+            if let Some((name, value)) = line.split_once('=') {
+                if name.trim() == key {
+                    return Some(value.trim().to_string());
+                }
+            }
+        }
+        None
+    }
+
+    /// 拆分 Exec 字段 (支持双引号与反斜杠转义，忽略 %f 等字段代码)
+    fn split_exec(exec: &str) -> Vec<String> {
+        let mut args = Vec::new();
+        let mut current = String::new();
+        let mut quoted = false;
+        let mut chars = exec.chars();
+        while let Some(ch) = chars.next() {
+            match ch {
+                '"' => quoted = !quoted,
+                '\\' if quoted => current.extend(chars.next()),
+                ' ' | '\t' if !quoted => {
+                    if !current.is_empty() {
+                        args.push(std::mem::take(&mut current));
+                    }
+                }
+                _ => current.push(ch),
+            }
+        }
+        if !current.is_empty() {
+            args.push(current);
+        }
+        args.retain(|arg| !(arg.len() == 2 && arg.starts_with('%')));
+        args
+    }
+
+    fn resolve_exec(exec: &str) -> Option<DiscoveredInstall> {
+        let args = split_exec(exec);
+        // 跳过 env 与环境变量赋值
+        let mut args = args
+            .iter()
+            .skip_while(|arg| *arg == "env" || arg.contains('='))
+            .map(String::as_str);
+        let program = args.next()?;
+
+        // flatpak run [选项] <应用 ID>
+        if Path::new(program).file_name()? == "flatpak" {
+            let app_id = args.filter(|arg| !arg.starts_with('-')).nth(1)?;
+            return flatpak_dirs()
+                .into_iter()
+                .map(|dir| dir.join(app_id))
+                .find(|dir| dir.is_dir())
+                .map(|dir| DiscoveredInstall {
+                    source: DiscoverySource::DesktopEntry,
+                    ..flatpak_install(&dir)
+                });
+        }
+
+        let binary = if program.contains('/') {
+            PathBuf::from(program)
+        } else {
+            find_in_path(program)?
+        };
+        resolve_binary(&binary, DiscoverySource::DesktopEntry)
+    }
+
+    fn find_in_path(program: &str) -> Option<PathBuf> {
+        let path = std::env::var_os("PATH")?;
+        std::env::split_paths(&path)
+            .map(|dir| dir.join(program))
+            .find(|candidate| candidate.is_file())
+    }
+
+    /// 可执行文件解析符号链接后所在的安装 (AppImage 直接返回)
+    fn resolve_binary(binary: &Path, source: DiscoverySource) -> Option<DiscoveredInstall> {
+        let canonical = fs::canonicalize(binary).ok()?;
+        resolve(&canonical, source)
+    }
+
+    pub(super) fn path_binaries(installs: &mut Vec<DiscoveredInstall>) {
+        let Some(path) = std::env::var_os("PATH") else {
+            return;
+        };
+        installs.extend(
+            std::env::split_paths(&path)
+                .flat_map(|dir| ["antigravity", "Antigravity"].map(|name| dir.join(name)))
+                .filter(|candidate| candidate.is_file())
+                .filter_map(|candidate| resolve_binary(&candidate, DiscoverySource::Path)),
+        );
+    }
+
+    /// 常见 AppImage 存放目录中文件名含 antigravity 的 AppImage
+    pub(super) fn appimages(installs: &mut Vec<DiscoveredInstall>) {
+        let mut dirs = vec![PathBuf::from("/opt")];
+        if let Some(home) = dirs::home_dir() {
+            dirs.extend(
+                ["Applications", ".local/bin", "Downloads"]
+                    .iter()
+                    .map(|dir| home.join(dir)),
+            );
+        }
+
+        installs.extend(
+            dirs.iter()
+                .filter_map(|dir| fs::read_dir(dir).ok())
+                .flat_map(|entries| entries.flatten())
+                .map(|entry| entry.path())
+                .filter(|path| {
+                    path.file_name()
+                        .and_then(|name| name.to_str())
+                        .is_some_and(contains_antigravity)
+                })
+                .filter(|path| appimage::is_appimage(path))
+                .map(|path| DiscoveredInstall::appimage(&path, DiscoverySource::AppImage)),
+        );
+    }
+
+    /// 系统与用户级 Flatpak 应用目录
+    fn flatpak_dirs() -> Vec<PathBuf> {
+        let mut dirs = vec![PathBuf::from("/var/lib/flatpak/app")];
+        if let Some(data_dir) = dirs::data_dir() {
+            dirs.push(data_dir.join("flatpak").join("app"));
+        }
+        dirs
+    }
+
+    /// Flatpak 的文件与 OSTree 仓库共用硬链接且在沙箱中运行，修改会破坏仓库校验，不支持打补丁
+    fn flatpak_install(app_dir: &Path) -> DiscoveredInstall {
+        let files = app_dir.join("current").join("active").join("files");
+        let root = FLATPAK_LAYOUTS
+            .iter()
+            .map(|layout| files.join(layout))
+            .find(|candidate| paths::is_valid_antigravity_root(candidate));
+        DiscoveredInstall {
+            path: root.as_deref().unwrap_or(&files).display().to_string(),
+            source: DiscoverySource::Flatpak,
+            format: InstallFormat::Flatpak,
+            patchable: Patchability::Unsupported,
+            version: root.and_then(|root| {
+                paths::read_antigravity_version(&paths::resources_app_root(&root))
+            }),
+        }
+    }
+
+    pub(super) fn flatpak(installs: &mut Vec<DiscoveredInstall>) {
+        installs.extend(
+            flatpak_dirs()
+                .iter()
+                .filter_map(|dir| fs::read_dir(dir).ok())
+                .flat_map(|entries| entries.flatten())
+                .filter(|entry| contains_antigravity(&entry.file_name().to_string_lossy()))
+                .map(|entry| flatpak_install(&entry.path())),
+        );
+    }
+
+    /// Snap 挂载为只读 squashfs，找到安装目录时通过影子安装修补
+    pub(super) fn snap(installs: &mut Vec<DiscoveredInstall>) {
+        let Ok(entries) = fs::read_dir("/snap") else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name == "bin" || !contains_antigravity(&name) {
+                continue;
+            }
+            let current = entry.path().join("current");
+            let root = SNAP_LAYOUTS
+                .iter()
+                .map(|layout| current.join(layout))
+                .find(|candidate| paths::is_valid_antigravity_root(candidate));
+            installs.push(match root {
+                Some(root) => DiscoveredInstall {
+                    format: InstallFormat::Snap,
+                    patchable: Patchability::Shadow,
+                    ..DiscoveredInstall::directory(&root, DiscoverySource::Snap)
+                },
+                None => DiscoveredInstall {
+                    path: current.display().to_string(),
+                    source: DiscoverySource::Snap,
+                    format: InstallFormat::Snap,
+                    patchable: Patchability::Unsupported,
+                    version: None,
+                },
+            });
+        }
+    }
+}
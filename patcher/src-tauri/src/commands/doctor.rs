@@ -0,0 +1,421 @@
+//! 环境诊断模块
+//!
+//! 逐项检查安装路径、目录权限、提权工具、脚本依赖、备份、product.json 校验和、
+//! 版本兼容性与 Antigravity 运行状态，返回每项的结果及修复建议
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+use super::asar::Resources;
+use super::features::Panel;
+use super::i18n;
+use super::packs::{compare_versions, parse_version};
+use super::patch::{can_write_dir, resolve_antigravity_root, CHECKSUMS_TO_REMOVE};
+use super::paths;
+use super::process;
+
+/// 经过测试的 Antigravity 版本 (与 README 保持一致)
+const SUPPORTED_ANTIGRAVITY_VERSION: &str = "1.16.5";
+
+/// 检查结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+/// 单项检查
+#[derive(Debug, Serialize)]
+pub struct DoctorCheck {
+    /// 检查项标识 (path / writable / elevation / python / sqlite / backup / checksums / version / running)
+    pub id: &'static str,
+    pub status: CheckStatus,
+    pub message: String,
+    /// 修复建议
+    pub fix: Option<String>,
+}
+
+/// 诊断报告
+#[derive(Debug, Serialize)]
+pub struct DoctorReport {
+    /// 最严重的检查结果
+    pub status: CheckStatus,
+    /// 规范化后的安装路径
+    #[serde(rename = "antigravityRoot")]
+    pub antigravity_root: Option<String>,
+    #[serde(rename = "antigravityVersion")]
+    pub antigravity_version: Option<String>,
+    pub checks: Vec<DoctorCheck>,
+}
+
+/// 收集检查结果
+pub(super) struct Checks<'a> {
+    /// 文案 key 前缀
+    prefix: &'static str,
+    pub(super) locale: Option<&'a str>,
+    pub(super) checks: Vec<DoctorCheck>,
+}
+
+impl<'a> Checks<'a> {
+    pub(super) fn new(prefix: &'static str, locale: Option<&'a str>) -> Self {
+        Self {
+            prefix,
+            locale,
+            checks: Vec::new(),
+        }
+    }
+
+    /// 文案 key 为 <prefix>.<id>.<outcome>，修复建议为 <prefix>.<id>.<outcome>Fix
+    pub(super) fn push(
+        &mut self,
+        id: &'static str,
+        status: CheckStatus,
+        outcome: &str,
+        vars: &[(&str, String)],
+    ) {
+        let key = format!("{}.{}.{}", self.prefix, id, outcome);
+        let fix_key = format!("{}Fix", key);
+        let fix = (status != CheckStatus::Pass)
+            .then(|| i18n::text_with(self.locale, &fix_key, vars))
+            .filter(|fix| *fix != fix_key);
+        self.checks.push(DoctorCheck {
+            id,
+            status,
+            message: i18n::text_with(self.locale, &key, vars),
+            fix,
+        });
+    }
+
+    /// 最严重的检查结果
+    pub(super) fn status(&self) -> CheckStatus {
+        self.checks
+            .iter()
+            .map(|check| check.status)
+            .max()
+            .unwrap_or(CheckStatus::Pass)
+    }
+}
+
+/// 运行环境诊断
+#[tauri::command]
+pub fn run_doctor(path: Option<String>, locale: Option<String>) -> DoctorReport {
+    let locale = locale.as_deref();
+    let mut checks = Checks::new("doctorBackend", locale);
+
+    // 只读解析，不解包 AppImage、不同步影子副本
+    let resolved = path
+        .as_deref()
+        .map(|path| resolve_antigravity_root(path, locale));
+    let root = resolved
+        .as_ref()
+        .and_then(|resolved| resolved.as_ref().ok().cloned());
+    let mut version = None;
+
+    match &resolved {
+        Some(Ok(root)) => {
+            checks.push(
+                "path",
+                CheckStatus::Pass,
+                "valid",
+                &[("path", root.display().to_string())],
+            );
+            let resources = Resources::open(root);
+            version = resources.antigravity_version();
+            // app.asar 布局写回的是 resources 目录中的归档
+            let writable_dirs = match paths::resources_archive(root) {
+                Some(archive) => archive
+                    .parent()
+                    .map(Path::to_path_buf)
+                    .into_iter()
+                    .collect(),
+                None => patch_dirs(&paths::resources_app_root(root)),
+            };
+            check_writable(&mut checks, writable_dirs);
+            check_backups(&mut checks, &resources);
+            check_checksums(&mut checks, &resources);
+            check_version(&mut checks, version.as_deref());
+        }
+        Some(Err(err)) => {
+            checks.push(
+                "path",
+                CheckStatus::Fail,
+                "invalid",
+                &[
+                    ("path", path.clone().unwrap_or_default()),
+                    ("detail", err.to_message(locale)),
+                ],
+            );
+        }
+        None => {
+            checks.push("path", CheckStatus::Fail, "missing", &[]);
+        }
+    }
+
+    check_elevation(&mut checks);
+    check_python(&mut checks);
+    check_sqlite(&mut checks);
+    check_running(&mut checks, root.as_deref());
+
+    DoctorReport {
+        status: checks.status(),
+        antigravity_root: root.map(|root| root.to_string_lossy().to_string()),
+        antigravity_version: version,
+        checks: checks.checks,
+    }
+}
+
+/// 补丁写入的目录
+fn patch_dirs(resources_root: &Path) -> Vec<PathBuf> {
+    vec![
+        resources_root.to_path_buf(),
+        resources_root.join("extensions").join("antigravity"),
+        resources_root
+            .join("out")
+            .join("vs")
+            .join("code")
+            .join("electron-browser")
+            .join("workbench"),
+    ]
+}
+
+fn check_writable(checks: &mut Checks, dirs: Vec<PathBuf>) {
+    for dir in dirs {
+        let vars = [("dir", dir.display().to_string())];
+        match can_write_dir(&dir, checks.locale) {
+            Ok(true) => checks.push("writable", CheckStatus::Pass, "writable", &vars),
+            // macOS/Linux 会通过提权脚本写入，Windows 需要以管理员身份运行
+            Ok(false) if cfg!(any(target_os = "macos", target_os = "linux")) => {
+                checks.push("writable", CheckStatus::Warn, "needsElevation", &vars)
+            }
+            Ok(false) => checks.push("writable", CheckStatus::Fail, "denied", &vars),
+            Err(err) => checks.push(
+                "writable",
+                CheckStatus::Fail,
+                "error",
+                &[
+                    ("dir", dir.display().to_string()),
+                    ("detail", err.to_message(checks.locale)),
+                ],
+            ),
+        }
+    }
+}
+
+/// 面板入口文件与备份的对应关系 (相对 resources/app 的路径)
+fn panel_entry(panel: Panel) -> (String, String) {
+    let entry = match panel {
+        Panel::Cascade => "cascade-panel.html",
+        Panel::Manager => "workbench-jetski-agent.html",
+    };
+    let entry = format!("{}/{}", panel.base_dir(), entry);
+    let backup = format!("{}.bak", entry);
+    (entry, backup)
+}
+
+/// 入口文件是否引用了补丁目录 (即已被补丁替换)
+fn references_panel(resources: &Resources, path: &str, panel: Panel) -> bool {
+    resources
+        .read_to_string(path)
+        .map(|content| content.contains(&format!("./{}/", panel.dir_name())))
+        .unwrap_or(false)
+}
+
+fn check_backups(checks: &mut Checks, resources: &Resources) {
+    for panel in [Panel::Cascade, Panel::Manager] {
+        let (entry, backup) = panel_entry(panel);
+        let vars = [
+            ("panel", panel.dir_name().to_string()),
+            (
+                "path",
+                resources.display_path(&backup).display().to_string(),
+            ),
+        ];
+        let patched = references_panel(resources, &entry, panel);
+        let panel_dir = format!("{}/{}", panel.base_dir(), panel.dir_name());
+
+        if resources.exists(&backup) && references_panel(resources, &backup, panel) {
+            // 备份本身是补丁文件，卸载无法恢复原版
+            checks.push("backup", CheckStatus::Fail, "backupPatched", &vars);
+        } else if patched && !resources.exists(&panel_dir) {
+            checks.push("backup", CheckStatus::Fail, "panelMissing", &vars);
+        } else if patched && !resources.exists(&backup) {
+            checks.push("backup", CheckStatus::Fail, "backupMissing", &vars);
+        } else if patched {
+            checks.push("backup", CheckStatus::Pass, "patched", &vars);
+        } else {
+            checks.push("backup", CheckStatus::Pass, "original", &vars);
+        }
+    }
+}
+
+fn check_checksums(checks: &mut Checks, resources: &Resources) {
+    let product_json_path = resources.display_path("product.json");
+    let vars = [("path", product_json_path.display().to_string())];
+    let Some(json) = resources
+        .read("product.json")
+        .ok()
+        .and_then(|content| serde_json::from_slice::<serde_json::Value>(&content).ok())
+    else {
+        checks.push("checksums", CheckStatus::Warn, "unreadable", &vars);
+        return;
+    };
+
+    let stale: Vec<&str> = CHECKSUMS_TO_REMOVE
+        .iter()
+        .copied()
+        .filter(|key| {
+            json.pointer(&format!("/checksums/{}", key.replace('/', "~1")))
+                .is_some()
+        })
+        .collect();
+    let (entry, _) = panel_entry(Panel::Manager);
+
+    if stale.is_empty() {
+        checks.push("checksums", CheckStatus::Pass, "clean", &vars);
+    } else if references_panel(resources, &entry, Panel::Manager) {
+        // 入口已修改但校验和仍在，Antigravity 会提示"已损坏"
+        checks.push(
+            "checksums",
+            CheckStatus::Fail,
+            "stale",
+            &[("files", stale.join(", "))],
+        );
+    } else {
+        checks.push("checksums", CheckStatus::Pass, "original", &vars);
+    }
+}
+
+fn check_version(checks: &mut Checks, version: Option<&str>) {
+    let supported = SUPPORTED_ANTIGRAVITY_VERSION.to_string();
+    let Some(version) = version else {
+        checks.push(
+            "version",
+            CheckStatus::Warn,
+            "unknown",
+            &[("supported", supported)],
+        );
+        return;
+    };
+
+    let vars = [("version", version.to_string()), ("supported", supported)];
+    // 只比较主次版本号，补丁版本的差异通常不影响补丁
+    let current = parse_version(version);
+    let tested = parse_version(SUPPORTED_ANTIGRAVITY_VERSION);
+    let minor = |v: &[u64]| v.iter().take(2).copied().collect::<Vec<_>>();
+    if compare_versions(&minor(&current), &minor(&tested)).is_eq() {
+        checks.push("version", CheckStatus::Pass, "supported", &vars);
+    } else {
+        checks.push("version", CheckStatus::Warn, "untested", &vars);
+    }
+}
+
+/// 在 PATH 中查找可执行文件
+pub(super) fn command_exists(name: &str) -> bool {
+    let Some(path) = std::env::var_os("PATH") else {
+        return false;
+    };
+    let names: Vec<String> = if cfg!(windows) {
+        vec![format!("{}.exe", name), name.to_string()]
+    } else {
+        vec![name.to_string()]
+    };
+    std::env::split_paths(&path).any(|dir| names.iter().any(|name| dir.join(name).is_file()))
+}
+
+fn check_elevation(checks: &mut Checks) {
+    let tool = if cfg!(target_os = "macos") {
+        "osascript"
+    } else if cfg!(target_os = "linux") {
+        "pkexec"
+    } else {
+        // Windows 不使用提权脚本
+        return;
+    };
+    let vars = [("tool", tool.to_string())];
+    if command_exists(tool) {
+        checks.push("elevation", CheckStatus::Pass, "available", &vars);
+    } else {
+        checks.push("elevation", CheckStatus::Warn, "missing", &vars);
+    }
+}
+
+/// 提权脚本用 Python 清理 product.json 的 checksums
+fn check_python(checks: &mut Checks) {
+    if !cfg!(any(target_os = "macos", target_os = "linux")) {
+        return;
+    }
+    match ["python3", "python"]
+        .into_iter()
+        .find(|name| command_exists(name))
+    {
+        Some(name) => checks.push(
+            "python",
+            CheckStatus::Pass,
+            "available",
+            &[("tool", name.to_string())],
+        ),
+        None => checks.push("python", CheckStatus::Warn, "missing", &[]),
+    }
+}
+
+/// 清理对话缓存需要 sqlite3
+fn check_sqlite(checks: &mut Checks) {
+    if command_exists("sqlite3") {
+        checks.push("sqlite", CheckStatus::Pass, "available", &[]);
+    } else {
+        checks.push("sqlite", CheckStatus::Warn, "missing", &[]);
+    }
+}
+
+fn check_running(checks: &mut Checks, root: Option<&Path>) {
+    // 已知安装目录时只检查该安装的进程
+    let running = match root {
+        Some(root) => Some(!process::find_processes(root).is_empty()),
+        None => is_antigravity_running(),
+    };
+    match running {
+        Some(false) => checks.push("running", CheckStatus::Pass, "notRunning", &[]),
+        Some(true) => checks.push("running", CheckStatus::Warn, "running", &[]),
+        None => checks.push("running", CheckStatus::Warn, "unknown", &[]),
+    }
+}
+
+/// 检测 Antigravity 是否在运行，无法检测时返回 None
+#[cfg(target_os = "windows")]
+fn is_antigravity_running() -> Option<bool> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+    let output = std::process::Command::new("tasklist")
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    Some(
+        String::from_utf8_lossy(&output.stdout)
+            .to_ascii_lowercase()
+            .contains("antigravity.exe"),
+    )
+}
+
+/// 检测 Antigravity 是否在运行，无法检测时返回 None
+#[cfg(not(target_os = "windows"))]
+fn is_antigravity_running() -> Option<bool> {
+    // pgrep 未匹配时退出码为 1，其他非零退出码表示出错
+    let output = std::process::Command::new("pgrep")
+        .args(["-if", "antigravity"])
+        .output()
+        .ok()?;
+    match output.status.code() {
+        Some(0) => Some(
+            // 排除安装器自身 (路径中可能包含 antigravity)
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .any(|pid| pid.trim() != std::process::id().to_string()),
+        ),
+        Some(1) => Some(false),
+        _ => None,
+    }
+}
@@ -0,0 +1,571 @@
+//! 面板功能配置模块
+//!
+//! 面板 config.json 的选项统一定义在 patches/feature-schema.json 中（类型、默认值、范围、可选值），
+//! 安装器按 schema 填充默认值、校验并生成 config.json，新增面板选项无需修改 Rust 代码
+//! 安装逻辑用到的选项 (字号、最大宽度、复制按钮样式、离线渲染) 另有强类型定义，反序列化时校验
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Number, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use super::i18n::CommandError;
+use super::patch::map_embedded_error;
+use crate::embedded;
+
+type FeatureResult<T> = Result<T, CommandError>;
+
+/// 功能配置 schema 文件名 (位于 patches 目录)
+const SCHEMA_FILE: &str = "feature-schema.json";
+
+fn feature_text(_locale: Option<&str>, key: &'static str) -> CommandError {
+    CommandError::key(key)
+}
+
+fn feature_with(_locale: Option<&str>, key: &'static str, vars: &[(&str, String)]) -> CommandError {
+    CommandError::key_with(key, vars)
+}
+
+/// 补丁面板
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Panel {
+    Cascade,
+    Manager,
+}
+
+impl Panel {
+    /// 面板目录名
+    pub(super) fn dir_name(self) -> &'static str {
+        match self {
+            Panel::Cascade => "cascade-panel",
+            Panel::Manager => "manager-panel",
+        }
+    }
+
+    /// 面板在 Antigravity 中的安装目录
+    pub(super) fn install_dir(self, resources_root: &Path) -> PathBuf {
+        self.base_dir()
+            .split('/')
+            .fold(resources_root.to_path_buf(), |dir, part| dir.join(part))
+            .join(self.dir_name())
+    }
+
+    /// 面板目录与入口 HTML 所在目录相对 resources/app 的路径 (/ 分隔，用于只读访问 app.asar)
+    pub(super) fn base_dir(self) -> &'static str {
+        match self {
+            Panel::Cascade => "extensions/antigravity",
+            Panel::Manager => "out/vs/code/electron-browser/workbench",
+        }
+    }
+
+    /// 面板目录中的文件相对 resources/app 的路径
+    pub(super) fn relative_path(self, name: &str) -> String {
+        format!("{}/{}/{}", self.base_dir(), self.dir_name(), name)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct FeatureSchema {
+    cascade: BTreeMap<String, OptionSpec>,
+    manager: BTreeMap<String, OptionSpec>,
+}
+
+impl FeatureSchema {
+    fn panel(&self, panel: Panel) -> &BTreeMap<String, OptionSpec> {
+        match panel {
+            Panel::Cascade => &self.cascade,
+            Panel::Manager => &self.manager,
+        }
+    }
+}
+
+/// 单个选项的定义
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase", deny_unknown_fields)]
+enum OptionSpec {
+    Boolean {
+        default: bool,
+    },
+    /// 数字选项必须声明取值范围
+    Number {
+        default: Number,
+        min: f64,
+        max: f64,
+    },
+    /// 字符串选项，声明 enum 时只接受列出的值
+    String {
+        default: String,
+        #[serde(rename = "enum", default)]
+        allowed: Option<Vec<String>>,
+        #[serde(rename = "maxLength", default)]
+        max_length: Option<usize>,
+    },
+}
+
+/// 选项值不符合 schema 的原因
+enum Violation {
+    WrongType(&'static str),
+    OutOfRange { min: f64, max: f64 },
+    NotAllowed(Vec<String>),
+    TooLong(usize),
+}
+
+impl OptionSpec {
+    fn default_value(&self) -> Value {
+        match self {
+            OptionSpec::Boolean { default } => Value::Bool(*default),
+            OptionSpec::Number { default, .. } => Value::Number(default.clone()),
+            OptionSpec::String { default, .. } => Value::String(default.clone()),
+        }
+    }
+
+    fn check(&self, value: &Value) -> Result<(), Violation> {
+        match self {
+            OptionSpec::Boolean { .. } => {
+                value.as_bool().ok_or(Violation::WrongType("boolean"))?;
+            }
+            OptionSpec::Number { min, max, .. } => {
+                let number = value.as_f64().ok_or(Violation::WrongType("number"))?;
+                if number < *min || number > *max {
+                    return Err(Violation::OutOfRange {
+                        min: *min,
+                        max: *max,
+                    });
+                }
+            }
+            OptionSpec::String {
+                allowed,
+                max_length,
+                ..
+            } => {
+                let text = value.as_str().ok_or(Violation::WrongType("string"))?;
+                if let Some(allowed) = allowed
+                    .as_ref()
+                    .filter(|allowed| !allowed.iter().any(|v| v == text))
+                {
+                    return Err(Violation::NotAllowed(allowed.clone()));
+                }
+                if let Some(max_length) = max_length.filter(|max| text.chars().count() > *max) {
+                    return Err(Violation::TooLong(max_length));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn violation_error(
+    locale: Option<&str>,
+    panel: Panel,
+    key: &str,
+    violation: Violation,
+) -> CommandError {
+    let mut vars = vec![
+        ("panel", panel.dir_name().to_string()),
+        ("key", key.to_string()),
+    ];
+    let message_key = match violation {
+        Violation::WrongType(expected) => {
+            vars.push(("expected", expected.to_string()));
+            "patchBackend.errors.invalidOptionType"
+        }
+        Violation::OutOfRange { min, max } => {
+            vars.push(("min", min.to_string()));
+            vars.push(("max", max.to_string()));
+            "patchBackend.errors.optionOutOfRange"
+        }
+        Violation::NotAllowed(allowed) => {
+            vars.push(("allowed", allowed.join(", ")));
+            "patchBackend.errors.optionNotAllowed"
+        }
+        Violation::TooLong(max) => {
+            vars.push(("max", max.to_string()));
+            "patchBackend.errors.optionTooLong"
+        }
+    };
+    feature_with(locale, message_key, &vars)
+}
+
+/// 字号 (px)，与 schema 中 fontSize 的范围一致 (由测试检查)
+pub(super) const FONT_SIZE_RANGE: (f64, f64) = (10.0, 40.0);
+/// Manager 内容最大宽度占窗口的百分比，与 schema 中 maxWidthRatio 的范围一致
+pub(super) const MAX_WIDTH_RATIO_RANGE: (f64, f64) = (30.0, 100.0);
+
+/// 检查数字是否在范围内，保留原始表示 (整数仍写为整数)
+fn bounded(number: Number, (min, max): (f64, f64)) -> Result<Number, String> {
+    match number.as_f64() {
+        Some(value) if (min..=max).contains(&value) => Ok(number),
+        _ => Err(format!("{} is out of range {}-{}", number, min, max)),
+    }
+}
+
+/// 面板字号，反序列化时校验范围
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "Number", into = "Number")]
+pub(super) struct FontSize(Number);
+
+impl TryFrom<Number> for FontSize {
+    type Error = String;
+
+    fn try_from(number: Number) -> Result<Self, Self::Error> {
+        bounded(number, FONT_SIZE_RANGE).map(Self)
+    }
+}
+
+impl From<FontSize> for Number {
+    fn from(size: FontSize) -> Self {
+        size.0
+    }
+}
+
+/// Manager 内容最大宽度比例，反序列化时校验范围
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "Number", into = "Number")]
+pub(super) struct MaxWidthRatio(Number);
+
+impl TryFrom<Number> for MaxWidthRatio {
+    type Error = String;
+
+    fn try_from(number: Number) -> Result<Self, Self::Error> {
+        bounded(number, MAX_WIDTH_RATIO_RANGE).map(Self)
+    }
+}
+
+impl From<MaxWidthRatio> for Number {
+    fn from(ratio: MaxWidthRatio) -> Self {
+        ratio.0
+    }
+}
+
+/// 复制按钮位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(super) enum CopyButtonPlacement {
+    /// 悬浮在内容右下角
+    Float,
+    /// 放在反馈按钮旁
+    Feedback,
+}
+
+/// 复制按钮样式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(super) enum CopyButtonStyle {
+    Arrow,
+    Icon,
+    Chinese,
+    /// 使用 copyButtonCustomText
+    Custom,
+}
+
+/// 经 schema 补全、校验后的面板选项
+/// 安装逻辑用到的选项为强类型，其余选项原样保留
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct PanelOptions {
+    pub(super) offline_rendering: bool,
+    pub(super) font_size: FontSize,
+    /// 仅 Manager 面板
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(super) max_width_ratio: Option<MaxWidthRatio>,
+    pub(super) copy_button_show_bottom: CopyButtonPlacement,
+    pub(super) copy_button_style: CopyButtonStyle,
+    #[serde(flatten)]
+    pub(super) other: Map<String, Value>,
+}
+
+/// 侧边栏功能配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeatureConfig {
+    /// 是否启用侧边栏补丁 (禁用时还原所有侧边栏相关文件)
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// config.json 选项，由 schema 定义
+    #[serde(flatten)]
+    pub options: Map<String, Value>,
+}
+
+impl Default for FeatureConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            options: Map::new(),
+        }
+    }
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// Manager 窗口功能配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ManagerFeatureConfig {
+    /// 是否启用 Manager 补丁 (禁用时还原所有 Manager 相关文件)
+    #[serde(default)]
+    pub enabled: bool,
+    /// config.json 选项，由 schema 定义
+    #[serde(flatten)]
+    pub options: Map<String, Value>,
+}
+
+/// 面板功能配置的公共访问
+pub(super) trait PanelFeatures {
+    const PANEL: Panel;
+
+    fn options(&self) -> &Map<String, Value>;
+
+    /// 按 schema 补全、校验后的强类型选项
+    fn panel_options(&self, locale: Option<&str>) -> FeatureResult<PanelOptions> {
+        let resolved = resolve_options(Self::PANEL, self.options(), locale)?;
+        // schema 已校验，此处失败说明 schema 与强类型定义不一致
+        serde_json::from_value(Value::Object(resolved)).map_err(|e| {
+            feature_with(
+                locale,
+                "patchBackend.errors.invalidFeatureSchema",
+                &[("detail", format!("{}: {}", Self::PANEL.dir_name(), e))],
+            )
+        })
+    }
+
+    /// 按 schema 生成 config.json 的选项
+    fn config_options(&self, locale: Option<&str>) -> FeatureResult<Map<String, Value>> {
+        match serde_json::to_value(self.panel_options(locale)?) {
+            Ok(Value::Object(options)) => Ok(options),
+            _ => Err(feature_text(
+                locale,
+                "patchBackend.errors.invalidFeatureSchema",
+            )),
+        }
+    }
+
+    /// 离线渲染 (使用内置的 Mermaid/KaTeX/MathJax，不访问 CDN)
+    /// 构建时未打包第三方库则始终关闭
+    fn offline_rendering(&self, locale: Option<&str>) -> FeatureResult<bool> {
+        Ok(self.panel_options(locale)?.offline_rendering
+            && !embedded::get_vendor_files().is_empty())
+    }
+}
+
+impl PanelFeatures for FeatureConfig {
+    const PANEL: Panel = Panel::Cascade;
+
+    fn options(&self) -> &Map<String, Value> {
+        &self.options
+    }
+}
+
+impl PanelFeatures for ManagerFeatureConfig {
+    const PANEL: Panel = Panel::Manager;
+
+    fn options(&self) -> &Map<String, Value> {
+        &self.options
+    }
+}
+
+/// 读取并校验 schema，成功后在进程内缓存 (开发模式下修改 schema 需重启安装器)
+/// 读取失败不缓存，下次调用重试
+fn load_schema(locale: Option<&str>) -> FeatureResult<&'static FeatureSchema> {
+    static SCHEMA: OnceLock<FeatureSchema> = OnceLock::new();
+    if let Some(schema) = SCHEMA.get() {
+        return Ok(schema);
+    }
+
+    let content = embedded::get_file_runtime(SCHEMA_FILE)
+        .map_err(|e| map_embedded_error(locale, e))?
+        .ok_or_else(|| feature_text(locale, "patchBackend.errors.featureSchemaMissing"))?;
+
+    let invalid = |detail: String| {
+        feature_with(
+            locale,
+            "patchBackend.errors.invalidFeatureSchema",
+            &[("detail", detail)],
+        )
+    };
+    let schema: FeatureSchema =
+        serde_json::from_slice(&content).map_err(|e| invalid(e.to_string()))?;
+
+    // 默认值本身也必须满足约束
+    for panel in [Panel::Cascade, Panel::Manager] {
+        for (key, spec) in schema.panel(panel) {
+            if spec.check(&spec.default_value()).is_err() {
+                return Err(invalid(format!(
+                    "{}: invalid default for {}",
+                    panel.dir_name(),
+                    key
+                )));
+            }
+        }
+    }
+
+    Ok(SCHEMA.get_or_init(|| schema))
+}
+
+/// 按 schema 生成 config.json 的选项
+/// 未提供的选项使用默认值，schema 以外的键不写入，类型不符或超出约束时报错
+fn resolve_options(
+    panel: Panel,
+    options: &Map<String, Value>,
+    locale: Option<&str>,
+) -> FeatureResult<Map<String, Value>> {
+    let schema = load_schema(locale)?;
+    let mut resolved = Map::new();
+
+    for (key, spec) in schema.panel(panel) {
+        let value = match options.get(key) {
+            Some(value) if !value.is_null() => {
+                spec.check(value)
+                    .map_err(|violation| violation_error(locale, panel, key, violation))?;
+                value.clone()
+            }
+            _ => spec.default_value(),
+        };
+        resolved.insert(key.clone(), value);
+    }
+
+    Ok(resolved)
+}
+
+/// 读取已安装的 config.json 选项
+/// 类型不符或超出约束的值回退到默认值，保证界面总能拿到完整的配置
+pub(super) fn installed_options(
+    panel: Panel,
+    installed: &Map<String, Value>,
+    locale: Option<&str>,
+) -> FeatureResult<Map<String, Value>> {
+    let schema = load_schema(locale)?;
+
+    Ok(schema
+        .panel(panel)
+        .iter()
+        .map(|(key, spec)| {
+            let value = installed
+                .get(key)
+                .filter(|value| spec.check(value).is_ok())
+                .cloned()
+                .unwrap_or_else(|| spec.default_value());
+            (key.clone(), value)
+        })
+        .collect())
+}
+
+/// 安装前校验两个面板的选项，避免写入部分文件后才失败
+pub(super) fn validate_features(
+    features: &FeatureConfig,
+    manager_features: &ManagerFeatureConfig,
+    locale: Option<&str>,
+) -> FeatureResult<()> {
+    features.config_options(locale)?;
+    manager_features.config_options(locale)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn cascade_options() -> Value {
+        json!({
+            "offlineRendering": true,
+            "fontSize": 20,
+            "copyButtonShowBottom": "feedback",
+            "copyButtonStyle": "custom",
+            "copyButtonCustomText": "复制",
+            "mermaid": true
+        })
+    }
+
+    #[test]
+    fn panel_options_round_trip_keeps_other_options() {
+        let options: PanelOptions = serde_json::from_value(cascade_options()).unwrap();
+        assert!(options.offline_rendering);
+        assert_eq!(
+            options.copy_button_show_bottom,
+            CopyButtonPlacement::Feedback
+        );
+        assert_eq!(options.copy_button_style, CopyButtonStyle::Custom);
+        assert_eq!(options.max_width_ratio, None);
+
+        // 整数仍写为整数，未定义强类型的选项原样保留
+        assert_eq!(serde_json::to_value(options).unwrap(), cascade_options());
+    }
+
+    #[test]
+    fn panel_options_reject_invalid_values() {
+        for (key, value) in [
+            ("fontSize", json!(41)),
+            ("fontSize", json!("20")),
+            ("maxWidthRatio", json!(29.5)),
+            ("copyButtonShowBottom", json!("inline")),
+            ("copyButtonStyle", json!("Arrow")),
+        ] {
+            let mut options = cascade_options();
+            options[key] = value;
+            assert!(
+                serde_json::from_value::<PanelOptions>(options).is_err(),
+                "{} accepted",
+                key
+            );
+        }
+
+        let mut options = cascade_options();
+        options["maxWidthRatio"] = json!(75);
+        let options: PanelOptions = serde_json::from_value(options).unwrap();
+        assert!(options.max_width_ratio.is_some());
+    }
+
+    fn assert_range(spec: &OptionSpec, range: (f64, f64)) {
+        match spec {
+            OptionSpec::Number { min, max, .. } => assert_eq!((*min, *max), range),
+            _ => panic!("expected a number option"),
+        }
+    }
+
+    /// 强类型枚举的取值与 schema 的 enum 完全一致
+    fn assert_variants<T>(spec: &OptionSpec, variants: &[T])
+    where
+        T: Serialize + serde::de::DeserializeOwned + PartialEq + std::fmt::Debug,
+    {
+        let OptionSpec::String {
+            allowed: Some(allowed),
+            ..
+        } = spec
+        else {
+            panic!("expected a string option with enum");
+        };
+        let names: Vec<Value> = variants
+            .iter()
+            .map(|variant| serde_json::to_value(variant).unwrap())
+            .collect();
+        assert_eq!(names, allowed.iter().map(|v| json!(v)).collect::<Vec<_>>());
+        for (name, variant) in names.into_iter().zip(variants) {
+            assert_eq!(&serde_json::from_value::<T>(name).unwrap(), variant);
+        }
+    }
+
+    #[test]
+    fn typed_options_match_schema() {
+        let schema = load_schema(None).unwrap();
+        for panel in [Panel::Cascade, Panel::Manager] {
+            let options = schema.panel(panel);
+            assert_range(&options["fontSize"], FONT_SIZE_RANGE);
+            assert_variants(
+                &options["copyButtonShowBottom"],
+                &[CopyButtonPlacement::Float, CopyButtonPlacement::Feedback],
+            );
+            assert_variants(
+                &options["copyButtonStyle"],
+                &[
+                    CopyButtonStyle::Arrow,
+                    CopyButtonStyle::Icon,
+                    CopyButtonStyle::Chinese,
+                    CopyButtonStyle::Custom,
+                ],
+            );
+        }
+        assert_range(&schema.manager["maxWidthRatio"], MAX_WIDTH_RATIO_RANGE);
+        assert!(!schema.cascade.contains_key("maxWidthRatio"));
+    }
+}
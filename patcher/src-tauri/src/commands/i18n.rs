@@ -95,7 +95,6 @@ pub fn text(locale: Option<&str>, key: &str) -> String {
         .unwrap_or_else(|| key.to_string())
 }
 
-#[allow(dead_code)]
 pub fn text_with(locale: Option<&str>, key: &str, vars: &[(&str, String)]) -> String {
     let mut message = text(locale, key);
     for (name, value) in vars {
@@ -0,0 +1,78 @@
+//! 配置热重载模块
+//!
+//! update_config 写入 config.json 后，若 Antigravity 开启了远程调试端口，
+//! 通过 CDP 调用面板脚本的热重载函数，无需重启即可应用新配置
+
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use super::cdp::{self, CdpSession};
+use super::live_verify::{self, CDP_HOST, PROBES};
+use super::oplog;
+use super::process;
+
+/// 在运行中的面板里重新读取配置，返回已应用新配置的面板
+/// 调试端口不可用、不属于该安装或面板脚本不支持热重载时跳过，不影响配置写入的结果
+pub(super) fn reload_panels(antigravity_root: &Path) -> Vec<&'static str> {
+    let port = live_verify::debug_port();
+    let Ok(targets) = cdp::list_targets(CDP_HOST, port) else {
+        return Vec::new();
+    };
+    if !owns_endpoint(antigravity_root, port) {
+        oplog::output(&format!(
+            "live reload skipped: {}:{} does not belong to {}",
+            CDP_HOST,
+            port,
+            antigravity_root.display()
+        ));
+        return Vec::new();
+    }
+
+    let mut reloaded = Vec::new();
+    for probe in PROBES {
+        let expression = format!(
+            "typeof window.{global} === 'function' ? window.{global}().then(() => true) : false",
+            global = probe.reload_global
+        );
+        let mut applied = false;
+        for target in live_verify::panel_targets(&targets, probe) {
+            let result = target
+                .ws_url
+                .as_deref()
+                .ok_or_else(String::new)
+                .and_then(CdpSession::connect)
+                .and_then(|mut session| session.evaluate(&expression));
+            match result {
+                Ok(value) if value.as_bool() == Some(true) => applied = true,
+                Ok(_) => {}
+                Err(detail) => oplog::output(&format!(
+                    "live reload {} failed: {}",
+                    probe.panel.dir_name(),
+                    detail
+                )),
+            }
+        }
+        if applied {
+            reloaded.push(probe.panel.dir_name());
+        }
+    }
+
+    if !reloaded.is_empty() {
+        oplog::output(&format!("live reloaded: {}", reloaded.join(", ")));
+    }
+    reloaded
+}
+
+/// 调试端口是否由该安装的进程提供
+/// 同一端口可能是另一份安装 (或其他 Chromium 程序)，不在其中执行代码
+fn owns_endpoint(antigravity_root: &Path, port: u16) -> bool {
+    let pids: BTreeSet<u32> = process::find_processes(antigravity_root)
+        .iter()
+        .map(|process| process.pid)
+        .collect();
+    if pids.is_empty() {
+        return false;
+    }
+    cdp::browser_process_ids(CDP_HOST, port)
+        .is_ok_and(|browser_pids| browser_pids.iter().any(|pid| pids.contains(pid)))
+}
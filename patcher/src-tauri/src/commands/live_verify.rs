@@ -0,0 +1,545 @@
+//! 补丁运行时验证模块
+//!
+//! 通过 Chrome DevTools Protocol 连接带 --remote-debugging-port 的 Antigravity
+//! (未运行时可自动启动)，确认面板脚本已加载、配置已读取且 DOM 钩子存在
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use super::asar::Resources;
+use super::cdp::{self, CdpSession, CdpTarget};
+use super::config;
+use super::doctor::{CheckStatus, Checks, DoctorCheck};
+use super::features::Panel;
+use super::patch::resolve_antigravity_root;
+use super::paths;
+use super::process;
+
+pub(super) const CDP_HOST: &str = "127.0.0.1";
+
+/// 默认调试端口 (与 tests/scripts 保持一致)
+const DEFAULT_DEBUG_PORT: u16 = 9222;
+
+/// 启动后等待调试端口可用的时间
+const LAUNCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 等待面板页面出现的时间
+const TARGET_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// 面板脚本异步读取配置，等待全局配置变量的时间 (毫秒)
+const CONFIG_WAIT_MS: u64 = 3000;
+
+/// 运行时验证报告
+#[derive(Debug, Serialize)]
+pub struct LiveVerifyReport {
+    /// 最严重的检查结果
+    pub status: CheckStatus,
+    /// 调试端口地址
+    pub endpoint: String,
+    /// 浏览器版本 (/json/version 的 Browser 字段)
+    pub browser: Option<String>,
+    /// 是否由本次验证启动了 Antigravity
+    pub launched: bool,
+    pub checks: Vec<DoctorCheck>,
+}
+
+/// DOM 钩子: (名称, 检查表达式, 需要启用的配置项)
+type Hook = (&'static str, &'static str, Option<&'static str>);
+
+const CASCADE_HOOKS: &[Hook] = &[
+    (
+        "stylesheet",
+        "!!document.querySelector('link[href*=\"cascade-panel/cascade-panel.css\"]')",
+        None,
+    ),
+    ("reactRoot", "!!document.getElementById('react-app')", None),
+    (
+        "fontSize",
+        "document.documentElement.style.getPropertyValue('--cascade-panel-font-size') !== ''",
+        Some("fontSizeEnabled"),
+    ),
+    (
+        "tableFix",
+        "!!document.querySelector('link[href*=\"table-fix.css\"]')",
+        Some("tableColor"),
+    ),
+];
+
+const MANAGER_HOOKS: &[Hook] = &[
+    (
+        "stylesheet",
+        "!!document.querySelector('link[href*=\"manager-panel.css\"]')",
+        None,
+    ),
+    (
+        "fontSize",
+        "document.documentElement.style.getPropertyValue('--manager-panel-font-size') !== ''",
+        Some("fontSizeEnabled"),
+    ),
+    (
+        "maxWidth",
+        "document.documentElement.hasAttribute('data-manager-panel-max-width')",
+        Some("maxWidthEnabled"),
+    ),
+];
+
+/// 面板在运行时的识别信息
+pub(super) struct PanelProbe {
+    pub(super) panel: Panel,
+    /// 页面 URL 中包含的入口文件名
+    page: &'static str,
+    /// 面板脚本写入的全局配置变量
+    config_global: &'static str,
+    /// 面板脚本提供的配置热重载函数
+    pub(super) reload_global: &'static str,
+    hooks: &'static [Hook],
+}
+
+pub(super) const PROBES: &[PanelProbe] = &[
+    PanelProbe {
+        panel: Panel::Cascade,
+        page: "cascade-panel.html",
+        config_global: "__CASCADE_CONFIG__",
+        reload_global: "__CASCADE_RELOAD_CONFIG__",
+        hooks: CASCADE_HOOKS,
+    },
+    PanelProbe {
+        panel: Panel::Manager,
+        page: "workbench-jetski-agent.html",
+        config_global: "__MANAGER_CONFIG__",
+        reload_global: "__MANAGER_RELOAD_CONFIG__",
+        hooks: MANAGER_HOOKS,
+    },
+];
+
+/// 应用配置中的调试端口，未设置时使用默认端口
+pub(super) fn debug_port() -> u16 {
+    config::read_config()
+        .debug_port
+        .unwrap_or(DEFAULT_DEBUG_PORT)
+}
+
+/// 验证补丁在运行中的 Antigravity 里是否生效
+/// port 默认使用应用配置中的调试端口；launch 为 true 且调试端口不可用时以调试模式启动 Antigravity
+#[tauri::command]
+pub fn verify_live_patch(
+    path: String,
+    port: Option<u16>,
+    launch: Option<bool>,
+    locale: Option<String>,
+) -> Result<LiveVerifyReport, String> {
+    let locale_ref = locale.as_deref();
+    let root =
+        resolve_antigravity_root(&path, locale_ref).map_err(|err| err.to_message(locale_ref))?;
+    let port = port.unwrap_or_else(debug_port);
+    let endpoint = format!("{}:{}", CDP_HOST, port);
+    let mut checks = Checks::new("liveVerifyBackend", locale_ref);
+    let vars = [("endpoint", endpoint.clone())];
+
+    let mut launched = false;
+    let mut version = cdp::browser_version(CDP_HOST, port).ok();
+    if version.is_none() && launch.unwrap_or(false) {
+        match launch_with_debug_port(&root, port) {
+            Ok(found) => {
+                launched = true;
+                version = Some(found);
+            }
+            Err(outcome) => checks.push("endpoint", CheckStatus::Fail, outcome, &vars),
+        }
+    }
+
+    let Some(version) = version else {
+        if checks.checks.is_empty() {
+            checks.push("endpoint", CheckStatus::Fail, "unreachable", &vars);
+        }
+        return Ok(report(checks, endpoint, None, launched));
+    };
+    checks.push("endpoint", CheckStatus::Pass, "connected", &vars);
+
+    let resources = Resources::open(&root);
+    let probes: Vec<&PanelProbe> = PROBES
+        .iter()
+        .filter(|probe| resources.exists(&probe.panel.relative_path("config.json")))
+        .collect();
+    if probes.is_empty() {
+        checks.push("target", CheckStatus::Fail, "notInstalled", &[]);
+    }
+
+    let targets = if launched {
+        wait_for_targets(port, &probes)
+    } else {
+        cdp::list_targets(CDP_HOST, port).unwrap_or_default()
+    };
+    for probe in probes {
+        verify_panel(&mut checks, probe, &resources, &targets);
+    }
+
+    let browser = version
+        .get("Browser")
+        .and_then(Value::as_str)
+        .map(str::to_string);
+    Ok(report(checks, endpoint, browser, launched))
+}
+
+fn report(
+    checks: Checks,
+    endpoint: String,
+    browser: Option<String>,
+    launched: bool,
+) -> LiveVerifyReport {
+    LiveVerifyReport {
+        status: checks.status(),
+        endpoint,
+        browser,
+        launched,
+        checks: checks.checks,
+    }
+}
+
+/// 以调试模式启动 Antigravity 并等待调试端口可用，失败时返回文案 outcome
+fn launch_with_debug_port(root: &Path, port: u16) -> Result<Value, &'static str> {
+    // 已运行的实例会接管新进程，调试参数不会生效
+    if !process::find_processes(root).is_empty() {
+        return Err("runningWithoutPort");
+    }
+    let exe = paths::antigravity_executable(root).ok_or("executableMissing")?;
+    Command::new(exe)
+        .arg(format!("--remote-debugging-port={}", port))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|_| "launchFailed")?;
+
+    let started = Instant::now();
+    while started.elapsed() < LAUNCH_TIMEOUT {
+        if let Ok(version) = cdp::browser_version(CDP_HOST, port) {
+            return Ok(version);
+        }
+        std::thread::sleep(Duration::from_millis(500));
+    }
+    Err("launchTimeout")
+}
+
+/// 刚启动时面板页面尚未创建，等待任一已安装面板出现
+fn wait_for_targets(port: u16, probes: &[&PanelProbe]) -> Vec<CdpTarget> {
+    let started = Instant::now();
+    loop {
+        let targets = cdp::list_targets(CDP_HOST, port).unwrap_or_default();
+        let found = probes
+            .iter()
+            .any(|probe| find_target(&targets, probe).is_some());
+        if found || started.elapsed() > TARGET_TIMEOUT {
+            return targets;
+        }
+        std::thread::sleep(Duration::from_millis(500));
+    }
+}
+
+fn find_target<'a>(targets: &'a [CdpTarget], probe: &PanelProbe) -> Option<&'a CdpTarget> {
+    panel_targets(targets, probe).next()
+}
+
+/// 面板对应的页面，多个窗口时每个窗口各有一个
+pub(super) fn panel_targets<'a>(
+    targets: &'a [CdpTarget],
+    probe: &PanelProbe,
+) -> impl Iterator<Item = &'a CdpTarget> {
+    let page = probe.page;
+    targets
+        .iter()
+        .filter(|target| target.ws_url.is_some())
+        .filter(move |target| target.url.contains(page))
+}
+
+fn verify_panel(
+    checks: &mut Checks,
+    probe: &PanelProbe,
+    resources: &Resources,
+    targets: &[CdpTarget],
+) {
+    let panel = probe.panel.dir_name();
+    let vars = [("panel", panel.to_string())];
+
+    let Some(target) = find_target(targets, probe) else {
+        // 侧边栏或 Manager 窗口未打开时没有对应页面
+        checks.push("target", CheckStatus::Warn, "notOpen", &vars);
+        return;
+    };
+    let expected = read_panel_config(resources, probe.panel);
+    let result = target
+        .ws_url
+        .as_deref()
+        .ok_or_else(String::new)
+        .and_then(CdpSession::connect)
+        .and_then(|mut session| session.evaluate(&probe_expression(probe, &expected)));
+    let result = match result {
+        Ok(result) => result,
+        Err(detail) => {
+            checks.push(
+                "target",
+                CheckStatus::Fail,
+                "evaluateFailed",
+                &[("panel", panel.to_string()), ("detail", detail)],
+            );
+            return;
+        }
+    };
+    checks.push(
+        "target",
+        CheckStatus::Pass,
+        "found",
+        &[("panel", panel.to_string()), ("url", target.url.clone())],
+    );
+
+    // 入口页面仍是未打补丁的版本: 补丁安装前已加载
+    if result.get("scriptTag").and_then(Value::as_bool) != Some(true) {
+        checks.push("script", CheckStatus::Fail, "notInjected", &vars);
+        return;
+    }
+    let Some(live) = result.get("config").and_then(Value::as_object) else {
+        checks.push("script", CheckStatus::Fail, "notLoaded", &vars);
+        return;
+    };
+    checks.push("script", CheckStatus::Pass, "loaded", &vars);
+
+    let stale: Vec<&str> = expected
+        .iter()
+        .filter(|(key, value)| live.get(key.as_str()) != Some(value))
+        .map(|(key, _)| key.as_str())
+        .collect();
+    if stale.is_empty() {
+        checks.push("config", CheckStatus::Pass, "matches", &vars);
+    } else {
+        checks.push(
+            "config",
+            CheckStatus::Warn,
+            "stale",
+            &[("panel", panel.to_string()), ("keys", stale.join(", "))],
+        );
+    }
+
+    let hooks = result.get("hooks").and_then(Value::as_object);
+    for (name, _, _) in enabled_hooks(probe, &expected) {
+        let present = hooks
+            .and_then(|hooks| hooks.get(*name))
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+        let hook_vars = [("panel", panel.to_string()), ("hook", name.to_string())];
+        if present {
+            checks.push("hook", CheckStatus::Pass, "present", &hook_vars);
+        } else {
+            checks.push("hook", CheckStatus::Fail, "missing", &hook_vars);
+        }
+    }
+}
+
+/// 读取已安装的面板配置，失败时返回空配置
+fn read_panel_config(resources: &Resources, panel: Panel) -> Map<String, Value> {
+    resources
+        .read(&panel.relative_path("config.json"))
+        .ok()
+        .and_then(|content| serde_json::from_slice(&content).ok())
+        .unwrap_or_default()
+}
+
+/// 需要检查的钩子: 依赖的配置项未启用时跳过
+fn enabled_hooks<'a>(
+    probe: &'a PanelProbe,
+    config: &'a Map<String, Value>,
+) -> impl Iterator<Item = &'a Hook> {
+    probe.hooks.iter().filter(|(_, _, option)| {
+        option.is_none_or(|option| config.get(option).and_then(Value::as_bool) == Some(true))
+    })
+}
+
+/// 在页面中执行的检查脚本，等待面板脚本写入全局配置后返回结果
+fn probe_expression(probe: &PanelProbe, config: &Map<String, Value>) -> String {
+    let script = format!("{}/{}.js", probe.panel.dir_name(), probe.panel.dir_name());
+    let hooks: Vec<String> = enabled_hooks(probe, config)
+        .map(|(name, expression, _)| format!("{:?}: {}", name, expression))
+        .collect();
+    format!(
+        r#"new Promise((resolve) => {{
+    const started = Date.now();
+    const probe = () => ({{
+        scriptTag: !!document.querySelector('script[src*="{script}"]'),
+        config: window.{global} ?? null,
+        hooks: {{ {hooks} }},
+    }});
+    const tick = () => {{
+        if (window.{global} || Date.now() - started > {wait}) {{
+            resolve(probe());
+        }} else {{
+            setTimeout(tick, 100);
+        }}
+    }};
+    tick();
+}})"#,
+        script = script,
+        global = probe.config_global,
+        hooks = hooks.join(", "),
+        wait = CONFIG_WAIT_MS,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::test_support::TempDir;
+    use serde_json::json;
+    use std::fs;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::thread;
+    use tungstenite::Message;
+
+    /// 模拟调试端口: 第一个连接返回 /json/list，第二个连接作为页面 WebSocket
+    /// 以 probe 作为 Runtime.evaluate 的结果值回复
+    fn serve_cdp(probe: Value) -> u16 {
+        let listener = TcpListener::bind((CDP_HOST, 0)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 0 && line != "\r\n" {
+                assert!(!line.starts_with("GET") || line.starts_with("GET /json/list "));
+                line.clear();
+            }
+            let body = json!([
+                { "url": "devtools://devtools/bundled/inspector.html" },
+                {
+                    "url": "vscode-file://vscode-app/extensions/antigravity/cascade-panel.html",
+                    "webSocketDebuggerUrl": format!("ws://{}:{}/devtools/page/1", CDP_HOST, port),
+                },
+            ])
+            .to_string();
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+            .unwrap();
+            // 关闭连接 (包括读取用的副本)，客户端读到结束
+            drop((reader, stream));
+
+            let (stream, _) = listener.accept().unwrap();
+            let mut socket = tungstenite::accept(stream).unwrap();
+            while let Ok(message) = socket.read() {
+                let Message::Text(text) = message else {
+                    continue;
+                };
+                let request: Value = serde_json::from_str(&text).unwrap();
+                assert_eq!(request["method"], "Runtime.evaluate");
+                let response = json!({
+                    "id": request["id"],
+                    "result": { "result": { "type": "object", "value": probe } },
+                });
+                socket.send(Message::Text(response.to_string())).unwrap();
+            }
+        });
+        port
+    }
+
+    /// 写入已安装的侧边栏 config.json
+    fn install_cascade(resources_root: &Path, config: &Value) {
+        let panel_dir = Panel::Cascade.install_dir(resources_root);
+        fs::create_dir_all(&panel_dir).unwrap();
+        fs::write(panel_dir.join("config.json"), config.to_string()).unwrap();
+    }
+
+    fn verify(resources_root: &Path, probe: Value) -> Vec<(&'static str, CheckStatus)> {
+        let port = serve_cdp(probe);
+        let targets = cdp::list_targets(CDP_HOST, port).unwrap();
+        let mut checks = Checks::new("liveVerifyBackend", None);
+        let resources = Resources::Dir(resources_root.to_path_buf());
+        verify_panel(&mut checks, &PROBES[0], &resources, &targets);
+        checks
+            .checks
+            .iter()
+            .map(|check| (check.id, check.status))
+            .collect()
+    }
+
+    #[test]
+    fn loaded_panel_with_current_config_passes() {
+        let dir = TempDir::new("pass");
+        let config = json!({ "fontSizeEnabled": true, "fontSize": 16, "tableColor": false });
+        install_cascade(&dir.0, &config);
+
+        let checks = verify(
+            &dir.0,
+            json!({
+                "scriptTag": true,
+                "config": config,
+                "hooks": { "stylesheet": true, "reactRoot": true, "fontSize": true },
+            }),
+        );
+        assert_eq!(
+            checks,
+            [
+                ("target", CheckStatus::Pass),
+                ("script", CheckStatus::Pass),
+                ("config", CheckStatus::Pass),
+                ("hook", CheckStatus::Pass),
+                ("hook", CheckStatus::Pass),
+                ("hook", CheckStatus::Pass),
+            ]
+        );
+    }
+
+    #[test]
+    fn stale_config_and_missing_hook_are_reported() {
+        let dir = TempDir::new("stale");
+        install_cascade(&dir.0, &json!({ "fontSizeEnabled": true, "fontSize": 18 }));
+
+        let checks = verify(
+            &dir.0,
+            json!({
+                "scriptTag": true,
+                "config": { "fontSizeEnabled": true, "fontSize": 16 },
+                "hooks": { "stylesheet": true, "reactRoot": true, "fontSize": false },
+            }),
+        );
+        assert_eq!(
+            checks,
+            [
+                ("target", CheckStatus::Pass),
+                ("script", CheckStatus::Pass),
+                ("config", CheckStatus::Warn),
+                ("hook", CheckStatus::Pass),
+                ("hook", CheckStatus::Pass),
+                ("hook", CheckStatus::Fail),
+            ]
+        );
+    }
+
+    #[test]
+    fn page_loaded_before_install_is_not_injected() {
+        let dir = TempDir::new("not-injected");
+        install_cascade(&dir.0, &json!({}));
+
+        let checks = verify(
+            &dir.0,
+            json!({ "scriptTag": false, "config": null, "hooks": {} }),
+        );
+        assert_eq!(
+            checks,
+            [("target", CheckStatus::Pass), ("script", CheckStatus::Fail)]
+        );
+    }
+
+    #[test]
+    fn closed_panel_is_a_warning() {
+        let dir = TempDir::new("closed");
+        let mut checks = Checks::new("liveVerifyBackend", None);
+        verify_panel(&mut checks, &PROBES[0], &Resources::Dir(dir.0.clone()), &[]);
+        assert_eq!(checks.checks.len(), 1);
+        assert_eq!(checks.checks[0].status, CheckStatus::Warn);
+    }
+}
@@ -0,0 +1,193 @@
+//! 配置文件版本迁移
+//!
+//! 应用配置与已安装面板的 config.json 都带有 schemaVersion，
+//! 加载时在内存中按迁移链逐级升级；版本变化的旧文件在写回前先备份到应用数据目录
+
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::config;
+use super::features::Panel;
+
+/// 版本字段名
+pub(super) const VERSION_KEY: &str = "schemaVersion";
+
+/// 单步迁移，将配置从版本 i 升级到 i + 1，无法升级时返回原因
+type Migration = fn(&mut Map<String, Value>) -> Result<(), String>;
+
+/// 应用配置迁移链，第 i 项将版本 i 升级到 i + 1
+const APP_CONFIG_MIGRATIONS: &[Migration] = &[unversioned_to_v1];
+
+/// 面板 config.json 迁移链
+const PANEL_CONFIG_MIGRATIONS: &[Migration] = &[unversioned_to_v1];
+
+/// 当前应用配置版本
+pub(super) const APP_CONFIG_VERSION: u64 = APP_CONFIG_MIGRATIONS.len() as u64;
+
+/// 当前面板 config.json 版本
+pub(super) const PANEL_CONFIG_VERSION: u64 = PANEL_CONFIG_MIGRATIONS.len() as u64;
+
+/// 引入 schemaVersion 之前的文件 (版本 0)，字段与版本 1 相同
+fn unversioned_to_v1(_config: &mut Map<String, Value>) -> Result<(), String> {
+    Ok(())
+}
+
+/// 读取文件的版本，没有版本字段视为 0
+pub(super) fn file_version(config: &Value) -> u64 {
+    config.get(VERSION_KEY).and_then(Value::as_u64).unwrap_or(0)
+}
+
+/// 按迁移链升级，返回原版本
+/// 迁移在副本上进行，中途失败时原配置保持不变
+fn migrate(config: &mut Value, migrations: &[Migration]) -> Result<u64, String> {
+    let from = file_version(config);
+    let Some(map) = config.as_object() else {
+        return Ok(from);
+    };
+
+    // 新版本安装器写入的文件原样读取，未知字段由 serde 忽略
+    if from >= migrations.len() as u64 {
+        return Ok(from);
+    }
+    let mut migrated = map.clone();
+    for (version, migration) in migrations.iter().enumerate().skip(from as usize) {
+        migration(&mut migrated).map_err(|e| format!("v{} -> v{}: {}", version, version + 1, e))?;
+    }
+    migrated.insert(
+        VERSION_KEY.to_string(),
+        Value::from(migrations.len() as u64),
+    );
+    *config = Value::Object(migrated);
+    Ok(from)
+}
+
+/// 升级应用配置，返回原版本
+pub(super) fn migrate_app_config(config: &mut Value) -> Result<u64, String> {
+    migrate(config, APP_CONFIG_MIGRATIONS)
+}
+
+/// 升级面板 config.json，返回原版本
+pub(super) fn migrate_panel_config(config: &mut Value) -> Result<u64, String> {
+    migrate(config, PANEL_CONFIG_MIGRATIONS)
+}
+
+/// 写入面板 config.json 的版本
+pub(super) fn stamp_panel_config(config: &mut Value) {
+    if let Some(map) = config.as_object_mut() {
+        map.insert(VERSION_KEY.to_string(), Value::from(PANEL_CONFIG_VERSION));
+    }
+}
+
+/// 迁移前的配置备份目录
+fn backup_dir() -> PathBuf {
+    config::get_app_data_dir().join("config-backups")
+}
+
+/// 备份版本与当前不同的配置文件 (同一版本只保留最早的备份)
+pub(super) fn backup_file(path: &Path, name: &str, version: u64) -> std::io::Result<()> {
+    let backup_path = backup_dir().join(format!("{}.v{}.json", name, version));
+    if backup_path.exists() {
+        return Ok(());
+    }
+    fs::create_dir_all(backup_dir())?;
+    fs::copy(path, backup_path).map(|_| ())
+}
+
+/// 覆盖已安装的面板 config.json 前备份旧版本文件
+pub(super) fn backup_outdated_panel_configs(resources_root: &Path) -> std::io::Result<()> {
+    for panel in [Panel::Cascade, Panel::Manager] {
+        let config_path = panel.install_dir(resources_root).join("config.json");
+        let Ok(content) = fs::read(&config_path) else {
+            continue;
+        };
+        let Ok(config) = serde_json::from_slice::<Value>(&content) else {
+            continue;
+        };
+        let version = file_version(&config);
+        if version != PANEL_CONFIG_VERSION {
+            backup_file(&config_path, panel.dir_name(), version)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn record_step(config: &mut Map<String, Value>, step: u64) -> Result<(), String> {
+        config
+            .entry("steps")
+            .or_insert_with(|| Value::Array(Vec::new()))
+            .as_array_mut()
+            .ok_or("steps is not an array")?
+            .push(Value::from(step));
+        Ok(())
+    }
+
+    fn step_0(config: &mut Map<String, Value>) -> Result<(), String> {
+        record_step(config, 0)
+    }
+
+    fn step_1(config: &mut Map<String, Value>) -> Result<(), String> {
+        record_step(config, 1)
+    }
+
+    fn step_2(config: &mut Map<String, Value>) -> Result<(), String> {
+        record_step(config, 2)
+    }
+
+    fn fail(_config: &mut Map<String, Value>) -> Result<(), String> {
+        Err("unsupported".to_string())
+    }
+
+    #[test]
+    fn migrations_run_in_order_from_file_version() {
+        let chain: &[Migration] = &[step_0, step_1, step_2];
+
+        let mut unversioned = json!({ "keep": true });
+        assert_eq!(migrate(&mut unversioned, chain), Ok(0));
+        assert_eq!(
+            unversioned,
+            json!({ "keep": true, "steps": [0, 1, 2], "schemaVersion": 3 })
+        );
+
+        let mut v1 = json!({ "schemaVersion": 1 });
+        assert_eq!(migrate(&mut v1, chain), Ok(1));
+        assert_eq!(v1, json!({ "schemaVersion": 3, "steps": [1, 2] }));
+    }
+
+    #[test]
+    fn current_and_future_versions_are_left_untouched() {
+        let chain: &[Migration] = &[step_0, step_1];
+
+        let mut current = json!({ "schemaVersion": 2, "field": 1 });
+        assert_eq!(migrate(&mut current, chain), Ok(2));
+        assert_eq!(current, json!({ "schemaVersion": 2, "field": 1 }));
+
+        let mut future = json!({ "schemaVersion": 7, "newField": "x" });
+        assert_eq!(migrate(&mut future, chain), Ok(7));
+        assert_eq!(future, json!({ "schemaVersion": 7, "newField": "x" }));
+    }
+
+    #[test]
+    fn failed_migration_leaves_config_unchanged() {
+        let chain: &[Migration] = &[step_0, fail, step_2];
+        let original = json!({ "field": 1 });
+
+        let mut config = original.clone();
+        let error = migrate(&mut config, chain).unwrap_err();
+        assert_eq!(error, "v1 -> v2: unsupported");
+        assert_eq!(config, original);
+    }
+
+    #[test]
+    fn unversioned_app_config_upgrades_to_current() {
+        let mut config = json!({ "antigravityPath": "/opt/Antigravity" });
+        assert_eq!(migrate_app_config(&mut config), Ok(0));
+        assert_eq!(file_version(&config), APP_CONFIG_VERSION);
+        assert_eq!(config["antigravityPath"], "/opt/Antigravity");
+    }
+}
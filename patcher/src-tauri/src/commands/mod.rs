@@ -2,17 +2,57 @@
 //!
 //! 导出所有 Tauri 命令供前端调用
 
+mod appimage;
+mod asar;
+mod atomic;
+mod cdp;
 mod clean;
 mod config;
 mod detect;
+mod dev_sync;
+mod diagnostics;
+mod discovery;
+mod doctor;
+mod features;
 mod i18n;
+mod live_reload;
+mod live_verify;
+mod migrations;
+mod modules;
+mod oplog;
+mod packs;
 mod patch;
 mod paths;
+mod process;
+mod profiles;
+mod restore;
+mod shadow;
+#[cfg(test)]
+mod test_support;
 
 pub use clean::run_anti_clean;
-pub use config::{get_config, save_config};
+pub use config::{get_config, get_config_location, get_user_overlay_dir, save_config};
 pub use detect::{detect_antigravity_path, normalize_antigravity_path};
+pub use dev_sync::{get_dev_sync_status, start_dev_sync, stop_dev_sync};
+pub use diagnostics::export_diagnostics;
+pub use discovery::discover_antigravity_installs;
+pub use doctor::run_doctor;
+pub use live_verify::verify_live_patch;
+pub use oplog::get_operation_history;
+pub use packs::{
+    add_trusted_key, import_patch_pack, list_patch_packs, list_trusted_keys, remove_patch_pack,
+    remove_trusted_key, set_unsigned_pack_override,
+};
 pub use patch::{
     check_patch_status, install_patch, read_manager_patch_config, read_patch_config,
     uninstall_patch, update_config,
 };
+pub use process::find_antigravity_processes;
+pub use profiles::{
+    activate_profile, delete_profile, export_profile, import_profile, list_profiles,
+    rename_profile, save_profile,
+};
+pub use restore::{
+    delete_restore_point, list_restore_points, rollback_restore_point, set_restore_point_limit,
+};
+pub use shadow::{get_shadow_status, set_shadow_install};
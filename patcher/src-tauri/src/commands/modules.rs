@@ -0,0 +1,175 @@
+//! 功能模块清单
+//!
+//! 面板目录下的 modules.json 描述各功能模块包含的文件及依赖模块，
+//! 安装时只部署已启用的模块及其依赖，未列入任何模块的文件始终部署
+
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+
+use super::features::Panel;
+use super::i18n::CommandError;
+
+type ModuleResult<T> = Result<T, CommandError>;
+
+/// 功能模块清单文件名
+pub(super) const MODULES_FILE: &str = "modules.json";
+
+#[derive(Debug, Deserialize)]
+struct ModuleManifest {
+    modules: BTreeMap<String, ModuleEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ModuleEntry {
+    /// 启用该模块的配置项 (config.json 中的布尔选项)，为空表示始终启用
+    #[serde(default)]
+    feature: Option<String>,
+    /// 相对面板目录的文件路径
+    files: Vec<String>,
+    /// 依赖的模块
+    #[serde(default)]
+    requires: Vec<String>,
+}
+
+/// 按配置划分的模块文件 (相对面板目录)
+struct ModuleSelection {
+    deployed: BTreeSet<String>,
+    excluded: BTreeSet<String>,
+}
+
+fn module_with(_locale: Option<&str>, key: &'static str, vars: &[(&str, String)]) -> CommandError {
+    CommandError::key_with(key, vars)
+}
+
+fn invalid_manifest(locale: Option<&str>, panel: Panel, detail: String) -> CommandError {
+    module_with(
+        locale,
+        "patchBackend.errors.invalidModuleManifest",
+        &[("panel", panel.dir_name().to_string()), ("detail", detail)],
+    )
+}
+
+/// 获取补丁集中未启用而不需部署的文件 (相对 patches 目录)
+/// 面板没有 modules.json 时全部部署
+pub(super) fn excluded_files(
+    files: &[(String, Vec<u8>)],
+    panel: Panel,
+    options: &Map<String, Value>,
+    locale: Option<&str>,
+) -> ModuleResult<BTreeSet<String>> {
+    let manifest_path = format!("{}/{}", panel.dir_name(), MODULES_FILE);
+    let Some((_, content)) = files.iter().find(|(path, _)| *path == manifest_path) else {
+        return Ok(BTreeSet::new());
+    };
+
+    let selection = select_modules(content, panel, options, locale)?;
+    Ok(selection
+        .excluded
+        .into_iter()
+        .map(|file| format!("{}/{}", panel.dir_name(), file))
+        .collect())
+}
+
+/// 检查已安装的面板文件是否与当前配置启用的模块一致
+/// 需要新部署或移除模块文件时返回 true，旧版本安装 (没有 modules.json) 返回 false
+pub(super) fn needs_redeploy(
+    panel_dir: &Path,
+    panel: Panel,
+    options: &Map<String, Value>,
+    locale: Option<&str>,
+) -> ModuleResult<bool> {
+    let Ok(content) = fs::read(panel_dir.join(MODULES_FILE)) else {
+        return Ok(false);
+    };
+
+    let selection = select_modules(&content, panel, options, locale)?;
+    let missing = selection
+        .deployed
+        .iter()
+        .any(|file| !panel_dir.join(file).is_file());
+    let stale = selection
+        .excluded
+        .iter()
+        .any(|file| panel_dir.join(file).exists());
+
+    Ok(missing || stale)
+}
+
+fn select_modules(
+    content: &[u8],
+    panel: Panel,
+    options: &Map<String, Value>,
+    locale: Option<&str>,
+) -> ModuleResult<ModuleSelection> {
+    let manifest: ModuleManifest = serde_json::from_slice(content)
+        .map_err(|e| invalid_manifest(locale, panel, e.to_string()))?;
+
+    resolve(&manifest, options).map_err(|detail| invalid_manifest(locale, panel, detail))
+}
+
+/// 计算启用的模块 (含依赖) 并划分文件
+fn resolve(
+    manifest: &ModuleManifest,
+    options: &Map<String, Value>,
+) -> Result<ModuleSelection, String> {
+    for (name, module) in &manifest.modules {
+        if let Some(unknown) = module
+            .requires
+            .iter()
+            .find(|dep| !manifest.modules.contains_key(*dep))
+        {
+            return Err(format!("{} requires unknown module {}", name, unknown));
+        }
+        if let Some(file) = module.files.iter().find(|file| !is_safe_file(file)) {
+            return Err(format!("{}: invalid file path {}", name, file));
+        }
+    }
+
+    let mut pending = Vec::new();
+    for (name, module) in &manifest.modules {
+        let enabled = match module.feature.as_deref() {
+            None => true,
+            Some(feature) => options
+                .get(feature)
+                .and_then(Value::as_bool)
+                .ok_or_else(|| format!("{}: unknown feature {}", name, feature))?,
+        };
+        if enabled {
+            pending.push(name.as_str());
+        }
+    }
+
+    // 展开依赖
+    let mut enabled = BTreeSet::new();
+    while let Some(name) = pending.pop() {
+        if enabled.insert(name) {
+            pending.extend(manifest.modules[name].requires.iter().map(String::as_str));
+        }
+    }
+
+    let deployed: BTreeSet<String> = enabled
+        .iter()
+        .flat_map(|name| manifest.modules[*name].files.iter().cloned())
+        .collect();
+    let excluded = manifest
+        .modules
+        .values()
+        .flat_map(|module| module.files.iter())
+        .filter(|file| !deployed.contains(*file))
+        .cloned()
+        .collect();
+
+    Ok(ModuleSelection { deployed, excluded })
+}
+
+fn is_safe_file(file: &str) -> bool {
+    !file.is_empty()
+        && !file.starts_with('/')
+        && !file.contains('\\')
+        && file
+            .split('/')
+            .all(|part| !part.is_empty() && part != "." && part != "..")
+}
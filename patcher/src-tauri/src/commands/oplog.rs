@@ -0,0 +1,241 @@
+//! 操作日志模块
+//!
+//! 安装、卸载、更新配置、清理等操作各追加一条 JSON Lines 记录到应用数据目录的
+//! logs/operations.jsonl，包括目标目录、配置、逐个文件的操作、提权方式、耗时和结果；
+//! 文件超过大小上限时轮转
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cell::RefCell;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use super::config;
+
+/// 日志目录名 (位于应用数据目录)
+const LOG_DIR: &str = "logs";
+/// 当前日志文件名
+const LOG_FILE: &str = "operations.jsonl";
+/// 单个日志文件大小上限，超过后轮转
+const MAX_LOG_SIZE: u64 = 1024 * 1024;
+/// 保留的轮转文件数 (operations.1.jsonl ~ operations.N.jsonl)
+const MAX_ROTATED_FILES: usize = 3;
+/// 记录的命令输出长度上限
+const MAX_OUTPUT_LENGTH: usize = 64 * 1024;
+
+/// 单个文件操作
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileAction {
+    /// write / copy / backup / restore / delete / clean
+    pub action: String,
+    pub path: String,
+}
+
+/// 提权执行信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Elevation {
+    /// 提权方式 (pkexec / sudo / ...)
+    pub method: String,
+    /// 执行的命令行
+    pub command: String,
+}
+
+/// 一条操作记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OperationEntry {
+    /// 开始时间 (Unix 毫秒)
+    pub timestamp: u64,
+    /// install / uninstall / updateConfig / rollback / clean
+    pub operation: String,
+    /// 目标目录
+    pub target: Option<String>,
+    /// 操作使用的配置
+    pub config: Option<Value>,
+    #[serde(default)]
+    pub files: Vec<FileAction>,
+    pub elevation: Option<Elevation>,
+    /// 命令输出 (如清理脚本的输出)
+    pub output: Option<String>,
+    #[serde(rename = "durationMs")]
+    pub duration_ms: u64,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+/// 进行中的操作收集的明细
+#[derive(Default)]
+struct Recorder {
+    files: Vec<FileAction>,
+    elevation: Option<Elevation>,
+    output: Option<String>,
+}
+
+thread_local! {
+    static CURRENT: RefCell<Option<Recorder>> = const { RefCell::new(None) };
+}
+
+/// 执行操作并记录日志
+/// 嵌套调用时明细归入外层操作，只写一条记录
+pub(super) fn record<T>(
+    operation: &str,
+    target: Option<&str>,
+    config: Option<Value>,
+    run: impl FnOnce() -> Result<T, String>,
+) -> Result<T, String> {
+    let nested = CURRENT.with(|current| current.borrow().is_some());
+    if nested {
+        return run();
+    }
+
+    CURRENT.with(|current| *current.borrow_mut() = Some(Recorder::default()));
+    let timestamp = now_millis();
+    let started = Instant::now();
+    let result = run();
+    let recorder = CURRENT
+        .with(|current| current.borrow_mut().take())
+        .unwrap_or_default();
+
+    let entry = OperationEntry {
+        timestamp,
+        operation: operation.to_string(),
+        target: target.map(str::to_string),
+        config,
+        files: recorder.files,
+        elevation: recorder.elevation,
+        output: recorder.output,
+        duration_ms: started.elapsed().as_millis() as u64,
+        success: result.is_ok(),
+        error: result.as_ref().err().cloned(),
+    };
+    // 日志写入失败不影响操作本身
+    let _ = append_entry(&entry);
+
+    result
+}
+
+fn with_recorder(update: impl FnOnce(&mut Recorder)) {
+    CURRENT.with(|current| {
+        if let Some(recorder) = current.borrow_mut().as_mut() {
+            update(recorder);
+        }
+    });
+}
+
+/// 记录文件操作 (不在操作中时忽略)
+pub(super) fn file(action: &str, path: &Path) {
+    with_recorder(|recorder| {
+        recorder.files.push(FileAction {
+            action: action.to_string(),
+            path: path.to_string_lossy().to_string(),
+        })
+    });
+}
+
+/// 记录提权执行的命令
+pub(super) fn elevation(method: &str, command: String) {
+    with_recorder(|recorder| {
+        recorder.elevation = Some(Elevation {
+            method: method.to_string(),
+            command,
+        })
+    });
+}
+
+/// 记录命令输出，多次调用时按行追加
+pub(super) fn output(text: &str) {
+    with_recorder(|recorder| {
+        let mut text = match recorder.output.take() {
+            Some(previous) => format!("{}\n{}", previous, text),
+            None => text.to_string(),
+        };
+        if text.len() > MAX_OUTPUT_LENGTH {
+            let mut end = MAX_OUTPUT_LENGTH;
+            while !text.is_char_boundary(end) {
+                end -= 1;
+            }
+            text.truncate(end);
+        }
+        recorder.output = Some(text);
+    });
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// 获取日志目录
+pub(super) fn log_dir() -> PathBuf {
+    config::get_app_data_dir().join(LOG_DIR)
+}
+
+/// 日志文件，从新到旧
+fn log_files() -> Vec<PathBuf> {
+    let dir = log_dir();
+    std::iter::once(dir.join(LOG_FILE))
+        .chain((1..=MAX_ROTATED_FILES).map(|index| dir.join(rotated_name(index))))
+        .collect()
+}
+
+fn rotated_name(index: usize) -> String {
+    format!("operations.{}.jsonl", index)
+}
+
+fn append_entry(entry: &OperationEntry) -> std::io::Result<()> {
+    let dir = log_dir();
+    fs::create_dir_all(&dir)?;
+    let log_path = dir.join(LOG_FILE);
+
+    if fs::metadata(&log_path).is_ok_and(|meta| meta.len() >= MAX_LOG_SIZE) {
+        rotate(&dir)?;
+    }
+
+    let mut line = serde_json::to_string(entry)?;
+    line.push('\n');
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&log_path)?
+        .write_all(line.as_bytes())
+}
+
+fn rotate(dir: &Path) -> std::io::Result<()> {
+    let _ = fs::remove_file(dir.join(rotated_name(MAX_ROTATED_FILES)));
+    for index in (1..MAX_ROTATED_FILES).rev() {
+        let from = dir.join(rotated_name(index));
+        if from.exists() {
+            fs::rename(from, dir.join(rotated_name(index + 1)))?;
+        }
+    }
+    fs::rename(dir.join(LOG_FILE), dir.join(rotated_name(1)))
+}
+
+/// 查询最近的操作记录 (从新到旧)
+#[tauri::command]
+pub fn get_operation_history(limit: Option<usize>) -> Vec<OperationEntry> {
+    let limit = limit.unwrap_or(50);
+    let mut entries = Vec::new();
+
+    for path in log_files() {
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        // 损坏的行 (如写入中断) 跳过
+        let mut file_entries: Vec<OperationEntry> = content
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect();
+        file_entries.reverse();
+        entries.extend(file_entries);
+        if entries.len() >= limit {
+            break;
+        }
+    }
+
+    entries.truncate(limit);
+    entries
+}
@@ -0,0 +1,1195 @@
+//! 第三方补丁包模块
+//!
+//! 导入 zip / tar 格式的补丁包，校验清单、目录结构与适用的 Antigravity 版本范围，
+//! 存放于应用数据目录，安装时与内置补丁走同一套安装流程
+//! 补丁内容会以 root 权限写入安装目录，安装前必须通过 ed25519 签名校验或用户显式放行
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use ed25519_dalek::{Signature, VerifyingKey};
+use flate2::read::GzDecoder;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Component, Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::config::{self, AppConfig, TrustedKey};
+use super::i18n::CommandError;
+use super::patch::{is_cascade_file, is_manager_file, PatchSet};
+use super::paths;
+use crate::embedded::{self, ManifestFile, PatchManifest};
+
+type PackResult<T> = Result<T, CommandError>;
+
+/// 补丁包清单文件名
+const PACK_MANIFEST_FILE: &str = "pack.json";
+
+/// 补丁包清单的分离签名文件名 (base64 编码的 ed25519 签名)
+const PACK_SIGNATURE_FILE: &str = "pack.json.sig";
+
+/// 补丁包中除清单列出的文件外允许存在的文件
+const PACK_EXTRA_FILES: &[&str] = &[PACK_MANIFEST_FILE, PACK_SIGNATURE_FILE];
+
+/// 面板目录下由补丁程序生成的文件/目录，补丁包不得包含
+const RESERVED_PANEL_ENTRIES: &[&str] = &["config.json", "manifest.json", "user/", "vendor/"];
+
+/// 解压后的补丁包大小上限
+const MAX_PACK_SIZE: u64 = 64 * 1024 * 1024;
+
+fn pack_text(_locale: Option<&str>, key: &'static str) -> CommandError {
+    CommandError::key(key)
+}
+
+fn pack_with(_locale: Option<&str>, key: &'static str, vars: &[(&str, String)]) -> CommandError {
+    CommandError::key_with(key, vars)
+}
+
+/// 补丁包清单 (pack.json)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackManifest {
+    /// 补丁包 ID，同时作为存放目录名
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub description: String,
+    /// 适用的最低 Antigravity 版本 (含)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_antigravity_version: Option<String>,
+    /// 适用的最高 Antigravity 版本 (含)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_antigravity_version: Option<String>,
+    /// 补丁包中的面板文件及其哈希
+    pub files: Vec<ManifestFile>,
+}
+
+impl PackManifest {
+    /// 是否提供侧边栏补丁
+    fn provides_cascade(&self) -> bool {
+        self.files.iter().any(|file| is_cascade_file(&file.path))
+    }
+
+    /// 是否提供 Manager 补丁
+    fn provides_manager(&self) -> bool {
+        self.files.iter().any(|file| is_manager_file(&file.path))
+    }
+
+    /// 生成写入安装目录的补丁集清单
+    pub(super) fn patch_manifest(&self, filter: &dyn Fn(&str) -> bool) -> PatchManifest {
+        let digest_input: String = self
+            .files
+            .iter()
+            .map(|file| format!("{}\0{}\n", file.path, file.sha256))
+            .collect();
+
+        PatchManifest {
+            version: self.version.clone(),
+            digest: embedded::sha256_hex(digest_input.as_bytes()),
+            files: self
+                .files
+                .iter()
+                .filter(|file| filter(&file.path))
+                .cloned()
+                .collect(),
+            modified_files: Vec::new(),
+            pack: Some(self.id.clone()),
+        }
+    }
+}
+
+/// 返回给前端的补丁包信息
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PatchPackInfo {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    pub description: String,
+    pub min_antigravity_version: Option<String>,
+    pub max_antigravity_version: Option<String>,
+    /// 是否包含侧边栏补丁
+    pub cascade: bool,
+    /// 是否包含 Manager 补丁
+    pub manager: bool,
+    pub signature: SignatureStatus,
+    /// 验证通过的签名公钥名称
+    pub signer: Option<String>,
+    /// 未签名时用户是否已放行当前内容
+    pub unsigned_allowed: bool,
+}
+
+/// 补丁包签名状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SignatureStatus {
+    /// 由受信任的公钥签名
+    Trusted,
+    /// 有签名，但无法用任何受信任的公钥验证
+    Untrusted,
+    /// 没有签名
+    Unsigned,
+}
+
+fn pack_info(pack_root: &Path, source: &PackSource, config: &AppConfig) -> PatchPackInfo {
+    let manifest = &source.manifest;
+    let (signature, signer) = check_signature(pack_root, &source.bytes, &config.trusted_keys);
+    PatchPackInfo {
+        id: manifest.id.clone(),
+        name: manifest.name.clone(),
+        version: manifest.version.clone(),
+        description: manifest.description.clone(),
+        min_antigravity_version: manifest.min_antigravity_version.clone(),
+        max_antigravity_version: manifest.max_antigravity_version.clone(),
+        cascade: manifest.provides_cascade(),
+        manager: manifest.provides_manager(),
+        signature,
+        signer,
+        unsigned_allowed: is_unsigned_allowed(&source.bytes, &manifest.id, config),
+    }
+}
+
+/// 获取补丁包存放目录
+fn get_packs_dir() -> PathBuf {
+    config::get_app_data_dir().join("packs")
+}
+
+/// 导入补丁包
+/// 支持 .zip / .tar / .tar.gz / .tgz，同 ID 的补丁包会被替换
+#[tauri::command]
+pub fn import_patch_pack(
+    archive_path: String,
+    locale: Option<String>,
+) -> Result<PatchPackInfo, String> {
+    let locale_ref = locale.as_deref();
+    import_patch_pack_internal(Path::new(&archive_path), locale_ref)
+        .map_err(|err| err.to_message(locale_ref))
+}
+
+/// 列出已导入的补丁包 (忽略无法读取的目录)
+#[tauri::command]
+pub fn list_patch_packs() -> Vec<PatchPackInfo> {
+    let Ok(entries) = fs::read_dir(get_packs_dir()) else {
+        return Vec::new();
+    };
+
+    let config = config::read_config();
+    let mut packs: Vec<PatchPackInfo> = entries
+        .flatten()
+        .filter(|entry| !entry.file_name().to_string_lossy().starts_with('.'))
+        .filter_map(|entry| {
+            let pack_root = entry.path();
+            let source = read_pack_manifest(&pack_root, None).ok()?;
+            Some(pack_info(&pack_root, &source, &config))
+        })
+        .collect();
+    packs.sort_by(|a, b| a.name.cmp(&b.name));
+    packs
+}
+
+/// 删除已导入的补丁包
+#[tauri::command]
+pub fn remove_patch_pack(pack_id: String, locale: Option<String>) -> Result<(), String> {
+    let locale_ref = locale.as_deref();
+    remove_patch_pack_internal(&pack_id, locale_ref).map_err(|err| err.to_message(locale_ref))
+}
+
+fn remove_patch_pack_internal(pack_id: &str, locale: Option<&str>) -> PackResult<()> {
+    let pack_dir = pack_dir(pack_id, locale)?;
+    let mut config = config::load_config_for_update(locale)?;
+    fs::remove_dir_all(&pack_dir).map_err(|e| {
+        pack_with(
+            locale,
+            "packBackend.errors.removeFailed",
+            &[("detail", e.to_string())],
+        )
+    })?;
+
+    // 同时清除放行记录，避免同 ID 的新补丁包沿用
+    if config.unsigned_pack_overrides.remove(pack_id).is_some() {
+        config::save_config_internal(config, locale)?;
+    }
+    Ok(())
+}
+
+/// 放行或取消放行未签名的补丁包
+/// 放行记录绑定当前 pack.json 的哈希，补丁包内容变化后需要重新放行
+#[tauri::command]
+pub fn set_unsigned_pack_override(
+    pack_id: String,
+    allow: bool,
+    locale: Option<String>,
+) -> Result<PatchPackInfo, String> {
+    let locale_ref = locale.as_deref();
+    set_unsigned_pack_override_internal(&pack_id, allow, locale_ref)
+        .map_err(|err| err.to_message(locale_ref))
+}
+
+fn set_unsigned_pack_override_internal(
+    pack_id: &str,
+    allow: bool,
+    locale: Option<&str>,
+) -> PackResult<PatchPackInfo> {
+    let pack_dir = pack_dir(pack_id, locale)?;
+    let source = read_pack_manifest(&pack_dir, locale)?;
+
+    let mut config = config::load_config_for_update(locale)?;
+    if allow {
+        config
+            .unsigned_pack_overrides
+            .insert(pack_id.to_string(), embedded::sha256_hex(&source.bytes));
+    } else {
+        config.unsigned_pack_overrides.remove(pack_id);
+    }
+    config::save_config_internal(config, locale)?;
+
+    Ok(pack_info(&pack_dir, &source, &config::read_config()))
+}
+
+/// 获取受信任的签名公钥
+#[tauri::command]
+pub fn list_trusted_keys() -> Vec<TrustedKey> {
+    config::read_config().trusted_keys
+}
+
+/// 添加受信任的签名公钥 (base64 编码的 32 字节 ed25519 公钥)
+#[tauri::command]
+pub fn add_trusted_key(
+    name: String,
+    public_key: String,
+    locale: Option<String>,
+) -> Result<Vec<TrustedKey>, String> {
+    let locale_ref = locale.as_deref();
+    add_trusted_key_internal(&name, &public_key, locale_ref)
+        .map_err(|err| err.to_message(locale_ref))
+}
+
+fn add_trusted_key_internal(
+    name: &str,
+    public_key: &str,
+    locale: Option<&str>,
+) -> PackResult<Vec<TrustedKey>> {
+    let public_key = public_key.trim();
+    if parse_public_key(public_key).is_none() {
+        return Err(pack_text(locale, "packBackend.errors.invalidPublicKey"));
+    }
+
+    let mut config = config::load_config_for_update(locale)?;
+    config
+        .trusted_keys
+        .retain(|key| key.public_key != public_key);
+    config.trusted_keys.push(TrustedKey {
+        name: name.trim().to_string(),
+        public_key: public_key.to_string(),
+    });
+    let trusted_keys = config.trusted_keys.clone();
+    config::save_config_internal(config, locale)?;
+
+    Ok(trusted_keys)
+}
+
+/// 移除受信任的签名公钥
+#[tauri::command]
+pub fn remove_trusted_key(
+    public_key: String,
+    locale: Option<String>,
+) -> Result<Vec<TrustedKey>, String> {
+    let locale_ref = locale.as_deref();
+    let mut config =
+        config::load_config_for_update(locale_ref).map_err(|err| err.to_message(locale_ref))?;
+    config
+        .trusted_keys
+        .retain(|key| key.public_key != public_key);
+    let trusted_keys = config.trusted_keys.clone();
+    config::save_config_internal(config, locale_ref).map_err(|err| err.to_message(locale_ref))?;
+
+    Ok(trusted_keys)
+}
+
+/// 加载补丁包用于安装
+/// 补丁包提供的面板替换内置补丁中对应的面板，其余文件 (脚本、未提供的面板) 沿用内置补丁
+/// pack.json 与各文件只读取一次，签名和哈希校验的内容就是写入安装目录的内容
+pub(super) fn load_patch_set(pack_id: &str, locale: Option<&str>) -> PackResult<PatchSet> {
+    let pack_dir = pack_dir(pack_id, locale)?;
+    let source = read_pack_manifest(&pack_dir, locale)?;
+
+    // 签名校验先于其他任何处理
+    ensure_pack_trusted(&pack_dir, &source, locale)?;
+    let manifest = source.manifest;
+    // 导入后 pack.json 可能被替换，安装前重新校验文件路径
+    validate_pack_manifest(&manifest, locale)?;
+
+    // 导入后文件可能被修改，安装前重新校验
+    let pack_files = read_pack_files(&pack_dir, &manifest, locale)?;
+    let files = merge_pack_files(
+        PatchSet::embedded(locale)?.files,
+        pack_files,
+        &manifest,
+        locale,
+    )?;
+
+    Ok(PatchSet {
+        files,
+        pack: Some(manifest),
+    })
+}
+
+/// 检查补丁集是否支持写入目标的 Antigravity 版本
+/// 内置补丁或无法读取版本号时不做限制
+pub(super) fn ensure_compatible(
+    patch_set: &PatchSet,
+    resources_root: &Path,
+    locale: Option<&str>,
+) -> PackResult<()> {
+    let Some(manifest) = &patch_set.pack else {
+        return Ok(());
+    };
+    match paths::read_antigravity_version(resources_root) {
+        Some(version) => ensure_version_compatible(manifest, &version, locale),
+        None => Ok(()),
+    }
+}
+
+/// 用补丁包文件替换内置补丁中对应的面板
+/// 补丁包文件不得与保留的内置文件 (安装脚本等) 重叠
+fn merge_pack_files(
+    embedded_files: Vec<(String, Vec<u8>)>,
+    pack_files: Vec<(String, Vec<u8>)>,
+    manifest: &PackManifest,
+    locale: Option<&str>,
+) -> PackResult<Vec<(String, Vec<u8>)>> {
+    let provides_cascade = manifest.provides_cascade();
+    let provides_manager = manifest.provides_manager();
+    let mut files: Vec<(String, Vec<u8>)> = embedded_files
+        .into_iter()
+        .filter(|(relative_path, _)| {
+            !(provides_cascade && is_cascade_file(relative_path)
+                || provides_manager && is_manager_file(relative_path))
+        })
+        .collect();
+
+    for (relative_path, content) in pack_files {
+        let overlaps = !(is_cascade_file(&relative_path) || is_manager_file(&relative_path))
+            || files.iter().any(|(kept, _)| *kept == relative_path);
+        if overlaps {
+            return Err(pack_with(
+                locale,
+                "packBackend.errors.invalidFilePath",
+                &[("path", relative_path)],
+            ));
+        }
+        files.push((relative_path, content));
+    }
+
+    Ok(files)
+}
+
+fn import_patch_pack_internal(
+    archive_path: &Path,
+    locale: Option<&str>,
+) -> PackResult<PatchPackInfo> {
+    let packs_dir = get_packs_dir();
+    fs::create_dir_all(&packs_dir).map_err(|e| {
+        pack_with(
+            locale,
+            "packBackend.errors.createPacksDirFailed",
+            &[("detail", e.to_string())],
+        )
+    })?;
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let staging_dir = packs_dir.join(format!(".staging-{}", timestamp));
+
+    let result = extract_archive(archive_path, &staging_dir, locale)
+        .and_then(|()| find_pack_root(&staging_dir, locale))
+        .and_then(|pack_root| {
+            let source = read_pack_manifest(&pack_root, locale)?;
+            validate_pack_manifest(&source.manifest, locale)?;
+            read_pack_files(&pack_root, &source.manifest, locale)?;
+            let target_dir = packs_dir.join(&source.manifest.id);
+            store_pack(&pack_root, &target_dir, locale)?;
+            Ok(pack_info(&target_dir, &source, &config::read_config()))
+        });
+
+    let _ = fs::remove_dir_all(&staging_dir);
+    result
+}
+
+/// 解压补丁包到临时目录
+fn extract_archive(archive_path: &Path, target_dir: &Path, locale: Option<&str>) -> PackResult<()> {
+    let file_name = archive_path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default();
+
+    let file = File::open(archive_path).map_err(|e| {
+        pack_with(
+            locale,
+            "packBackend.errors.openArchiveFailed",
+            &[("detail", e.to_string())],
+        )
+    })?;
+
+    fs::create_dir_all(target_dir).map_err(|e| {
+        pack_with(
+            locale,
+            "packBackend.errors.extractFailed",
+            &[("detail", e.to_string())],
+        )
+    })?;
+
+    if file_name.ends_with(".zip") {
+        extract_zip(file, target_dir, locale)
+    } else if file_name.ends_with(".tar.gz") || file_name.ends_with(".tgz") {
+        extract_tar(GzDecoder::new(file), target_dir, locale)
+    } else if file_name.ends_with(".tar") {
+        extract_tar(file, target_dir, locale)
+    } else {
+        Err(pack_with(
+            locale,
+            "packBackend.errors.unsupportedFormat",
+            &[("name", file_name)],
+        ))
+    }
+}
+
+fn extract_zip(file: File, target_dir: &Path, locale: Option<&str>) -> PackResult<()> {
+    let extract_error = |detail: String| {
+        pack_with(
+            locale,
+            "packBackend.errors.extractFailed",
+            &[("detail", detail)],
+        )
+    };
+
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| extract_error(e.to_string()))?;
+    let mut remaining = MAX_PACK_SIZE;
+
+    for index in 0..archive.len() {
+        let mut entry = archive
+            .by_index(index)
+            .map_err(|e| extract_error(e.to_string()))?;
+        let Some(relative_path) = entry.enclosed_name() else {
+            return Err(pack_with(
+                locale,
+                "packBackend.errors.unsafeEntry",
+                &[("path", entry.name().to_string())],
+            ));
+        };
+
+        let full_path = target_dir.join(relative_path);
+        if entry.is_dir() {
+            fs::create_dir_all(&full_path).map_err(|e| extract_error(e.to_string()))?;
+        } else {
+            write_entry(&mut entry, &full_path, &mut remaining, locale)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn extract_tar(reader: impl Read, target_dir: &Path, locale: Option<&str>) -> PackResult<()> {
+    let extract_error = |detail: String| {
+        pack_with(
+            locale,
+            "packBackend.errors.extractFailed",
+            &[("detail", detail)],
+        )
+    };
+
+    let mut archive = tar::Archive::new(reader);
+    let mut remaining = MAX_PACK_SIZE;
+
+    for entry in archive
+        .entries()
+        .map_err(|e| extract_error(e.to_string()))?
+    {
+        let mut entry = entry.map_err(|e| extract_error(e.to_string()))?;
+        let relative_path = entry
+            .path()
+            .map_err(|e| extract_error(e.to_string()))?
+            .into_owned();
+        if !is_safe_relative_path(&relative_path) {
+            return Err(pack_with(
+                locale,
+                "packBackend.errors.unsafeEntry",
+                &[("path", relative_path.display().to_string())],
+            ));
+        }
+
+        let full_path = target_dir.join(&relative_path);
+        let entry_type = entry.header().entry_type();
+        if entry_type.is_dir() {
+            fs::create_dir_all(&full_path).map_err(|e| extract_error(e.to_string()))?;
+        } else if entry_type.is_file() {
+            write_entry(&mut entry, &full_path, &mut remaining, locale)?;
+        } else if !entry_type.is_pax_global_extensions() && !entry_type.is_pax_local_extensions() {
+            // 不支持符号链接、硬链接等特殊条目
+            return Err(pack_with(
+                locale,
+                "packBackend.errors.unsafeEntry",
+                &[("path", relative_path.display().to_string())],
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// 写入单个解压文件，超出大小上限时中止
+fn write_entry(
+    reader: &mut impl Read,
+    full_path: &Path,
+    remaining: &mut u64,
+    locale: Option<&str>,
+) -> PackResult<()> {
+    let extract_error = |detail: String| {
+        pack_with(
+            locale,
+            "packBackend.errors.extractFailed",
+            &[("detail", detail)],
+        )
+    };
+
+    if let Some(parent) = full_path.parent() {
+        fs::create_dir_all(parent).map_err(|e| extract_error(e.to_string()))?;
+    }
+
+    let mut output = File::create(full_path).map_err(|e| extract_error(e.to_string()))?;
+    let written = io::copy(&mut reader.take(*remaining + 1), &mut output)
+        .map_err(|e| extract_error(e.to_string()))?;
+    if written > *remaining {
+        return Err(pack_with(
+            locale,
+            "packBackend.errors.tooLarge",
+            &[("limit", format!("{} MB", MAX_PACK_SIZE / 1024 / 1024))],
+        ));
+    }
+    *remaining -= written;
+
+    Ok(())
+}
+
+/// 只允许普通的相对路径，拒绝绝对路径和 ..
+fn is_safe_relative_path(path: &Path) -> bool {
+    path.components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+}
+
+/// 定位补丁包根目录
+/// 允许归档内多包一层目录 (直接压缩整个文件夹的情况)
+fn find_pack_root(extracted_dir: &Path, locale: Option<&str>) -> PackResult<PathBuf> {
+    if extracted_dir.join(PACK_MANIFEST_FILE).is_file() {
+        return Ok(extracted_dir.to_path_buf());
+    }
+
+    let entries: Vec<PathBuf> = fs::read_dir(extracted_dir)
+        .map(|entries| entries.flatten().map(|entry| entry.path()).collect())
+        .unwrap_or_default();
+    if let [single] = entries.as_slice() {
+        if single.is_dir() && single.join(PACK_MANIFEST_FILE).is_file() {
+            return Ok(single.clone());
+        }
+    }
+
+    Err(pack_text(locale, "packBackend.errors.manifestMissing"))
+}
+
+/// 读取一次的 pack.json: 签名校验、放行记录哈希与解析都使用同一份内容
+struct PackSource {
+    bytes: Vec<u8>,
+    manifest: PackManifest,
+}
+
+fn read_pack_manifest(pack_root: &Path, locale: Option<&str>) -> PackResult<PackSource> {
+    let bytes = fs::read(pack_root.join(PACK_MANIFEST_FILE))
+        .map_err(|_| pack_text(locale, "packBackend.errors.manifestMissing"))?;
+    let manifest = parse_pack_manifest(&bytes, locale)?;
+    Ok(PackSource { bytes, manifest })
+}
+
+fn parse_pack_manifest(bytes: &[u8], locale: Option<&str>) -> PackResult<PackManifest> {
+    serde_json::from_slice(bytes).map_err(|e| {
+        pack_with(
+            locale,
+            "packBackend.errors.manifestInvalid",
+            &[("detail", e.to_string())],
+        )
+    })
+}
+
+/// 校验补丁包 ID，ID 会作为目录名使用
+fn is_valid_pack_id(pack_id: &str) -> bool {
+    !pack_id.is_empty()
+        && !pack_id.starts_with('.')
+        && pack_id
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '-' | '_' | '.'))
+}
+
+fn pack_dir(pack_id: &str, locale: Option<&str>) -> PackResult<PathBuf> {
+    if !is_valid_pack_id(pack_id) {
+        return Err(pack_with(
+            locale,
+            "packBackend.errors.invalidId",
+            &[("id", pack_id.to_string())],
+        ));
+    }
+
+    let pack_dir = get_packs_dir().join(pack_id);
+    if !pack_dir.join(PACK_MANIFEST_FILE).is_file() {
+        return Err(pack_with(
+            locale,
+            "packBackend.errors.notFound",
+            &[("id", pack_id.to_string())],
+        ));
+    }
+    Ok(pack_dir)
+}
+
+/// 校验清单字段、版本范围与文件路径
+fn validate_pack_manifest(manifest: &PackManifest, locale: Option<&str>) -> PackResult<()> {
+    if !is_valid_pack_id(&manifest.id) {
+        return Err(pack_with(
+            locale,
+            "packBackend.errors.invalidId",
+            &[("id", manifest.id.clone())],
+        ));
+    }
+
+    for (field, value) in [("name", &manifest.name), ("version", &manifest.version)] {
+        if value.trim().is_empty() {
+            return Err(pack_with(
+                locale,
+                "packBackend.errors.missingField",
+                &[("field", field.to_string())],
+            ));
+        }
+    }
+
+    let min_version = parse_range_bound(manifest.min_antigravity_version.as_deref(), locale)?;
+    let max_version = parse_range_bound(manifest.max_antigravity_version.as_deref(), locale)?;
+    if let (Some(min_version), Some(max_version)) = (&min_version, &max_version) {
+        if compare_versions(min_version, max_version).is_gt() {
+            return Err(pack_with(
+                locale,
+                "packBackend.errors.invalidVersionRange",
+                &[("detail", version_range_text(manifest))],
+            ));
+        }
+    }
+
+    if manifest.files.is_empty() {
+        return Err(pack_text(locale, "packBackend.errors.emptyFiles"));
+    }
+
+    for file in &manifest.files {
+        let path = file.path.as_str();
+        if path.contains('\\') || !is_safe_relative_path(Path::new(path)) {
+            return Err(pack_with(
+                locale,
+                "packBackend.errors.invalidFilePath",
+                &[("path", path.to_string())],
+            ));
+        }
+        if !is_cascade_file(path) && !is_manager_file(path) {
+            return Err(pack_with(
+                locale,
+                "packBackend.errors.invalidFilePath",
+                &[("path", path.to_string())],
+            ));
+        }
+
+        let panel_relative = path
+            .strip_prefix("cascade-panel/")
+            .or_else(|| path.strip_prefix("manager-panel/"));
+        if let Some(panel_relative) = panel_relative {
+            let reserved =
+                RESERVED_PANEL_ENTRIES
+                    .iter()
+                    .any(|entry| match entry.strip_suffix('/') {
+                        Some(dir) => panel_relative.starts_with(entry) || panel_relative == dir,
+                        None => panel_relative == *entry,
+                    });
+            if reserved {
+                return Err(pack_with(
+                    locale,
+                    "packBackend.errors.reservedFilePath",
+                    &[("path", path.to_string())],
+                ));
+            }
+        }
+    }
+
+    // 面板入口 HTML 与面板目录必须同时提供
+    let panels = [
+        ("cascade-panel", "cascade-panel.html", "cascade-panel/"),
+        (
+            "manager-panel",
+            "workbench-jetski-agent.html",
+            "manager-panel/",
+        ),
+    ];
+    for (panel, entry_html, dir_prefix) in panels {
+        let has_entry = manifest.files.iter().any(|file| file.path == entry_html);
+        let has_dir = manifest
+            .files
+            .iter()
+            .any(|file| file.path.starts_with(dir_prefix));
+        if has_entry != has_dir {
+            return Err(pack_with(
+                locale,
+                "packBackend.errors.incompleteLayout",
+                &[("panel", panel.to_string())],
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// 读取清单中的文件并校验大小与哈希，且不包含清单外的文件
+/// 返回校验过的内容，安装时直接使用，不再从磁盘读取
+fn read_pack_files(
+    pack_root: &Path,
+    manifest: &PackManifest,
+    locale: Option<&str>,
+) -> PackResult<Vec<(String, Vec<u8>)>> {
+    let mut files = Vec::with_capacity(manifest.files.len());
+    for file in &manifest.files {
+        let content = fs::read(pack_root.join(&file.path)).map_err(|_| {
+            pack_with(
+                locale,
+                "packBackend.errors.fileMissing",
+                &[("path", file.path.clone())],
+            )
+        })?;
+        if content.len() != file.size || embedded::sha256_hex(&content) != file.sha256 {
+            return Err(pack_with(
+                locale,
+                "packBackend.errors.fileMismatch",
+                &[("path", file.path.clone())],
+            ));
+        }
+        files.push((file.path.clone(), content));
+    }
+
+    let mut on_disk = Vec::new();
+    collect_relative_files(pack_root, "", &mut on_disk);
+    for relative_path in on_disk {
+        let listed = PACK_EXTRA_FILES.contains(&relative_path.as_str())
+            || manifest.files.iter().any(|file| file.path == relative_path);
+        if !listed {
+            return Err(pack_with(
+                locale,
+                "packBackend.errors.unlistedFile",
+                &[("path", relative_path)],
+            ));
+        }
+    }
+
+    Ok(files)
+}
+
+fn collect_relative_files(dir: &Path, prefix: &str, files: &mut Vec<String>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let relative_path = if prefix.is_empty() {
+            name
+        } else {
+            format!("{}/{}", prefix, name)
+        };
+        if entry.path().is_dir() {
+            collect_relative_files(&entry.path(), &relative_path, files);
+        } else {
+            files.push(relative_path);
+        }
+    }
+}
+
+/// 将校验通过的补丁包移动到存放目录，替换同 ID 的旧版本
+fn store_pack(pack_root: &Path, target_dir: &Path, locale: Option<&str>) -> PackResult<()> {
+    let store_error = |detail: String| {
+        pack_with(
+            locale,
+            "packBackend.errors.storeFailed",
+            &[("detail", detail)],
+        )
+    };
+
+    if target_dir.exists() {
+        fs::remove_dir_all(target_dir).map_err(|e| store_error(e.to_string()))?;
+    }
+    fs::rename(pack_root, target_dir).map_err(|e| store_error(e.to_string()))
+}
+
+/// 使用受信任的公钥验证 pack.json 的分离签名
+/// pack.json 中包含所有文件的哈希，因此签名覆盖补丁包的全部内容
+/// manifest_bytes 为已读入内存、随后解析使用的 pack.json 内容
+fn check_signature(
+    pack_root: &Path,
+    manifest_bytes: &[u8],
+    trusted_keys: &[TrustedKey],
+) -> (SignatureStatus, Option<String>) {
+    let Ok(signature_text) = fs::read_to_string(pack_root.join(PACK_SIGNATURE_FILE)) else {
+        return (SignatureStatus::Unsigned, None);
+    };
+    let Some(signature) = BASE64
+        .decode(signature_text.trim())
+        .ok()
+        .and_then(|bytes| Signature::from_slice(&bytes).ok())
+    else {
+        return (SignatureStatus::Untrusted, None);
+    };
+
+    trusted_keys
+        .iter()
+        .find(|key| {
+            parse_public_key(&key.public_key).is_some_and(|verifying_key| {
+                verifying_key
+                    .verify_strict(manifest_bytes, &signature)
+                    .is_ok()
+            })
+        })
+        .map(|key| (SignatureStatus::Trusted, Some(key.name.clone())))
+        .unwrap_or((SignatureStatus::Untrusted, None))
+}
+
+fn parse_public_key(public_key: &str) -> Option<VerifyingKey> {
+    let bytes: [u8; 32] = BASE64.decode(public_key.trim()).ok()?.try_into().ok()?;
+    VerifyingKey::from_bytes(&bytes).ok()
+}
+
+/// 放行记录绑定 pack.json 的 SHA-256，内容变化后失效
+fn is_unsigned_allowed(manifest_bytes: &[u8], pack_id: &str, config: &AppConfig) -> bool {
+    config
+        .unsigned_pack_overrides
+        .get(pack_id)
+        .is_some_and(|allowed| *allowed == embedded::sha256_hex(manifest_bytes))
+}
+
+/// 安装前确认补丁包可信: 受信任的签名，或用户已放行的未签名补丁包
+fn ensure_pack_trusted(
+    pack_root: &Path,
+    source: &PackSource,
+    locale: Option<&str>,
+) -> PackResult<()> {
+    let config = config::read_config();
+    let manifest = &source.manifest;
+    match check_signature(pack_root, &source.bytes, &config.trusted_keys).0 {
+        SignatureStatus::Trusted => Ok(()),
+        SignatureStatus::Unsigned if is_unsigned_allowed(&source.bytes, &manifest.id, &config) => {
+            Ok(())
+        }
+        SignatureStatus::Unsigned => Err(pack_with(
+            locale,
+            "packBackend.errors.unsignedNotAllowed",
+            &[("id", manifest.id.clone())],
+        )),
+        SignatureStatus::Untrusted => Err(pack_with(
+            locale,
+            "packBackend.errors.signatureUntrusted",
+            &[("id", manifest.id.clone())],
+        )),
+    }
+}
+
+/// 检查 Antigravity 版本是否在补丁包声明的范围内
+fn ensure_version_compatible(
+    manifest: &PackManifest,
+    version: &str,
+    locale: Option<&str>,
+) -> PackResult<()> {
+    let current = parse_version(version);
+    let min_version = parse_range_bound(manifest.min_antigravity_version.as_deref(), locale)?;
+    let max_version = parse_range_bound(manifest.max_antigravity_version.as_deref(), locale)?;
+
+    let too_old = min_version.is_some_and(|min| compare_versions(&current, &min).is_lt());
+    let too_new = max_version.is_some_and(|max| compare_versions(&current, &max).is_gt());
+    if too_old || too_new {
+        return Err(pack_with(
+            locale,
+            "packBackend.errors.incompatible",
+            &[
+                ("version", version.to_string()),
+                ("range", version_range_text(manifest)),
+            ],
+        ));
+    }
+
+    Ok(())
+}
+
+fn parse_range_bound(value: Option<&str>, locale: Option<&str>) -> PackResult<Option<Vec<u64>>> {
+    let Some(value) = value else {
+        return Ok(None);
+    };
+
+    let version = parse_version(value);
+    if version.is_empty() {
+        return Err(pack_with(
+            locale,
+            "packBackend.errors.invalidVersionRange",
+            &[("detail", value.to_string())],
+        ));
+    }
+    Ok(Some(version))
+}
+
+/// 解析版本号的数字部分，如 "1.13.2-insider" -> [1, 13, 2]
+/// 无法解析时返回空列表
+pub(super) fn parse_version(value: &str) -> Vec<u64> {
+    let core = value
+        .trim()
+        .trim_start_matches('v')
+        .split(['-', '+'])
+        .next()
+        .unwrap_or("");
+    let parts: Option<Vec<u64>> = core.split('.').map(|part| part.parse().ok()).collect();
+    parts.unwrap_or_default()
+}
+
+/// 按段比较版本号，缺失的段视为 0
+pub(super) fn compare_versions(a: &[u64], b: &[u64]) -> std::cmp::Ordering {
+    let len = a.len().max(b.len());
+    (0..len)
+        .map(|i| {
+            let left = a.get(i).copied().unwrap_or(0);
+            let right = b.get(i).copied().unwrap_or(0);
+            left.cmp(&right)
+        })
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(std::cmp::Ordering::Equal)
+}
+
+fn version_range_text(manifest: &PackManifest) -> String {
+    format!(
+        "{} ~ {}",
+        manifest.min_antigravity_version.as_deref().unwrap_or("*"),
+        manifest.max_antigravity_version.as_deref().unwrap_or("*")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::test_support::TempDir;
+    use ed25519_dalek::{Signer, SigningKey};
+    use std::io::{Cursor, Write};
+
+    fn error_key(error: CommandError) -> &'static str {
+        match error {
+            CommandError::Localized { key, .. } => key,
+            CommandError::Raw(message) => panic!("unexpected raw error: {}", message),
+        }
+    }
+
+    const PANEL_FILE: &str = "cascade-panel/cascade-panel.js";
+    const PANEL_CONTENT: &[u8] = b"console.log('pack');";
+
+    /// 写入只含一个面板文件的补丁包，返回 pack.json 内容
+    fn write_pack(root: &Path) -> Vec<u8> {
+        fs::create_dir_all(root.join("cascade-panel")).unwrap();
+        fs::write(root.join(PANEL_FILE), PANEL_CONTENT).unwrap();
+        let manifest = PackManifest {
+            id: "test-pack".into(),
+            name: "Test".into(),
+            version: "1.0.0".into(),
+            description: String::new(),
+            min_antigravity_version: None,
+            max_antigravity_version: None,
+            files: vec![ManifestFile {
+                path: PANEL_FILE.into(),
+                sha256: embedded::sha256_hex(PANEL_CONTENT),
+                size: PANEL_CONTENT.len(),
+            }],
+        };
+        let bytes = serde_json::to_vec(&manifest).unwrap();
+        fs::write(root.join(PACK_MANIFEST_FILE), &bytes).unwrap();
+        bytes
+    }
+
+    fn sign(root: &Path, key: &SigningKey, bytes: &[u8]) -> TrustedKey {
+        let signature = key.sign(bytes);
+        fs::write(
+            root.join(PACK_SIGNATURE_FILE),
+            BASE64.encode(signature.to_bytes()),
+        )
+        .unwrap();
+        TrustedKey {
+            name: "tester".into(),
+            public_key: BASE64.encode(key.verifying_key().to_bytes()),
+        }
+    }
+
+    #[test]
+    fn signed_pack_is_trusted() {
+        let dir = TempDir::new("signed");
+        let bytes = write_pack(&dir.0);
+        let trusted = sign(&dir.0, &SigningKey::from_bytes(&[7; 32]), &bytes);
+
+        let source = read_pack_manifest(&dir.0, None).unwrap();
+        assert_eq!(source.bytes, bytes);
+        let (status, signer) = check_signature(&dir.0, &source.bytes, &[trusted]);
+        assert_eq!(status, SignatureStatus::Trusted);
+        assert_eq!(signer.as_deref(), Some("tester"));
+    }
+
+    #[test]
+    fn tampered_manifest_is_untrusted() {
+        let dir = TempDir::new("tampered");
+        let bytes = write_pack(&dir.0);
+        let trusted = sign(&dir.0, &SigningKey::from_bytes(&[7; 32]), &bytes);
+
+        let mut tampered = bytes.clone();
+        tampered.extend_from_slice(b" ");
+        let (status, _) = check_signature(&dir.0, &tampered, &[trusted]);
+        assert_eq!(status, SignatureStatus::Untrusted);
+
+        // 其他公钥签名的补丁包同样不可信
+        let other = TrustedKey {
+            name: "other".into(),
+            public_key: BASE64.encode(SigningKey::from_bytes(&[9; 32]).verifying_key().to_bytes()),
+        };
+        let (status, _) = check_signature(&dir.0, &bytes, &[other]);
+        assert_eq!(status, SignatureStatus::Untrusted);
+    }
+
+    #[test]
+    fn unsigned_pack_needs_matching_override() {
+        let dir = TempDir::new("unsigned");
+        let bytes = write_pack(&dir.0);
+        assert_eq!(
+            check_signature(&dir.0, &bytes, &[]).0,
+            SignatureStatus::Unsigned
+        );
+
+        let mut config = AppConfig::default();
+        assert!(!is_unsigned_allowed(&bytes, "test-pack", &config));
+        config
+            .unsigned_pack_overrides
+            .insert("test-pack".into(), embedded::sha256_hex(&bytes));
+        assert!(is_unsigned_allowed(&bytes, "test-pack", &config));
+        assert!(!is_unsigned_allowed(b"{}", "test-pack", &config));
+    }
+
+    #[test]
+    fn pack_files_are_verified_buffers() {
+        let dir = TempDir::new("files");
+        write_pack(&dir.0);
+        let manifest = read_pack_manifest(&dir.0, None).unwrap().manifest;
+
+        let files = read_pack_files(&dir.0, &manifest, None).unwrap();
+        assert_eq!(
+            files,
+            vec![(PANEL_FILE.to_string(), PANEL_CONTENT.to_vec())]
+        );
+
+        fs::write(dir.0.join(PANEL_FILE), b"console.log('evil');").unwrap();
+        let error = read_pack_files(&dir.0, &manifest, None).unwrap_err();
+        assert_eq!(error_key(error), "packBackend.errors.fileMismatch");
+
+        fs::write(dir.0.join(PANEL_FILE), PANEL_CONTENT).unwrap();
+        fs::write(dir.0.join("cascade-panel/extra.js"), b"").unwrap();
+        let error = read_pack_files(&dir.0, &manifest, None).unwrap_err();
+        assert_eq!(error_key(error), "packBackend.errors.unlistedFile");
+    }
+
+    #[test]
+    fn pack_cannot_replace_scripts() {
+        let dir = TempDir::new("scripts");
+        write_pack(&dir.0);
+        let mut manifest = read_pack_manifest(&dir.0, None).unwrap().manifest;
+        let embedded_files = vec![
+            ("anti-power.sh".to_string(), b"#!/bin/sh".to_vec()),
+            (PANEL_FILE.to_string(), b"builtin".to_vec()),
+        ];
+
+        let files = merge_pack_files(
+            embedded_files.clone(),
+            vec![(PANEL_FILE.to_string(), PANEL_CONTENT.to_vec())],
+            &manifest,
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            files,
+            vec![
+                ("anti-power.sh".to_string(), b"#!/bin/sh".to_vec()),
+                (PANEL_FILE.to_string(), PANEL_CONTENT.to_vec()),
+            ]
+        );
+
+        let script = vec![("anti-power.sh".to_string(), b"evil".to_vec())];
+        let error = merge_pack_files(embedded_files, script, &manifest, None).unwrap_err();
+        assert_eq!(error_key(error), "packBackend.errors.invalidFilePath");
+
+        manifest.files[0].path = "anti-power.sh".into();
+        let error = validate_pack_manifest(&manifest, None).unwrap_err();
+        assert_eq!(error_key(error), "packBackend.errors.invalidFilePath");
+    }
+
+    #[test]
+    fn zip_slip_is_rejected() {
+        let dir = TempDir::new("zip-slip");
+        let archive_path = dir.0.join("pack.zip");
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Stored);
+        writer.start_file("../evil.js", options).unwrap();
+        writer.write_all(b"evil").unwrap();
+        fs::write(&archive_path, writer.finish().unwrap().into_inner()).unwrap();
+
+        let target = dir.0.join("out");
+        fs::create_dir_all(&target).unwrap();
+        let error = extract_zip(File::open(&archive_path).unwrap(), &target, None).unwrap_err();
+        assert_eq!(error_key(error), "packBackend.errors.unsafeEntry");
+        assert!(!dir.0.join("evil.js").exists());
+    }
+
+    #[test]
+    fn tar_symlink_is_rejected() {
+        let dir = TempDir::new("tar-symlink");
+        let mut builder = tar::Builder::new(Vec::new());
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(tar::EntryType::Symlink);
+        header.set_size(0);
+        builder
+            .append_link(&mut header, "cascade-panel.html", "/etc/passwd")
+            .unwrap();
+        let archive = builder.into_inner().unwrap();
+
+        let error = extract_tar(Cursor::new(archive), &dir.0, None).unwrap_err();
+        assert_eq!(error_key(error), "packBackend.errors.unsafeEntry");
+        assert!(fs::symlink_metadata(dir.0.join("cascade-panel.html")).is_err());
+    }
+
+    #[test]
+    fn oversized_entry_is_rejected() {
+        let dir = TempDir::new("size");
+        let path = dir.0.join("big.js");
+
+        let mut remaining = 8;
+        write_entry(&mut &b"12345678"[..], &path, &mut remaining, None).unwrap();
+        assert_eq!(remaining, 0);
+
+        let mut remaining = 8;
+        let error = write_entry(&mut &b"123456789"[..], &path, &mut remaining, None).unwrap_err();
+        assert_eq!(error_key(error), "packBackend.errors.tooLarge");
+    }
+
+    #[test]
+    fn unsafe_relative_paths() {
+        assert!(is_safe_relative_path(Path::new("cascade-panel/a.js")));
+        assert!(!is_safe_relative_path(Path::new("../a.js")));
+        assert!(!is_safe_relative_path(Path::new("/etc/passwd")));
+    }
+}
@@ -2,17 +2,38 @@
 //!
 //! 处理补丁文件的安装、卸载、配置更新等操作
 
+use super::appimage;
+use super::asar::{self, Resources};
+use super::atomic::{write_atomic, FileOp};
+use super::config;
+use super::features::{
+    installed_options, validate_features, FeatureConfig, ManagerFeatureConfig, Panel, PanelFeatures,
+};
 use super::i18n::{self, CommandError};
+use super::live_reload;
+use super::migrations;
+use super::modules;
+use super::oplog;
+use super::packs::{self, PackManifest};
 use super::paths;
-use crate::embedded::{self, EmbeddedError};
-use serde::{Deserialize, Serialize};
+use super::process;
+use super::restore;
+use super::shadow;
+use crate::embedded::{self, EmbeddedError, PatchManifest};
+use serde::Serialize;
 use serde_json::Value;
 use std::fs;
 use std::io::ErrorKind;
 use std::path::{Path, PathBuf};
 
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+use std::collections::BTreeSet;
 #[cfg(any(target_os = "macos", target_os = "linux"))]
 use std::env;
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+use std::io::Read;
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+use std::os::unix::fs::DirBuilderExt;
 #[cfg(unix)]
 use std::os::unix::fs::PermissionsExt;
 #[cfg(any(target_os = "macos", target_os = "linux"))]
@@ -20,11 +41,26 @@ use std::process::Command;
 
 /// 需要从 product.json checksums 中移除的文件路径
 /// (这些文件会被补丁修改，如果不移除校验和，Antigravity 会报"已损坏")
-const CHECKSUMS_TO_REMOVE: &[&str] = &[
+pub(super) const CHECKSUMS_TO_REMOVE: &[&str] = &[
     "vs/code/electron-browser/workbench/workbench-jetski-agent.html",
     // 未来如果有其他需要清理的，添加到这里
 ];
 
+/// 离线渲染第三方库在面板目录下的子目录
+pub(super) const VENDOR_DIR: &str = "vendor";
+
+/// 安装时写入面板目录的补丁集清单文件名
+pub(super) const MANIFEST_FILE: &str = "manifest.json";
+
+/// 用户覆盖层在面板目录下的子目录
+const USER_OVERLAY_DIR: &str = "user";
+
+/// 侧边栏用户覆盖层文件 (样式, 脚本)
+const CASCADE_OVERLAY_FILES: [&str; 2] = ["sidebar.css", "sidebar.js"];
+
+/// Manager 用户覆盖层文件 (样式, 脚本)
+const MANAGER_OVERLAY_FILES: [&str; 2] = ["manager.css", "manager.js"];
+
 enum PatchMode {
     Install,
     Uninstall,
@@ -33,6 +69,31 @@ enum PatchMode {
 
 type PatchResult<T> = Result<T, CommandError>;
 
+/// 安装所用的补丁文件集合
+/// 默认为内置补丁，也可以来自已导入的第三方补丁包
+pub(super) struct PatchSet {
+    /// (相对 patches 目录的路径, 文件字节)
+    pub(super) files: Vec<(String, Vec<u8>)>,
+    /// 第三方补丁包清单 (内置补丁为 None)
+    pub(super) pack: Option<PackManifest>,
+}
+
+impl PatchSet {
+    /// 内置补丁
+    pub(super) fn embedded(locale: Option<&str>) -> PatchResult<Self> {
+        let files = embedded::get_all_files_runtime().map_err(|e| map_embedded_error(locale, e))?;
+        Ok(Self { files, pack: None })
+    }
+
+    /// 写入面板目录的补丁集清单
+    fn manifest(&self, filter: &dyn Fn(&str) -> bool) -> PatchManifest {
+        match &self.pack {
+            Some(pack) => pack.patch_manifest(filter),
+            None => embedded::manifest(filter),
+        }
+    }
+}
+
 impl PatchMode {
     fn as_str(&self) -> &'static str {
         match self {
@@ -51,7 +112,7 @@ fn patch_with(_locale: Option<&str>, key: &'static str, vars: &[(&str, String)])
     CommandError::key_with(key, vars)
 }
 
-fn map_embedded_error(locale: Option<&str>, err: EmbeddedError) -> CommandError {
+pub(super) fn map_embedded_error(locale: Option<&str>, err: EmbeddedError) -> CommandError {
     match err {
         EmbeddedError::PatchesDirNotFound => {
             patch_text(locale, "patchBackend.errors.patchesDirNotFound")
@@ -61,99 +122,11 @@ fn map_embedded_error(locale: Option<&str>, err: EmbeddedError) -> CommandError
             "patchBackend.errors.readPatchFileFailed",
             &[("detail", format!("{:?}: {}", path, detail))],
         ),
-    }
-}
-
-/// 侧边栏功能开关配置
-#[derive(Debug, Serialize, Deserialize)]
-#[serde(default)]
-pub struct FeatureConfig {
-    /// 是否启用侧边栏补丁 (禁用时还原所有侧边栏相关文件)
-    pub enabled: bool,
-    pub mermaid: bool,
-    pub math: bool,
-    #[serde(rename = "copyButton")]
-    pub copy_button: bool,
-    #[serde(rename = "tableColor")]
-    pub table_color: bool,
-    #[serde(rename = "fontSizeEnabled")]
-    pub font_size_enabled: bool,
-    #[serde(rename = "fontSize")]
-    pub font_size: f32,
-    // 复制按钮子选项
-    #[serde(rename = "copyButtonSmartHover")]
-    pub copy_button_smart_hover: bool,
-    #[serde(rename = "copyButtonShowBottom")]
-    pub copy_button_bottom_position: String,
-    #[serde(rename = "copyButtonStyle")]
-    pub copy_button_style: String,
-    #[serde(rename = "copyButtonCustomText")]
-    pub copy_button_custom_text: String,
-}
-
-impl Default for FeatureConfig {
-    fn default() -> Self {
-        Self {
-            enabled: true,
-            mermaid: true,
-            math: true,
-            copy_button: true,
-            table_color: true,
-            font_size_enabled: true,
-            font_size: 20.0,
-            copy_button_smart_hover: false,
-            copy_button_bottom_position: "float".to_string(),
-            copy_button_style: "arrow".to_string(),
-            copy_button_custom_text: "".to_string(),
-        }
-    }
-}
-
-/// Manager 窗口功能开关配置
-#[derive(Debug, Serialize, Deserialize)]
-#[serde(default)]
-pub struct ManagerFeatureConfig {
-    /// 是否启用 Manager 补丁 (禁用时还原所有 Manager 相关文件)
-    pub enabled: bool,
-    pub mermaid: bool,
-    pub math: bool,
-    #[serde(rename = "copyButton")]
-    pub copy_button: bool,
-    #[serde(rename = "maxWidthEnabled")]
-    pub max_width_enabled: bool,
-    #[serde(rename = "maxWidthRatio")]
-    pub max_width_ratio: f32,
-    #[serde(rename = "fontSizeEnabled")]
-    pub font_size_enabled: bool,
-    #[serde(rename = "fontSize")]
-    pub font_size: f32,
-    // 复制按钮子选项
-    #[serde(rename = "copyButtonSmartHover")]
-    pub copy_button_smart_hover: bool,
-    #[serde(rename = "copyButtonShowBottom")]
-    pub copy_button_bottom_position: String,
-    #[serde(rename = "copyButtonStyle")]
-    pub copy_button_style: String,
-    #[serde(rename = "copyButtonCustomText")]
-    pub copy_button_custom_text: String,
-}
-
-impl Default for ManagerFeatureConfig {
-    fn default() -> Self {
-        Self {
-            enabled: false,
-            mermaid: false,
-            math: false,
-            copy_button: true,
-            max_width_enabled: false,
-            max_width_ratio: 75.0,
-            font_size_enabled: false,
-            font_size: 16.0,
-            copy_button_smart_hover: false,
-            copy_button_bottom_position: "float".to_string(),
-            copy_button_style: "arrow".to_string(),
-            copy_button_custom_text: "".to_string(),
-        }
+        EmbeddedError::DecompressFailed { path, detail } => patch_with(
+            locale,
+            "patchBackend.errors.decompressPatchFileFailed",
+            &[("detail", format!("{}: {}", path, detail))],
+        ),
     }
 }
 
@@ -163,42 +136,102 @@ pub fn install_patch(
     path: String,
     features: FeatureConfig,
     manager_features: ManagerFeatureConfig,
+    pack_id: Option<String>,
+    restart_antigravity: Option<bool>,
     locale: Option<String>,
 ) -> Result<(), String> {
-    let locale_ref = locale.as_deref();
-    let antigravity_root =
-        resolve_antigravity_root(&path, locale_ref).map_err(|err| err.to_message(locale_ref))?;
-    let resources_root = paths::resources_app_root(&antigravity_root);
+    oplog::record(
+        "install",
+        Some(&path),
+        operation_config(&features, &manager_features, pack_id.as_deref()),
+        || {
+            let locale_ref = locale.as_deref();
+            // 选项与补丁包校验先于同步影子副本、解包等写入准备
+            validate_features(&features, &manager_features, locale_ref)
+                .map_err(|err| err.to_message(locale_ref))?;
+            // 未指定补丁包时使用内置补丁
+            let patch_set = match pack_id.as_deref() {
+                Some(pack_id) => packs::load_patch_set(pack_id, locale_ref),
+                None => PatchSet::embedded(locale_ref),
+            }
+            .map_err(|err| err.to_message(locale_ref))?;
+
+            let antigravity_root =
+                prepare_for_write(&path, locale_ref).map_err(|err| err.to_message(locale_ref))?;
+            let resources_root = mount_resources(&antigravity_root, locale_ref)
+                .map_err(|err| err.to_message(locale_ref))?;
+            packs::ensure_compatible(&patch_set, &resources_root, locale_ref)
+                .and_then(|()| backup_outdated_panel_configs(&resources_root, locale_ref))
+                .and_then(|()| restore::capture("install", &antigravity_root, locale_ref))
+                .map_err(|err| err.to_message(locale_ref))?;
+
+            // 运行中的 Antigravity 不会重新加载面板，按需关闭并在完成后重新启动
+            let running = process::close_for_patch(
+                &antigravity_root,
+                restart_antigravity.unwrap_or(false),
+                locale_ref,
+            )
+            .map_err(|err| err.to_message(locale_ref))?;
 
-    let result = if should_use_privileged(&resources_root) {
-        run_privileged_patch(
-            PatchMode::Install,
-            &resources_root,
-            Some(&features),
-            Some(&manager_features),
-            locale_ref,
-        )
-    } else {
-        match install_patch_internal(&resources_root, &features, &manager_features, locale_ref) {
-            Ok(()) => Ok(()),
-            Err(err) if is_permission_error(&err) => run_privileged_patch(
-                PatchMode::Install,
+            let result = install_with_patch_set(
                 &resources_root,
-                Some(&features),
-                Some(&manager_features),
+                &features,
+                &manager_features,
+                &patch_set,
                 locale_ref,
-            ),
-            Err(err) => Err(err),
-        }
-    };
+            )
+            .and_then(|()| commit_resources(&antigravity_root, locale_ref));
+            if let Some(app) = &running {
+                process::relaunch(app);
+            }
+            result.map_err(|err| err.to_message(locale_ref))
+        },
+    )
+}
 
-    result.map_err(|err| err.to_message(locale_ref))
+fn install_with_patch_set(
+    resources_root: &Path,
+    features: &FeatureConfig,
+    manager_features: &ManagerFeatureConfig,
+    patch_set: &PatchSet,
+    locale: Option<&str>,
+) -> PatchResult<()> {
+    if should_use_privileged(resources_root) {
+        return run_privileged_patch(
+            PatchMode::Install,
+            resources_root,
+            Some(features),
+            Some(manager_features),
+            Some(patch_set),
+            locale,
+        );
+    }
+
+    match install_patch_internal(
+        resources_root,
+        features,
+        manager_features,
+        patch_set,
+        locale,
+    ) {
+        Ok(()) => Ok(()),
+        Err(err) if is_permission_error(&err) => run_privileged_patch(
+            PatchMode::Install,
+            resources_root,
+            Some(features),
+            Some(manager_features),
+            Some(patch_set),
+            locale,
+        ),
+        Err(err) => Err(err),
+    }
 }
 
 fn install_patch_internal(
     resources_root: &Path,
     features: &FeatureConfig,
     manager_features: &ManagerFeatureConfig,
+    patch_set: &PatchSet,
     locale: Option<&str>,
 ) -> PatchResult<()> {
     // 侧边栏目标目录
@@ -228,6 +261,7 @@ fn install_patch_internal(
             resources_root,
             Some(features),
             Some(manager_features),
+            Some(patch_set),
             &dir,
             locale,
         );
@@ -237,7 +271,7 @@ fn install_patch_internal(
     if features.enabled {
         // 备份并安装侧边栏补丁
         backup_cascade_files(&extensions_dir, locale)?;
-        write_cascade_patches(&extensions_dir, features, locale)?;
+        write_cascade_patches(&extensions_dir, features, patch_set, locale)?;
     } else {
         // 禁用时还原侧边栏文件
         restore_cascade_files(&extensions_dir, locale)?;
@@ -247,7 +281,7 @@ fn install_patch_internal(
     if manager_features.enabled {
         // 备份并安装 Manager 补丁
         backup_manager_files(&workbench_dir, locale)?;
-        write_manager_patches(&workbench_dir, manager_features, locale)?;
+        write_manager_patches(&workbench_dir, manager_features, patch_set, locale)?;
 
         // 清理 product.json 中的 checksums (防止 Antigravity 报"已损坏")
         let product_json_path = resources_root.join("product.json");
@@ -260,37 +294,94 @@ fn install_patch_internal(
     Ok(())
 }
 
+/// 操作日志中记录的配置
+fn operation_config(
+    features: &FeatureConfig,
+    manager_features: &ManagerFeatureConfig,
+    pack_id: Option<&str>,
+) -> Option<Value> {
+    Some(serde_json::json!({
+        "features": features,
+        "managerFeatures": manager_features,
+        "packId": pack_id,
+    }))
+}
+
 /// 卸载补丁 (恢复原版)
 #[tauri::command]
-pub fn uninstall_patch(path: String, locale: Option<String>) -> Result<(), String> {
-    let locale_ref = locale.as_deref();
-    let antigravity_root =
-        resolve_antigravity_root(&path, locale_ref).map_err(|err| err.to_message(locale_ref))?;
-    let resources_root = paths::resources_app_root(&antigravity_root);
-
-    let result = if should_use_privileged(&resources_root) {
-        run_privileged_patch(
-            PatchMode::Uninstall,
-            &resources_root,
-            None,
-            None,
+pub fn uninstall_patch(
+    path: String,
+    restart_antigravity: Option<bool>,
+    locale: Option<String>,
+) -> Result<(), String> {
+    oplog::record("uninstall", Some(&path), None, || {
+        let locale_ref = locale.as_deref();
+        let antigravity_root =
+            prepare_for_write(&path, locale_ref).map_err(|err| err.to_message(locale_ref))?;
+        let resources_root = mount_resources(&antigravity_root, locale_ref)
+            .map_err(|err| err.to_message(locale_ref))?;
+        restore::capture("uninstall", &antigravity_root, locale_ref)
+            .map_err(|err| err.to_message(locale_ref))?;
+        let running = process::close_for_patch(
+            &antigravity_root,
+            restart_antigravity.unwrap_or(false),
             locale_ref,
         )
-    } else {
-        match uninstall_patch_internal(&resources_root, locale_ref) {
-            Ok(()) => Ok(()),
-            Err(err) if is_permission_error(&err) => run_privileged_patch(
-                PatchMode::Uninstall,
-                &resources_root,
-                None,
-                None,
-                locale_ref,
-            ),
+        .map_err(|err| err.to_message(locale_ref))?;
+
+        // AppImage, 影子副本与 app.asar 布局直接恢复原始状态，没有备份时按普通方式卸载后写回
+        let result = match restore_original(&antigravity_root, locale_ref) {
+            Ok(true) => Ok(()),
+            Ok(false) => uninstall_resources(&resources_root, locale_ref)
+                .and_then(|()| commit_resources(&antigravity_root, locale_ref)),
             Err(err) => Err(err),
+        };
+
+        if let Some(app) = &running {
+            process::relaunch(app);
         }
-    };
+        result.map_err(|err| err.to_message(locale_ref))
+    })
+}
 
-    result.map_err(|err| err.to_message(locale_ref))
+/// AppImage 恢复原文件，影子副本直接删除，app.asar 布局恢复备份归档，普通安装返回 false
+fn restore_original(antigravity_root: &Path, locale: Option<&str>) -> PatchResult<bool> {
+    if appimage::restore_original(antigravity_root).map_err(|e| appimage_error(locale, e))? {
+        return Ok(true);
+    }
+    if shadow::remove(antigravity_root).map_err(|e| shadow_error(locale, e))? {
+        return Ok(true);
+    }
+    match asar::restore_ops(antigravity_root).map_err(|e| asar_error(locale, e))? {
+        Some(ops) => apply_file_ops(antigravity_root, &ops, locale).map(|()| true),
+        None => Ok(false),
+    }
+}
+
+fn uninstall_resources(resources_root: &Path, locale: Option<&str>) -> PatchResult<()> {
+    if should_use_privileged(resources_root) {
+        return run_privileged_patch(
+            PatchMode::Uninstall,
+            resources_root,
+            None,
+            None,
+            None,
+            locale,
+        );
+    }
+
+    match uninstall_patch_internal(resources_root, locale) {
+        Ok(()) => Ok(()),
+        Err(err) if is_permission_error(&err) => run_privileged_patch(
+            PatchMode::Uninstall,
+            resources_root,
+            None,
+            None,
+            None,
+            locale,
+        ),
+        Err(err) => Err(err),
+    }
 }
 
 fn uninstall_patch_internal(resources_root: &Path, locale: Option<&str>) -> PatchResult<()> {
@@ -313,6 +404,7 @@ fn uninstall_patch_internal(resources_root: &Path, locale: Option<&str>) -> Patc
             resources_root,
             None,
             None,
+            None,
             &dir,
             locale,
         );
@@ -324,42 +416,95 @@ fn uninstall_patch_internal(resources_root: &Path, locale: Option<&str>) -> Patc
     Ok(())
 }
 
+/// 配置更新结果
+#[derive(Debug, Serialize)]
+pub struct ConfigUpdate {
+    /// 已在运行中热重载新配置的面板 (调试端口不可用时为空)
+    #[serde(rename = "liveReloaded")]
+    pub live_reloaded: Vec<&'static str>,
+}
+
 /// 仅更新配置文件 (不重新复制补丁文件)
+/// 写入后若能连接调试端口，会在运行中的面板里热重载新配置
 #[tauri::command]
 pub fn update_config(
     path: String,
     features: FeatureConfig,
     manager_features: ManagerFeatureConfig,
     locale: Option<String>,
-) -> Result<(), String> {
-    let locale_ref = locale.as_deref();
-    let antigravity_root =
-        resolve_antigravity_root(&path, locale_ref).map_err(|err| err.to_message(locale_ref))?;
-    let resources_root = paths::resources_app_root(&antigravity_root);
-
-    let result = if should_use_privileged(&resources_root) {
-        run_privileged_patch(
-            PatchMode::UpdateConfig,
-            &resources_root,
-            Some(&features),
-            Some(&manager_features),
-            locale_ref,
-        )
-    } else {
-        match update_config_internal(&resources_root, &features, &manager_features, locale_ref) {
-            Ok(()) => Ok(()),
-            Err(err) if is_permission_error(&err) => run_privileged_patch(
-                PatchMode::UpdateConfig,
-                &resources_root,
-                Some(&features),
-                Some(&manager_features),
-                locale_ref,
-            ),
-            Err(err) => Err(err),
-        }
-    };
-
-    result.map_err(|err| err.to_message(locale_ref))
+) -> Result<ConfigUpdate, String> {
+    oplog::record(
+        "updateConfig",
+        Some(&path),
+        operation_config(&features, &manager_features, None),
+        || {
+            let locale_ref = locale.as_deref();
+            validate_features(&features, &manager_features, locale_ref)
+                .map_err(|err| err.to_message(locale_ref))?;
+            let antigravity_root =
+                prepare_for_write(&path, locale_ref).map_err(|err| err.to_message(locale_ref))?;
+            let resources_root = mount_resources(&antigravity_root, locale_ref)
+                .map_err(|err| err.to_message(locale_ref))?;
+
+            // 启用的功能模块变化时需要部署或移除面板文件，按已安装的补丁集重新安装
+            // 补丁集在备份配置与创建还原点之前加载，校验失败时不留下任何改动
+            let patch_set =
+                modules_changed(&resources_root, &features, &manager_features, locale_ref)
+                    .and_then(|changed| {
+                        changed
+                            .then(|| installed_patch_set(&resources_root, locale_ref))
+                            .transpose()
+                    })
+                    .map_err(|err| err.to_message(locale_ref))?;
+            backup_outdated_panel_configs(&resources_root, locale_ref)
+                .and_then(|()| restore::capture("updateConfig", &antigravity_root, locale_ref))
+                .map_err(|err| err.to_message(locale_ref))?;
+
+            let result = if let Some(patch_set) = &patch_set {
+                install_with_patch_set(
+                    &resources_root,
+                    &features,
+                    &manager_features,
+                    patch_set,
+                    locale_ref,
+                )
+            } else if should_use_privileged(&resources_root) {
+                run_privileged_patch(
+                    PatchMode::UpdateConfig,
+                    &resources_root,
+                    Some(&features),
+                    Some(&manager_features),
+                    None,
+                    locale_ref,
+                )
+            } else {
+                match update_config_internal(
+                    &resources_root,
+                    &features,
+                    &manager_features,
+                    locale_ref,
+                ) {
+                    Ok(()) => Ok(()),
+                    Err(err) if is_permission_error(&err) => run_privileged_patch(
+                        PatchMode::UpdateConfig,
+                        &resources_root,
+                        Some(&features),
+                        Some(&manager_features),
+                        None,
+                        locale_ref,
+                    ),
+                    Err(err) => Err(err),
+                }
+            };
+            result
+                .and_then(|()| commit_resources(&antigravity_root, locale_ref))
+                .map_err(|err| err.to_message(locale_ref))?;
+
+            Ok(ConfigUpdate {
+                live_reloaded: live_reload::reload_panels(&antigravity_root),
+            })
+        },
+    )
 }
 
 fn update_config_internal(
@@ -418,6 +563,7 @@ fn update_config_internal(
                 resources_root,
                 Some(features),
                 Some(manager_features),
+                None,
                 &dir,
                 locale,
             );
@@ -425,6 +571,12 @@ fn update_config_internal(
     }
 
     write_config_file(&cascade_config_path, features, locale)?;
+    if let Some(cascade_panel_dir) = cascade_config_path.parent() {
+        if features.offline_rendering(locale)? && !cascade_panel_dir.join(VENDOR_DIR).exists() {
+            write_vendor_files(cascade_panel_dir, locale)?;
+        }
+        write_overlay_files(cascade_panel_dir, &CASCADE_OVERLAY_FILES, locale)?;
+    }
 
     if manager_config_path
         .parent()
@@ -432,27 +584,216 @@ fn update_config_internal(
         .unwrap_or(false)
     {
         write_manager_config_file(&manager_config_path, manager_features, locale)?;
+        if let Some(manager_panel_dir) = manager_config_path.parent() {
+            if manager_features.offline_rendering(locale)?
+                && !manager_panel_dir.join(VENDOR_DIR).exists()
+            {
+                write_vendor_files(manager_panel_dir, locale)?;
+            }
+            write_overlay_files(manager_panel_dir, &MANAGER_OVERLAY_FILES, locale)?;
+        }
     }
 
     Ok(())
 }
 
-/// 检测补丁是否已安装
+/// 检查已安装面板的模块文件是否与新配置一致
+fn modules_changed(
+    resources_root: &Path,
+    features: &FeatureConfig,
+    manager_features: &ManagerFeatureConfig,
+    locale: Option<&str>,
+) -> PatchResult<bool> {
+    let cascade_panel_dir = resources_root
+        .join("extensions")
+        .join("antigravity")
+        .join("cascade-panel");
+    let manager_panel_dir = resources_root
+        .join("out")
+        .join("vs")
+        .join("code")
+        .join("electron-browser")
+        .join("workbench")
+        .join("manager-panel");
+
+    if features.enabled
+        && modules::needs_redeploy(
+            &cascade_panel_dir,
+            Panel::Cascade,
+            &features.config_options(locale)?,
+            locale,
+        )?
+    {
+        return Ok(true);
+    }
+    if manager_features.enabled
+        && modules::needs_redeploy(
+            &manager_panel_dir,
+            Panel::Manager,
+            &manager_features.config_options(locale)?,
+            locale,
+        )?
+    {
+        return Ok(true);
+    }
+    Ok(false)
+}
+
+/// 根据已安装的清单确定补丁来源：已安装第三方补丁包时重新加载该补丁包，否则使用内置补丁
+fn installed_patch_set(resources_root: &Path, locale: Option<&str>) -> PatchResult<PatchSet> {
+    let patch_set = match installed_pack_id(&Resources::Dir(resources_root.to_path_buf())) {
+        Some(pack_id) => packs::load_patch_set(&pack_id, locale),
+        None => PatchSet::embedded(locale),
+    }?;
+    packs::ensure_compatible(&patch_set, resources_root, locale)?;
+    Ok(patch_set)
+}
+
+/// 已安装的第三方补丁包 ID
+fn installed_pack_id(resources: &Resources) -> Option<String> {
+    let (cascade_manifest, manager_manifest) = read_installed_manifests(resources);
+    cascade_manifest
+        .into_iter()
+        .chain(manager_manifest)
+        .find_map(|manifest| manifest.pack)
+}
+
+/// 补丁操作使用的 resources/app 目录，app.asar 布局时重新解包
+fn mount_resources(antigravity_root: &Path, locale: Option<&str>) -> PatchResult<PathBuf> {
+    asar::mount(antigravity_root, true).map_err(|e| asar_error(locale, e))
+}
+
+/// 将 app.asar 布局的改动写回归档，AppImage 解包目录再写回 AppImage，影子副本生成启动项
+fn commit_resources(antigravity_root: &Path, locale: Option<&str>) -> PatchResult<()> {
+    commit_asar(antigravity_root, locale)?;
+    appimage::commit(antigravity_root).map_err(|e| appimage_error(locale, e))?;
+    shadow::commit(antigravity_root).map_err(|e| shadow_error(locale, e))
+}
+
+/// 将 app.asar 布局的改动写回归档，安装目录不可写时提权
+pub(super) fn commit_asar(antigravity_root: &Path, locale: Option<&str>) -> PatchResult<()> {
+    let ops = asar::commit_ops(antigravity_root).map_err(|e| asar_error(locale, e))?;
+    let result = apply_file_ops(antigravity_root, &ops, locale);
+    asar::finish_commit(antigravity_root, result.is_ok()).map_err(|e| asar_error(locale, e))?;
+    result
+}
+
+/// 执行写入安装目录的文件操作，目标目录需要提权或不可写时通过提权脚本执行
+/// 提权脚本只接受 antigravity_root 安装内的目标
+pub(super) fn apply_file_ops(
+    antigravity_root: &Path,
+    ops: &[FileOp],
+    locale: Option<&str>,
+) -> PatchResult<()> {
+    // 尚未创建的目录检查最近的已有上级目录
+    let mut dirs: Vec<&Path> = ops
+        .iter()
+        .filter_map(|op| op.target().parent())
+        .filter_map(|dir| dir.ancestors().find(|dir| dir.exists()))
+        .collect();
+    dirs.sort();
+    dirs.dedup();
+    let unwritable = match dirs.iter().find(|dir| should_use_privileged(dir)) {
+        Some(dir) => Some(dir.to_path_buf()),
+        None => first_unwritable_dir(&dirs, locale)?,
+    };
+    if let Some(dir) = unwritable {
+        return run_privileged_file_ops(antigravity_root, ops, &dir, locale);
+    }
+
+    for op in ops {
+        op.apply().map_err(|e| {
+            patch_with(
+                locale,
+                "patchBackend.errors.writeFileFailed",
+                &[("detail", format!("{:?}: {}", op.target(), e))],
+            )
+        })?;
+        log_file_op(op);
+    }
+    Ok(())
+}
+
+fn log_file_op(op: &FileOp) {
+    let action = match op {
+        FileOp::Write { .. } | FileOp::Copy { .. } => "write",
+        FileOp::Remove(_) | FileOp::RemoveDir(_) => "delete",
+    };
+    oplog::file(action, op.target());
+}
+
+fn asar_error(locale: Option<&str>, e: std::io::Error) -> CommandError {
+    patch_with(
+        locale,
+        "patchBackend.errors.asarFailed",
+        &[("detail", e.to_string())],
+    )
+}
+
+fn appimage_error(locale: Option<&str>, e: std::io::Error) -> CommandError {
+    if let Some(replaced) = e
+        .get_ref()
+        .and_then(|inner| inner.downcast_ref::<appimage::Replaced>())
+    {
+        return patch_with(
+            locale,
+            "patchBackend.errors.appImageReplaced",
+            &[("backup", replaced.backup.display().to_string())],
+        );
+    }
+    patch_with(
+        locale,
+        "patchBackend.errors.appImageFailed",
+        &[("detail", e.to_string())],
+    )
+}
+
+fn shadow_error(locale: Option<&str>, e: std::io::Error) -> CommandError {
+    patch_with(
+        locale,
+        "patchBackend.errors.shadowFailed",
+        &[("detail", e.to_string())],
+    )
+}
+
+/// 补丁安装状态
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PatchStatus {
+    /// 侧边栏配置文件存在即视为已安装
+    pub installed: bool,
+    /// 已安装的补丁集版本号 (未安装或旧版本安装没有清单时为 None)
+    pub version: Option<String>,
+    /// 已安装补丁集的整体摘要，区分同一版本号的不同构建
+    pub build: Option<String>,
+    /// 已安装的第三方补丁包 ID
+    pub pack: Option<String>,
+    /// 已安装的内置补丁与当前补丁程序的构建不同，重新安装后更新
+    pub outdated: bool,
+}
+
+/// 检测补丁是否已安装，并读取已安装补丁集的版本与构建
 #[tauri::command]
-pub fn check_patch_status(path: String, locale: Option<String>) -> Result<bool, String> {
+pub fn check_patch_status(path: String, locale: Option<String>) -> Result<PatchStatus, String> {
     let locale_ref = locale.as_deref();
     let antigravity_root =
         resolve_antigravity_root(&path, locale_ref).map_err(|err| err.to_message(locale_ref))?;
-    let resources_root = paths::resources_app_root(&antigravity_root);
+    let resources = Resources::open(&antigravity_root);
 
-    let config_path = resources_root
-        .join("extensions")
-        .join("antigravity")
-        .join("cascade-panel")
-        .join("config.json");
+    let installed = resources.exists(&Panel::Cascade.relative_path("config.json"));
 
-    // 如果 config.json 存在, 则认为补丁已安装
-    Ok(config_path.exists())
+    let (cascade_manifest, manager_manifest) = read_installed_manifests(&resources);
+    let manifest = cascade_manifest.or(manager_manifest).filter(|_| installed);
+    let outdated = manifest.as_ref().is_some_and(|manifest| {
+        manifest.pack.is_none() && manifest.digest != embedded::PATCH_SET_DIGEST
+    });
+    Ok(PatchStatus {
+        installed,
+        version: manifest.as_ref().map(|manifest| manifest.version.clone()),
+        build: manifest.as_ref().map(|manifest| manifest.digest.clone()),
+        pack: manifest.and_then(|manifest| manifest.pack),
+        outdated,
+    })
 }
 
 /// 读取已安装的补丁配置
@@ -464,37 +805,40 @@ pub fn read_patch_config(
     let locale_ref = locale.as_deref();
     let antigravity_root =
         resolve_antigravity_root(&path, locale_ref).map_err(|err| err.to_message(locale_ref))?;
-    let resources_root = paths::resources_app_root(&antigravity_root);
+    read_cascade_config(&Resources::open(&antigravity_root), locale_ref)
+        .map_err(|err| err.to_message(locale_ref))
+}
 
-    let config_path = resources_root
-        .join("extensions")
-        .join("antigravity")
-        .join("cascade-panel")
-        .join("config.json");
+fn read_cascade_config(
+    resources: &Resources,
+    locale: Option<&str>,
+) -> PatchResult<Option<FeatureConfig>> {
+    let config_path = Panel::Cascade.relative_path("config.json");
 
-    if !config_path.exists() {
+    if !resources.exists(&config_path) {
         return Ok(None);
     }
 
-    let content = fs::read_to_string(&config_path)
-        .map_err(|e| {
-            patch_with(
-                locale_ref,
-                "patchBackend.errors.readConfigFailed",
-                &[("detail", e.to_string())],
-            )
-        })
-        .map_err(|err| err.to_message(locale_ref))?;
+    let content = resources.read_to_string(&config_path).map_err(|e| {
+        patch_with(
+            locale,
+            "patchBackend.errors.readConfigFailed",
+            &[("detail", e.to_string())],
+        )
+    })?;
 
-    let config: FeatureConfig = serde_json::from_str(&content)
-        .map_err(|e| {
-            patch_with(
-                locale_ref,
-                "patchBackend.errors.parseConfigFailed",
-                &[("detail", e.to_string())],
-            )
-        })
-        .map_err(|err| err.to_message(locale_ref))?;
+    let parse_error = |detail: String| {
+        patch_with(
+            locale,
+            "patchBackend.errors.parseConfigFailed",
+            &[("detail", detail)],
+        )
+    };
+    let mut value = serde_json::from_str(&content).map_err(|e| parse_error(e.to_string()))?;
+    migrations::migrate_panel_config(&mut value).map_err(parse_error)?;
+    let mut config: FeatureConfig =
+        serde_json::from_value(value).map_err(|e| parse_error(e.to_string()))?;
+    config.options = installed_options(Panel::Cascade, &config.options, locale)?;
 
     Ok(Some(config))
 }
@@ -508,44 +852,98 @@ pub fn read_manager_patch_config(
     let locale_ref = locale.as_deref();
     let antigravity_root =
         resolve_antigravity_root(&path, locale_ref).map_err(|err| err.to_message(locale_ref))?;
-    let resources_root = paths::resources_app_root(&antigravity_root);
+    read_manager_config(&Resources::open(&antigravity_root), locale_ref)
+        .map_err(|err| err.to_message(locale_ref))
+}
 
-    let config_path = resources_root
-        .join("out")
-        .join("vs")
-        .join("code")
-        .join("electron-browser")
-        .join("workbench")
-        .join("manager-panel")
-        .join("config.json");
+fn read_manager_config(
+    resources: &Resources,
+    locale: Option<&str>,
+) -> PatchResult<Option<ManagerFeatureConfig>> {
+    let config_path = Panel::Manager.relative_path("config.json");
 
-    if !config_path.exists() {
+    if !resources.exists(&config_path) {
         return Ok(None);
     }
 
-    let content = fs::read_to_string(&config_path)
-        .map_err(|e| {
-            patch_with(
-                locale_ref,
-                "patchBackend.errors.readManagerConfigFailed",
-                &[("detail", e.to_string())],
-            )
-        })
-        .map_err(|err| err.to_message(locale_ref))?;
+    let content = resources.read_to_string(&config_path).map_err(|e| {
+        patch_with(
+            locale,
+            "patchBackend.errors.readManagerConfigFailed",
+            &[("detail", e.to_string())],
+        )
+    })?;
 
-    let config: ManagerFeatureConfig = serde_json::from_str(&content)
-        .map_err(|e| {
-            patch_with(
-                locale_ref,
-                "patchBackend.errors.parseManagerConfigFailed",
-                &[("detail", e.to_string())],
-            )
-        })
-        .map_err(|err| err.to_message(locale_ref))?;
+    let parse_error = |detail: String| {
+        patch_with(
+            locale,
+            "patchBackend.errors.parseManagerConfigFailed",
+            &[("detail", detail)],
+        )
+    };
+    let mut value = serde_json::from_str(&content).map_err(|e| parse_error(e.to_string()))?;
+    migrations::migrate_panel_config(&mut value).map_err(parse_error)?;
+    let mut config: ManagerFeatureConfig =
+        serde_json::from_value(value).map_err(|e| parse_error(e.to_string()))?;
+    config.options = installed_options(Panel::Manager, &config.options, locale)?;
 
     Ok(Some(config))
 }
 
+/// 读取安装目录中侧边栏与 Manager 的补丁集清单 (旧版本安装或未安装时为 None)
+fn read_installed_manifests(
+    resources: &Resources,
+) -> (Option<PatchManifest>, Option<PatchManifest>) {
+    (
+        read_manifest_file(resources, Panel::Cascade),
+        read_manifest_file(resources, Panel::Manager),
+    )
+}
+
+/// 读取面板的清单文件，不存在或无法解析时返回 None
+fn read_manifest_file(resources: &Resources, panel: Panel) -> Option<PatchManifest> {
+    let content = resources.read(&panel.relative_path(MANIFEST_FILE)).ok()?;
+    serde_json::from_slice(&content).ok()
+}
+
+/// 判断补丁文件是否属于侧边栏
+pub(super) fn is_cascade_file(relative_path: &str) -> bool {
+    relative_path == "cascade-panel.html" || relative_path.starts_with("cascade-panel/")
+}
+
+/// 判断补丁文件是否属于 Manager
+pub(super) fn is_manager_file(relative_path: &str) -> bool {
+    relative_path == "workbench-jetski-agent.html" || relative_path.starts_with("manager-panel/")
+}
+
+/// 写入面板目录的补丁集清单
+fn write_panel_manifest(
+    panel_dir: &Path,
+    patch_set: &PatchSet,
+    filter: &dyn Fn(&str) -> bool,
+    locale: Option<&str>,
+) -> PatchResult<()> {
+    let manifest_path = panel_dir.join(MANIFEST_FILE);
+    let content = serde_json::to_string_pretty(&patch_set.manifest(filter)).map_err(|e| {
+        patch_with(
+            locale,
+            "patchBackend.errors.writeManifestFailed",
+            &[("detail", e.to_string())],
+        )
+    })?;
+
+    fs::write(&manifest_path, content).map_err(|e| {
+        patch_with(
+            locale,
+            "patchBackend.errors.writeManifestFailed",
+            &[("detail", format!("{:?}: {}", manifest_path, e))],
+        )
+    })?;
+    oplog::file("write", &manifest_path);
+
+    Ok(())
+}
+
 /// 备份侧边栏相关文件
 fn backup_cascade_files(extensions_dir: &Path, locale: Option<&str>) -> PatchResult<()> {
     let cascade_panel = extensions_dir.join("cascade-panel.html");
@@ -558,6 +956,7 @@ fn backup_cascade_files(extensions_dir: &Path, locale: Option<&str>) -> PatchRes
                 &[("detail", e.to_string())],
             )
         })?;
+        oplog::file("backup", &cascade_backup);
     }
     Ok(())
 }
@@ -574,6 +973,7 @@ fn backup_manager_files(workbench_dir: &Path, locale: Option<&str>) -> PatchResu
                 &[("detail", e.to_string())],
             )
         })?;
+        oplog::file("backup", &jetski_backup);
     }
     Ok(())
 }
@@ -582,6 +982,7 @@ fn backup_manager_files(workbench_dir: &Path, locale: Option<&str>) -> PatchResu
 fn write_cascade_patches(
     extensions_dir: &Path,
     features: &FeatureConfig,
+    patch_set: &PatchSet,
     locale: Option<&str>,
 ) -> PatchResult<()> {
     let cascade_panel_dir = extensions_dir.join("cascade-panel");
@@ -595,6 +996,7 @@ fn write_cascade_patches(
                 &[("detail", e.to_string())],
             )
         })?;
+        oplog::file("delete", &cascade_panel_dir);
     }
 
     // 创建目录
@@ -606,16 +1008,22 @@ fn write_cascade_patches(
         )
     })?;
 
+    // 未启用的功能模块不部署
+    let excluded = modules::excluded_files(
+        &patch_set.files,
+        Panel::Cascade,
+        &features.config_options(locale)?,
+        locale,
+    )?;
+
     // 写入侧边栏相关补丁文件
-    let patch_files =
-        embedded::get_all_files_runtime().map_err(|e| map_embedded_error(locale, e))?;
-    for (relative_path, content) in patch_files {
+    for (relative_path, content) in &patch_set.files {
         // 只处理侧边栏相关文件
-        if relative_path != "cascade-panel.html" && !relative_path.starts_with("cascade-panel/") {
+        if !is_cascade_file(relative_path) || excluded.contains(relative_path) {
             continue;
         }
 
-        let full_path = extensions_dir.join(&relative_path);
+        let full_path = extensions_dir.join(relative_path);
 
         // 确保父目录存在
         if let Some(parent) = full_path.parent() {
@@ -637,8 +1045,25 @@ fn write_cascade_patches(
                 &[("detail", format!("{:?}: {}", full_path, e))],
             )
         })?;
+        oplog::file("write", &full_path);
     }
 
+    // 离线渲染：部署内置的第三方库
+    if features.offline_rendering(locale)? {
+        write_vendor_files(&cascade_panel_dir, locale)?;
+    }
+
+    // 部署用户覆盖层
+    write_overlay_files(&cascade_panel_dir, &CASCADE_OVERLAY_FILES, locale)?;
+
+    // 记录所安装的补丁集版本
+    write_panel_manifest(
+        &cascade_panel_dir,
+        patch_set,
+        &|path| is_cascade_file(path) && !excluded.contains(path),
+        locale,
+    )?;
+
     // 生成侧边栏配置文件
     let cascade_config_path = cascade_panel_dir.join("config.json");
     write_config_file(&cascade_config_path, features, locale)?;
@@ -650,6 +1075,7 @@ fn write_cascade_patches(
 fn write_manager_patches(
     workbench_dir: &Path,
     manager_features: &ManagerFeatureConfig,
+    patch_set: &PatchSet,
     locale: Option<&str>,
 ) -> PatchResult<()> {
     let manager_panel_dir = workbench_dir.join("manager-panel");
@@ -663,6 +1089,7 @@ fn write_manager_patches(
                 &[("detail", e.to_string())],
             )
         })?;
+        oplog::file("delete", &manager_panel_dir);
     }
 
     // 创建目录
@@ -674,18 +1101,22 @@ fn write_manager_patches(
         )
     })?;
 
+    // 未启用的功能模块不部署
+    let excluded = modules::excluded_files(
+        &patch_set.files,
+        Panel::Manager,
+        &manager_features.config_options(locale)?,
+        locale,
+    )?;
+
     // 写入 Manager 相关补丁文件
-    let patch_files =
-        embedded::get_all_files_runtime().map_err(|e| map_embedded_error(locale, e))?;
-    for (relative_path, content) in patch_files {
+    for (relative_path, content) in &patch_set.files {
         // 只处理 Manager 相关文件
-        if relative_path != "workbench-jetski-agent.html"
-            && !relative_path.starts_with("manager-panel/")
-        {
+        if !is_manager_file(relative_path) || excluded.contains(relative_path) {
             continue;
         }
 
-        let full_path = workbench_dir.join(&relative_path);
+        let full_path = workbench_dir.join(relative_path);
 
         // 确保父目录存在
         if let Some(parent) = full_path.parent() {
@@ -707,8 +1138,25 @@ fn write_manager_patches(
                 &[("detail", format!("{:?}: {}", full_path, e))],
             )
         })?;
+        oplog::file("write", &full_path);
+    }
+
+    // 离线渲染：部署内置的第三方库
+    if manager_features.offline_rendering(locale)? {
+        write_vendor_files(&manager_panel_dir, locale)?;
     }
 
+    // 部署用户覆盖层
+    write_overlay_files(&manager_panel_dir, &MANAGER_OVERLAY_FILES, locale)?;
+
+    // 记录所安装的补丁集版本
+    write_panel_manifest(
+        &manager_panel_dir,
+        patch_set,
+        &|path| is_manager_file(path) && !excluded.contains(path),
+        locale,
+    )?;
+
     // 生成 Manager 配置文件
     let manager_config_path = manager_panel_dir.join("config.json");
     write_manager_config_file(&manager_config_path, manager_features, locale)?;
@@ -722,18 +1170,12 @@ fn write_config_file(
     features: &FeatureConfig,
     locale: Option<&str>,
 ) -> PatchResult<()> {
-    let config_content = serde_json::json!({
-        "mermaid": features.mermaid,
-        "math": features.math,
-        "copyButton": features.copy_button,
-        "tableColor": features.table_color,
-        "fontSizeEnabled": features.font_size_enabled,
-        "fontSize": features.font_size,
-        "copyButtonSmartHover": features.copy_button_smart_hover,
-        "copyButtonShowBottom": features.copy_button_bottom_position,
-        "copyButtonStyle": features.copy_button_style,
-        "copyButtonCustomText": features.copy_button_custom_text
-    });
+    let mut config_content = Value::Object(features.config_options(locale)?);
+    if features.offline_rendering(locale)? {
+        apply_offline_urls(&mut config_content, "cascade-panel");
+    }
+    apply_overlay_flags(&mut config_content, &CASCADE_OVERLAY_FILES);
+    migrations::stamp_panel_config(&mut config_content);
 
     let content = serde_json::to_string_pretty(&config_content).map_err(|e| {
         patch_with(
@@ -743,13 +1185,14 @@ fn write_config_file(
         )
     })?;
 
-    fs::write(config_path, content).map_err(|e| {
+    write_atomic(config_path, content).map_err(|e| {
         patch_with(
             locale,
             "patchBackend.errors.writeConfigFailed",
             &[("detail", e.to_string())],
         )
     })?;
+    oplog::file("write", config_path);
 
     Ok(())
 }
@@ -760,19 +1203,12 @@ fn write_manager_config_file(
     features: &ManagerFeatureConfig,
     locale: Option<&str>,
 ) -> PatchResult<()> {
-    let config_content = serde_json::json!({
-        "mermaid": features.mermaid,
-        "math": features.math,
-        "copyButton": features.copy_button,
-        "maxWidthEnabled": features.max_width_enabled,
-        "maxWidthRatio": features.max_width_ratio,
-        "fontSizeEnabled": features.font_size_enabled,
-        "fontSize": features.font_size,
-        "copyButtonSmartHover": features.copy_button_smart_hover,
-        "copyButtonShowBottom": features.copy_button_bottom_position,
-        "copyButtonStyle": features.copy_button_style,
-        "copyButtonCustomText": features.copy_button_custom_text
-    });
+    let mut config_content = Value::Object(features.config_options(locale)?);
+    if features.offline_rendering(locale)? {
+        apply_offline_urls(&mut config_content, "manager-panel");
+    }
+    apply_overlay_flags(&mut config_content, &MANAGER_OVERLAY_FILES);
+    migrations::stamp_panel_config(&mut config_content);
 
     let content = serde_json::to_string_pretty(&config_content).map_err(|e| {
         patch_with(
@@ -782,13 +1218,123 @@ fn write_manager_config_file(
         )
     })?;
 
-    fs::write(config_path, content).map_err(|e| {
+    write_atomic(config_path, content).map_err(|e| {
         patch_with(
             locale,
             "patchBackend.errors.writeManagerConfigFailed",
             &[("detail", e.to_string())],
         )
     })?;
+    oplog::file("write", config_path);
+
+    Ok(())
+}
+
+/// 写入离线渲染的本地资源地址
+/// 地址相对于面板入口 HTML，由面板脚本在加载第三方库前读取
+fn apply_offline_urls(config_content: &mut Value, panel_dir_name: &str) {
+    let base = format!("./{}/{}", panel_dir_name, VENDOR_DIR);
+    config_content["katexBaseUrl"] = Value::String(format!("{}/katex", base));
+    config_content["mathjaxUrl"] = Value::String(format!("{}/mathjax/tex-mml-chtml.js", base));
+    config_content["mermaidUrl"] = Value::String(format!("{}/mermaid/mermaid.min.js", base));
+}
+
+/// 写入用户覆盖层是否存在，由面板脚本决定是否加载
+fn apply_overlay_flags(config_content: &mut Value, overlay_files: &[&str; 2]) {
+    let overlay_dir = config::get_overlay_dir();
+    let [css_file, js_file] = overlay_files;
+    config_content["userCss"] = Value::Bool(overlay_dir.join(css_file).is_file());
+    config_content["userJs"] = Value::Bool(overlay_dir.join(js_file).is_file());
+}
+
+/// 部署用户覆盖层到面板目录的 user/ 下
+/// 每次都重新复制，已从覆盖层目录删除的文件同时从安装目录移除
+fn write_overlay_files(
+    panel_dir: &Path,
+    overlay_files: &[&str; 2],
+    locale: Option<&str>,
+) -> PatchResult<()> {
+    let target_dir = panel_dir.join(USER_OVERLAY_DIR);
+    if target_dir.exists() {
+        fs::remove_dir_all(&target_dir).map_err(|e| {
+            patch_with(
+                locale,
+                "patchBackend.errors.writeOverlayFailed",
+                &[("detail", format!("{:?}: {}", target_dir, e))],
+            )
+        })?;
+        oplog::file("delete", &target_dir);
+    }
+
+    let overlay_dir = config::get_overlay_dir();
+    for file_name in overlay_files {
+        let source = overlay_dir.join(file_name);
+        if !source.is_file() {
+            continue;
+        }
+
+        fs::create_dir_all(&target_dir).map_err(|e| {
+            patch_with(
+                locale,
+                "patchBackend.errors.createDirFailed",
+                &[("detail", e.to_string())],
+            )
+        })?;
+
+        let target = target_dir.join(file_name);
+        fs::copy(&source, &target).map_err(|e| {
+            patch_with(
+                locale,
+                "patchBackend.errors.writeOverlayFailed",
+                &[("detail", format!("{:?}: {}", source, e))],
+            )
+        })?;
+        oplog::file("copy", &target);
+    }
+
+    Ok(())
+}
+
+/// 覆盖旧版本的面板 config.json 前先备份
+fn backup_outdated_panel_configs(resources_root: &Path, locale: Option<&str>) -> PatchResult<()> {
+    migrations::backup_outdated_panel_configs(resources_root).map_err(|e| {
+        patch_with(
+            locale,
+            "patchBackend.errors.backupConfigFailed",
+            &[("detail", e.to_string())],
+        )
+    })
+}
+
+/// 部署离线渲染第三方库到面板目录的 vendor/ 下
+fn write_vendor_files(panel_dir: &Path, locale: Option<&str>) -> PatchResult<()> {
+    let vendor_files =
+        embedded::get_vendor_files_runtime().map_err(|e| map_embedded_error(locale, e))?;
+    for (relative_path, content) in vendor_files {
+        let full_path = panel_dir.join(&relative_path);
+
+        // 确保父目录存在
+        if let Some(parent) = full_path.parent() {
+            if !parent.exists() {
+                fs::create_dir_all(parent).map_err(|e| {
+                    patch_with(
+                        locale,
+                        "patchBackend.errors.createDirFailed",
+                        &[("detail", e.to_string())],
+                    )
+                })?;
+            }
+        }
+
+        fs::write(&full_path, content).map_err(|e| {
+            patch_with(
+                locale,
+                "patchBackend.errors.writeFileFailed",
+                &[("detail", format!("{:?}: {}", full_path, e))],
+            )
+        })?;
+        oplog::file("write", &full_path);
+    }
 
     Ok(())
 }
@@ -806,6 +1352,7 @@ fn restore_cascade_files(extensions_dir: &Path, locale: Option<&str>) -> PatchRe
                 &[("detail", e.to_string())],
             )
         })?;
+        oplog::file("restore", &cascade_panel);
     }
 
     // 删除侧边栏补丁目录
@@ -818,6 +1365,7 @@ fn restore_cascade_files(extensions_dir: &Path, locale: Option<&str>) -> PatchRe
                 &[("detail", e.to_string())],
             )
         })?;
+        oplog::file("delete", &cascade_dir);
     }
 
     Ok(())
@@ -836,6 +1384,7 @@ fn restore_manager_files(workbench_dir: &Path, locale: Option<&str>) -> PatchRes
                 &[("detail", e.to_string())],
             )
         })?;
+        oplog::file("restore", &jetski_agent);
     }
 
     // 删除 Manager 补丁目录
@@ -848,6 +1397,7 @@ fn restore_manager_files(workbench_dir: &Path, locale: Option<&str>) -> PatchRes
                 &[("detail", e.to_string())],
             )
         })?;
+        oplog::file("delete", &manager_dir);
     }
 
     Ok(())
@@ -918,6 +1468,7 @@ fn clean_checksums(product_json_path: &Path, locale: Option<&str>) -> PatchResul
                         &[("detail", e.to_string())],
                     )
                 })?;
+                oplog::file("write", product_json_path);
             }
         }
     }
@@ -925,10 +1476,72 @@ fn clean_checksums(product_json_path: &Path, locale: Option<&str>) -> PatchResul
     Ok(())
 }
 
-fn resolve_antigravity_root(path: &str, locale: Option<&str>) -> PatchResult<PathBuf> {
+/// 解析读取补丁状态使用的安装根目录，不解包、不同步副本，可用于只读命令
+pub(super) fn resolve_antigravity_root(path: &str, locale: Option<&str>) -> PatchResult<PathBuf> {
+    let input = PathBuf::from(path);
+    // AppImage 使用解包目录，尚未解包时在安装时解包
+    if appimage::is_appimage(&input) {
+        return appimage::current_root(&input)
+            .ok_or_else(|| patch_text(locale, "patchBackend.errors.appImageNotExtracted"));
+    }
+    let root = paths::normalize_antigravity_root(&input)
+        .ok_or_else(|| patch_text(locale, "patchBackend.errors.invalidInstallDir"))?;
+    // 已有影子副本时读取副本
+    Ok(shadow::current_root(&root).unwrap_or(root))
+}
+
+/// 解析补丁写入使用的安装根目录，仅供安装、卸载与更新配置使用
+/// 只读安装或开启影子安装时同步用户副本
+fn prepare_for_write(path: &str, locale: Option<&str>) -> PatchResult<PathBuf> {
     let input = PathBuf::from(path);
-    paths::normalize_antigravity_root(&input)
-        .ok_or_else(|| patch_text(locale, "patchBackend.errors.invalidInstallDir"))
+    if appimage::is_appimage(&input) {
+        return appimage::extracted_root(&input).map_err(|e| appimage_error(locale, e));
+    }
+    let root = paths::normalize_antigravity_root(&input)
+        .ok_or_else(|| patch_text(locale, "patchBackend.errors.invalidInstallDir"))?;
+    if shadow::is_enabled(&root) {
+        return shadow_root(&root, locale);
+    }
+    Ok(root)
+}
+
+/// 同步影子副本，源安装版本变化后按旧副本中已安装的配置重新安装补丁
+fn shadow_root(source: &Path, locale: Option<&str>) -> PatchResult<PathBuf> {
+    let synced = shadow::sync(source).map_err(|e| shadow_error(locale, e))?;
+    if let Some(previous) = &synced.previous {
+        reapply_patch(previous, &synced.root, locale)?;
+    }
+    shadow::finish(source, &synced.root, synced.previous.as_deref())
+        .map_err(|e| shadow_error(locale, e))?;
+    Ok(synced.root)
+}
+
+fn reapply_patch(previous: &Path, root: &Path, locale: Option<&str>) -> PatchResult<()> {
+    let previous_resources = Resources::open(previous);
+    let features = read_cascade_config(&previous_resources, locale)?;
+    let manager_features = read_manager_config(&previous_resources, locale)?;
+    if features.is_none() && manager_features.is_none() {
+        return Ok(());
+    }
+
+    let patch_set = match installed_pack_id(&previous_resources) {
+        Some(pack_id) => packs::load_patch_set(&pack_id, locale),
+        None => PatchSet::embedded(locale),
+    }?;
+    let resources_root = mount_resources(root, locale)?;
+    packs::ensure_compatible(&patch_set, &resources_root, locale)?;
+    let features = features.unwrap_or(FeatureConfig {
+        enabled: false,
+        ..FeatureConfig::default()
+    });
+    install_with_patch_set(
+        &resources_root,
+        &features,
+        &manager_features.unwrap_or_default(),
+        &patch_set,
+        locale,
+    )?;
+    commit_resources(root, locale)
 }
 
 fn is_permission_error(error: &CommandError) -> bool {
@@ -972,7 +1585,7 @@ fn first_unwritable_dir(dirs: &[&Path], locale: Option<&str>) -> PatchResult<Opt
     Ok(None)
 }
 
-fn can_write_dir(dir: &Path, locale: Option<&str>) -> PatchResult<bool> {
+pub(super) fn can_write_dir(dir: &Path, locale: Option<&str>) -> PatchResult<bool> {
     let test_path = dir.join(".anti-power-write-test");
     match fs::OpenOptions::new()
         .create(true)
@@ -1012,13 +1625,21 @@ fn handle_privileged_or_error(
     resources_root: &Path,
     features: Option<&FeatureConfig>,
     manager_features: Option<&ManagerFeatureConfig>,
+    patch_set: Option<&PatchSet>,
     dir: &Path,
     locale: Option<&str>,
 ) -> PatchResult<()> {
     #[cfg(any(target_os = "macos", target_os = "linux"))]
     {
         let _ = dir;
-        run_privileged_patch(mode, resources_root, features, manager_features, locale)
+        run_privileged_patch(
+            mode,
+            resources_root,
+            features,
+            manager_features,
+            patch_set,
+            locale,
+        )
     }
 
     #[cfg(not(any(target_os = "macos", target_os = "linux")))]
@@ -1037,10 +1658,56 @@ fn run_privileged_patch(
     resources_root: &Path,
     features: Option<&FeatureConfig>,
     manager_features: Option<&ManagerFeatureConfig>,
+    patch_set: Option<&PatchSet>,
     locale: Option<&str>,
 ) -> PatchResult<()> {
+    // 卸载和更新配置只需要脚本，使用内置补丁即可
+    let embedded_set;
+    let patch_set = match patch_set {
+        Some(patch_set) => patch_set,
+        None => {
+            embedded_set = PatchSet::embedded(locale)?;
+            &embedded_set
+        }
+    };
+
+    // 安装时跳过未启用的功能模块，脚本会整体复制临时目录中的面板目录
+    let excluded = match (&mode, features, manager_features) {
+        (PatchMode::Install, Some(features), Some(manager_features)) => {
+            let mut excluded = modules::excluded_files(
+                &patch_set.files,
+                Panel::Cascade,
+                &features.config_options(locale)?,
+                locale,
+            )?;
+            excluded.extend(modules::excluded_files(
+                &patch_set.files,
+                Panel::Manager,
+                &manager_features.config_options(locale)?,
+                locale,
+            )?);
+            excluded
+        }
+        _ => BTreeSet::new(),
+    };
+
     let temp_dir = prepare_temp_patch_dir(locale)?;
-    write_embedded_files_to_dir(&temp_dir, locale)?;
+    write_patch_files_to_dir(&temp_dir, patch_set, &excluded, locale)?;
+
+    if matches!(mode, PatchMode::Install) {
+        write_panel_manifest(
+            &temp_dir.join("cascade-panel"),
+            patch_set,
+            &|path| is_cascade_file(path) && !excluded.contains(path),
+            locale,
+        )?;
+        write_panel_manifest(
+            &temp_dir.join("manager-panel"),
+            patch_set,
+            &|path| is_manager_file(path) && !excluded.contains(path),
+            locale,
+        )?;
+    }
 
     if matches!(mode, PatchMode::Install | PatchMode::UpdateConfig) {
         let feature_config = features
@@ -1053,6 +1720,26 @@ fn run_privileged_patch(
 
         let manager_config_path = temp_dir.join("manager-panel").join("config.json");
         write_manager_config_file(&manager_config_path, manager_config, locale)?;
+
+        // 离线渲染：第三方库随面板目录一起复制到安装目录
+        if feature_config.offline_rendering(locale)? {
+            write_vendor_files(&temp_dir.join("cascade-panel"), locale)?;
+        }
+        if manager_config.offline_rendering(locale)? {
+            write_vendor_files(&temp_dir.join("manager-panel"), locale)?;
+        }
+
+        // 用户覆盖层随面板目录一起复制到安装目录
+        write_overlay_files(
+            &temp_dir.join("cascade-panel"),
+            &CASCADE_OVERLAY_FILES,
+            locale,
+        )?;
+        write_overlay_files(
+            &temp_dir.join("manager-panel"),
+            &MANAGER_OVERLAY_FILES,
+            locale,
+        )?;
     }
 
     let script_name = select_privileged_script(locale);
@@ -1072,12 +1759,22 @@ fn run_privileged_patch(
     let manager_enabled = manager_features
         .map(|config| config.enabled)
         .unwrap_or(true);
+    let file_actions = privileged_file_actions(
+        &mode,
+        &temp_dir,
+        resources_root,
+        cascade_enabled,
+        manager_enabled,
+    );
     let args = build_script_args(mode, resources_root, cascade_enabled, manager_enabled);
     let status_path = temp_dir.join("privileged-status.txt");
 
     match run_privileged_script(&script_path, &args, &status_path, locale) {
         Ok(()) => {
             let _ = fs::remove_dir_all(&temp_dir);
+            for (action, path) in &file_actions {
+                oplog::file(action, path);
+            }
             Ok(())
         }
         Err(err) => {
@@ -1092,6 +1789,206 @@ fn run_privileged_patch(
     }
 }
 
+/// 提权脚本的改动不经过本进程，按脚本的处理方式推算暂存文件在安装目录中的位置
+/// 在脚本执行前计算，成功后写入操作日志
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+fn privileged_file_actions(
+    mode: &PatchMode,
+    temp_dir: &Path,
+    resources_root: &Path,
+    cascade_enabled: bool,
+    manager_enabled: bool,
+) -> Vec<(&'static str, PathBuf)> {
+    let panels = [
+        (Panel::Cascade, "cascade-panel.html", cascade_enabled),
+        (
+            Panel::Manager,
+            "workbench-jetski-agent.html",
+            manager_enabled,
+        ),
+    ];
+    let mut actions = Vec::new();
+    for (panel, entry, enabled) in panels {
+        let panel_dir = panel.install_dir(resources_root);
+        let Some(parent) = panel_dir.parent() else {
+            continue;
+        };
+        let mut staged = Vec::new();
+        collect_staged_files(&temp_dir.join(panel.dir_name()), Path::new(""), &mut staged);
+
+        match mode {
+            PatchMode::Install if enabled => {
+                actions.push(("write", parent.join(entry)));
+                actions.extend(
+                    staged
+                        .iter()
+                        .map(|relative| ("write", panel_dir.join(relative))),
+                );
+            }
+            // 只更新配置时脚本复制 config.json、vendor/ 与 user/
+            PatchMode::UpdateConfig => {
+                if panel_dir.is_dir() {
+                    actions.extend(
+                        staged
+                            .iter()
+                            .filter(|relative| {
+                                relative.as_path() == Path::new("config.json")
+                                    || relative.starts_with(VENDOR_DIR)
+                                    || relative.starts_with(USER_OVERLAY_DIR)
+                            })
+                            .map(|relative| ("write", panel_dir.join(relative))),
+                    );
+                }
+            }
+            // 卸载或安装时停用的面板: 恢复入口 HTML 并删除面板目录
+            _ => {
+                if parent.join(format!("{}.bak", entry)).is_file() {
+                    actions.push(("restore", parent.join(entry)));
+                }
+                if panel_dir.is_dir() {
+                    actions.push(("delete", panel_dir));
+                }
+            }
+        }
+    }
+    if matches!(mode, PatchMode::Install) && manager_enabled {
+        actions.push(("write", resources_root.join("product.json")));
+    }
+    actions
+}
+
+/// 列出暂存目录中的文件 (相对路径)
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+fn collect_staged_files(dir: &Path, relative: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let relative = relative.join(entry.file_name());
+        if path.is_dir() {
+            collect_staged_files(&path, &relative, files);
+        } else {
+            files.push(relative);
+        }
+    }
+}
+
+/// 通过提权脚本执行文件操作: 写入内容和安装目录外的复制源先暂存到临时目录，
+/// 操作列表写入 file-ops.txt (每行以制表符分隔: copy 源 目标 / remove 目标 / remove-dir 目标)
+/// 脚本拒绝暂存目录以外的复制源与 --app-path 以外的目标
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+fn run_privileged_file_ops(
+    antigravity_root: &Path,
+    ops: &[FileOp],
+    dir: &Path,
+    locale: Option<&str>,
+) -> PatchResult<()> {
+    let temp_dir = prepare_temp_patch_dir(locale)?;
+    let result = stage_file_ops(&temp_dir, antigravity_root, ops, locale).and_then(|script_path| {
+        let args = [
+            "--mode".to_string(),
+            "apply-files".to_string(),
+            "--app-path".to_string(),
+            paths::resources_app_root(antigravity_root)
+                .to_string_lossy()
+                .to_string(),
+        ];
+        let status_path = temp_dir.join("privileged-status.txt");
+        run_privileged_script(&script_path, &args, &status_path, locale).map_err(|err| {
+            patch_with(
+                locale,
+                "patchBackend.errors.privilegedScriptFailed",
+                &[("message", annotate_privileged_error(err, dir, locale))],
+            )
+        })
+    });
+    let _ = fs::remove_dir_all(&temp_dir);
+    result?;
+
+    for op in ops {
+        log_file_op(op);
+    }
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+fn run_privileged_file_ops(
+    _antigravity_root: &Path,
+    _ops: &[FileOp],
+    dir: &Path,
+    locale: Option<&str>,
+) -> PatchResult<()> {
+    Err(patch_with(
+        locale,
+        "patchBackend.errors.permissionDeniedDir",
+        &[("dir", dir.display().to_string())],
+    ))
+}
+
+/// 写出脚本、暂存内容与操作列表，返回脚本路径
+/// 安装目录外的复制源 (还原点快照、暂存归档) 先链接或复制到暂存目录
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+fn stage_file_ops(
+    temp_dir: &Path,
+    antigravity_root: &Path,
+    ops: &[FileOp],
+    locale: Option<&str>,
+) -> PatchResult<PathBuf> {
+    let write_error = |path: &Path, e: std::io::Error| {
+        patch_with(
+            locale,
+            "patchBackend.errors.writeFileFailed",
+            &[("detail", format!("{:?}: {}", path, e))],
+        )
+    };
+
+    let script_name = select_privileged_script(locale);
+    let patch_set = PatchSet::embedded(locale)?;
+    let (_, script) = patch_set
+        .files
+        .iter()
+        .find(|(path, _)| path == script_name)
+        .ok_or_else(|| {
+            patch_with(
+                locale,
+                "patchBackend.errors.notFound",
+                &[("name", script_name.to_string())],
+            )
+        })?;
+    let script_path = temp_dir.join(script_name);
+    fs::write(&script_path, script).map_err(|e| write_error(&script_path, e))?;
+    ensure_script_executable(&script_path, locale)?;
+
+    let mut list = String::new();
+    for (index, op) in ops.iter().enumerate() {
+        let line = match op {
+            FileOp::Write { path, content } => {
+                let staged = temp_dir.join(format!("staged-{}", index));
+                fs::write(&staged, content).map_err(|e| write_error(&staged, e))?;
+                format!("copy\t{}\t{}", staged.display(), path.display())
+            }
+            FileOp::Copy { from, to } if from.starts_with(antigravity_root) => {
+                format!("copy\t{}\t{}", from.display(), to.display())
+            }
+            FileOp::Copy { from, to } => {
+                let staged = temp_dir.join(format!("staged-{}", index));
+                fs::hard_link(from, &staged)
+                    .or_else(|_| fs::copy(from, &staged).map(|_| ()))
+                    .map_err(|e| write_error(&staged, e))?;
+                format!("copy\t{}\t{}", staged.display(), to.display())
+            }
+            FileOp::Remove(path) => format!("remove\t{}", path.display()),
+            FileOp::RemoveDir(path) => format!("remove-dir\t{}", path.display()),
+        };
+        list.push_str(&line);
+        list.push('\n');
+    }
+    let list_path = temp_dir.join("file-ops.txt");
+    fs::write(&list_path, list).map_err(|e| write_error(&list_path, e))?;
+    Ok(script_path)
+}
+
 fn annotate_privileged_error(
     error: CommandError,
     resources_root: &Path,
@@ -1126,6 +2023,7 @@ fn run_privileged_patch(
     _resources_root: &Path,
     _features: Option<&FeatureConfig>,
     _manager_features: Option<&ManagerFeatureConfig>,
+    _patch_set: Option<&PatchSet>,
     _locale: Option<&str>,
 ) -> PatchResult<()> {
     Err(patch_text(
@@ -1134,38 +2032,45 @@ fn run_privileged_patch(
     ))
 }
 
+/// 创建提权脚本使用的临时目录 (同 mkdtemp): 随机名称、权限 0700，
+/// 名称已存在时换名重试，不使用其他用户可能预先创建的目录
 #[cfg(any(target_os = "macos", target_os = "linux"))]
 fn prepare_temp_patch_dir(locale: Option<&str>) -> PatchResult<PathBuf> {
-    let mut dir = env::temp_dir();
-    dir.push(format!("anti-power-privileged-{}", std::process::id()));
-
-    if dir.exists() {
-        fs::remove_dir_all(&dir).map_err(|e| {
-            patch_with(
-                locale,
-                "patchBackend.errors.cleanTempDirFailed",
-                &[("detail", e.to_string())],
-            )
-        })?;
-    }
-
-    fs::create_dir_all(&dir).map_err(|e| {
+    let create_error = |e: std::io::Error| {
         patch_with(
             locale,
             "patchBackend.errors.createTempDirFailed",
             &[("detail", e.to_string())],
         )
-    })?;
+    };
 
-    Ok(dir)
+    let mut random = [0u8; 8];
+    let mut urandom = fs::File::open("/dev/urandom").map_err(create_error)?;
+    loop {
+        urandom.read_exact(&mut random).map_err(create_error)?;
+        let suffix: String = random.iter().map(|b| format!("{:02x}", b)).collect();
+        let dir = env::temp_dir().join(format!("anti-power-privileged-{}", suffix));
+        match fs::DirBuilder::new().mode(0o700).create(&dir) {
+            Ok(()) => return Ok(dir),
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(create_error(e)),
+        }
+    }
 }
 
 #[cfg(any(target_os = "macos", target_os = "linux"))]
-fn write_embedded_files_to_dir(root: &Path, locale: Option<&str>) -> PatchResult<()> {
-    let patch_files =
-        embedded::get_all_files_runtime().map_err(|e| map_embedded_error(locale, e))?;
-    for (relative_path, content) in patch_files {
-        let full_path = root.join(&relative_path);
+fn write_patch_files_to_dir(
+    root: &Path,
+    patch_set: &PatchSet,
+    excluded: &BTreeSet<String>,
+    locale: Option<&str>,
+) -> PatchResult<()> {
+    for (relative_path, content) in &patch_set.files {
+        if excluded.contains(relative_path) {
+            continue;
+        }
+
+        let full_path = root.join(relative_path);
         if let Some(parent) = full_path.parent() {
             if !parent.exists() {
                 fs::create_dir_all(parent).map_err(|e| {
@@ -1250,6 +2155,7 @@ fn run_privileged_script(
     }
 
     let command_line = command_parts.join(" ");
+    oplog::elevation("sudo (Terminal)", command_line.clone());
     let status_path_quoted = shell_quote(status_path.to_string_lossy().as_ref());
     let terminal_command = format!("sudo {} ; echo $? > {}", command_line, status_path_quoted);
     let apple_script = format!(
@@ -1279,6 +2185,13 @@ fn run_privileged_script(
     _status_path: &Path,
     locale: Option<&str>,
 ) -> PatchResult<()> {
+    oplog::elevation(
+        "pkexec",
+        std::iter::once(format!("pkexec /bin/bash {}", script_path.display()))
+            .chain(args.iter().cloned())
+            .collect::<Vec<_>>()
+            .join(" "),
+    );
     let output = Command::new("pkexec")
         .arg("/bin/bash")
         .arg(script_path)
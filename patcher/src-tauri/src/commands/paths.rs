@@ -4,6 +4,8 @@
 
 use std::path::{Path, PathBuf};
 
+use super::asar::{self, Archive};
+
 /// 获取资源目录名称
 /// macOS 使用 "Resources"，其他平台使用 "resources"
 fn resources_dir_name() -> &'static str {
@@ -33,14 +35,57 @@ pub fn resources_app_root(root: &Path) -> PathBuf {
     }
 }
 
+/// 读取 Antigravity 版本号
+/// 来自 resources/app/package.json 的 version 字段，读取失败时返回 None
+pub fn read_antigravity_version(resources_root: &Path) -> Option<String> {
+    parse_antigravity_version(&std::fs::read(resources_root.join("package.json")).ok()?)
+}
+
+/// 从 package.json 内容中解析版本号
+pub fn parse_antigravity_version(package_json: &[u8]) -> Option<String> {
+    let package: serde_json::Value = serde_json::from_slice(package_json).ok()?;
+    package
+        .get("version")
+        .and_then(|version| version.as_str())
+        .map(|version| version.to_string())
+}
+
+/// 获取 app.asar 归档路径
+/// 只有 resources/app 目录不存在且存在 app.asar 时返回
+pub fn resources_archive(root: &Path) -> Option<PathBuf> {
+    let app_root = resources_app_root(root);
+    if app_root.is_dir() {
+        return None;
+    }
+    Some(app_root.with_file_name(asar::ARCHIVE_NAME)).filter(|archive| archive.is_file())
+}
+
 /// 验证是否为有效的 Antigravity 安装根目录
-/// 通过检查 cascade-panel.html 是否存在来判断
+/// 通过检查 cascade-panel.html 是否存在来判断 (app.asar 布局检查归档内的条目)
 pub fn is_valid_antigravity_root(root: &Path) -> bool {
-    resources_app_root(root)
-        .join("extensions")
-        .join("antigravity")
-        .join("cascade-panel.html")
-        .exists()
+    const CASCADE_PANEL: &str = "extensions/antigravity/cascade-panel.html";
+
+    if let Some(archive) = resources_archive(root) {
+        return Archive::open(&archive).is_ok_and(|archive| archive.contains(CASCADE_PANEL));
+    }
+    resources_app_root(root).join(CASCADE_PANEL).exists()
+}
+
+/// 获取 Antigravity 可执行文件路径
+/// macOS 的安装根目录为 .app/Contents，可执行文件位于 MacOS 目录
+pub fn antigravity_executable(root: &Path) -> Option<PathBuf> {
+    let candidates: &[&str] = if cfg!(target_os = "windows") {
+        &["Antigravity.exe"]
+    } else if cfg!(target_os = "macos") {
+        &["MacOS/Antigravity", "MacOS/Electron"]
+    } else {
+        &["antigravity", "Antigravity"]
+    };
+
+    candidates
+        .iter()
+        .map(|candidate| root.join(candidate))
+        .find(|path| path.is_file())
 }
 
 /// 规范化 Antigravity 安装路径
@@ -0,0 +1,327 @@
+//! 进程管理模块
+//!
+//! 查找属于指定安装目录的 Antigravity 进程 (Linux 读取 /proc，macOS 使用 ps，
+//! Windows 使用 CIM)，安装/卸载补丁前可正常关闭并在完成后重新启动
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use super::i18n::CommandError;
+use super::oplog;
+use super::patch::resolve_antigravity_root;
+
+type ProcessResult<T> = Result<T, CommandError>;
+
+/// 等待进程退出的时间
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(15);
+
+#[cfg(target_os = "windows")]
+const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+fn process_text(_locale: Option<&str>, key: &'static str) -> CommandError {
+    CommandError::key(key)
+}
+
+fn process_with(_locale: Option<&str>, key: &'static str, vars: &[(&str, String)]) -> CommandError {
+    CommandError::key_with(key, vars)
+}
+
+/// Antigravity 进程
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AntigravityProcess {
+    pub pid: u32,
+    /// 父进程 ID
+    pub ppid: u32,
+    /// 可执行文件路径
+    pub exe: String,
+    /// 命令行参数 (不含可执行文件，无法读取时为空)
+    pub args: Vec<String>,
+}
+
+/// 关闭前记录的主进程，用于重新启动
+pub(super) struct Relaunch {
+    exe: PathBuf,
+    args: Vec<String>,
+}
+
+/// 查找属于安装目录的 Antigravity 进程
+#[tauri::command]
+pub fn find_antigravity_processes(
+    path: String,
+    locale: Option<String>,
+) -> Result<Vec<AntigravityProcess>, String> {
+    let locale_ref = locale.as_deref();
+    let root =
+        resolve_antigravity_root(&path, locale_ref).map_err(|err| err.to_message(locale_ref))?;
+    Ok(find_processes(&root))
+}
+
+/// 查找可执行文件位于安装目录下的进程 (不含安装器自身)
+pub(super) fn find_processes(antigravity_root: &Path) -> Vec<AntigravityProcess> {
+    let root = std::fs::canonicalize(antigravity_root).unwrap_or(antigravity_root.to_path_buf());
+    let own_pid = std::process::id();
+    list_processes()
+        .into_iter()
+        .filter(|process| process.pid != own_pid)
+        .filter(|process| is_under(Path::new(&process.exe), &root))
+        .collect()
+}
+
+#[cfg(target_os = "windows")]
+fn is_under(exe: &Path, root: &Path) -> bool {
+    let exe = exe.to_string_lossy().to_lowercase();
+    let root = root.to_string_lossy().to_lowercase();
+    let root = root.trim_start_matches(r"\\?\").trim_end_matches('\\');
+    exe.trim_start_matches(r"\\?\")
+        .strip_prefix(root)
+        .is_some_and(|rest| rest.starts_with('\\'))
+}
+
+#[cfg(not(target_os = "windows"))]
+fn is_under(exe: &Path, root: &Path) -> bool {
+    exe.starts_with(root)
+}
+
+#[cfg(target_os = "linux")]
+fn list_processes() -> Vec<AntigravityProcess> {
+    let Ok(entries) = std::fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+
+    entries
+        .flatten()
+        .filter_map(|entry| entry.file_name().to_str()?.parse::<u32>().ok())
+        .filter_map(|pid| {
+            let proc_dir = PathBuf::from(format!("/proc/{}", pid));
+            let mut cmdline: Vec<String> = std::fs::read(proc_dir.join("cmdline"))
+                .map(|bytes| {
+                    bytes
+                        .split(|byte| *byte == 0)
+                        .filter(|arg| !arg.is_empty())
+                        .map(|arg| String::from_utf8_lossy(arg).to_string())
+                        .collect()
+                })
+                .unwrap_or_default();
+            // 其他用户的进程无法读取 exe 链接，退回到 argv[0]
+            let exe = std::fs::read_link(proc_dir.join("exe"))
+                .ok()
+                .map(|exe| exe.to_string_lossy().to_string())
+                .or_else(|| cmdline.first().cloned())?;
+            // stat 格式: pid (comm) state ppid ...，comm 可能包含空格
+            let stat = std::fs::read_to_string(proc_dir.join("stat")).ok()?;
+            let ppid = stat
+                .rsplit_once(')')?
+                .1
+                .split_whitespace()
+                .nth(1)?
+                .parse()
+                .ok()?;
+            let args = if cmdline.is_empty() {
+                Vec::new()
+            } else {
+                cmdline.split_off(1)
+            };
+            Some(AntigravityProcess {
+                pid,
+                ppid,
+                exe: exe.trim_end_matches(" (deleted)").to_string(),
+                args,
+            })
+        })
+        .collect()
+}
+
+#[cfg(target_os = "macos")]
+fn list_processes() -> Vec<AntigravityProcess> {
+    // macOS 的 comm 为可执行文件完整路径
+    let Ok(output) = Command::new("ps")
+        .args(["-axww", "-o", "pid=,ppid=,comm="])
+        .output()
+    else {
+        return Vec::new();
+    };
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.trim_start().splitn(2, char::is_whitespace);
+            let pid = parts.next()?.parse().ok()?;
+            let mut rest = parts.next()?.trim_start().splitn(2, char::is_whitespace);
+            let ppid = rest.next()?.parse().ok()?;
+            let exe = rest.next()?.trim().to_string();
+            Some(AntigravityProcess {
+                pid,
+                ppid,
+                exe,
+                args: Vec::new(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(target_os = "windows")]
+fn list_processes() -> Vec<AntigravityProcess> {
+    use std::os::windows::process::CommandExt;
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct Win32Process {
+        process_id: u32,
+        parent_process_id: u32,
+        executable_path: Option<String>,
+    }
+
+    let script = "Get-CimInstance Win32_Process | \
+                  Select-Object ProcessId,ParentProcessId,ExecutablePath | \
+                  ConvertTo-Json -Compress";
+    let Ok(output) = Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", script])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+    else {
+        return Vec::new();
+    };
+
+    // 只有一个进程时 ConvertTo-Json 输出对象而不是数组
+    let value: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap_or_default();
+    let items = match value {
+        serde_json::Value::Array(items) => items,
+        serde_json::Value::Null => Vec::new(),
+        item => vec![item],
+    };
+
+    items
+        .into_iter()
+        .filter_map(|item| serde_json::from_value::<Win32Process>(item).ok())
+        .filter_map(|process| {
+            Some(AntigravityProcess {
+                pid: process.process_id,
+                ppid: process.parent_process_id,
+                exe: process.executable_path?,
+                args: Vec::new(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn list_processes() -> Vec<AntigravityProcess> {
+    Vec::new()
+}
+
+/// 主进程: 父进程不属于 Antigravity 的进程
+fn main_processes(processes: &[AntigravityProcess]) -> Vec<&AntigravityProcess> {
+    let pids: BTreeSet<u32> = processes.iter().map(|process| process.pid).collect();
+    processes
+        .iter()
+        .filter(|process| !pids.contains(&process.ppid))
+        .collect()
+}
+
+/// 请求进程正常退出
+fn terminate(pid: u32) -> std::io::Result<()> {
+    #[cfg(target_os = "windows")]
+    let mut command = {
+        use std::os::windows::process::CommandExt;
+
+        let mut command = Command::new("taskkill");
+        command
+            .args(["/PID", &pid.to_string()])
+            .creation_flags(CREATE_NO_WINDOW);
+        command
+    };
+
+    #[cfg(not(target_os = "windows"))]
+    let mut command = {
+        let mut command = Command::new("kill");
+        command.args(["-TERM", &pid.to_string()]);
+        command
+    };
+
+    command
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()?;
+    Ok(())
+}
+
+/// 补丁操作前按需关闭 Antigravity
+/// close 为 false 时不做处理；关闭后返回需要重新启动的主进程
+pub(super) fn close_for_patch(
+    antigravity_root: &Path,
+    close: bool,
+    locale: Option<&str>,
+) -> ProcessResult<Option<Relaunch>> {
+    if !close {
+        return Ok(None);
+    }
+    let processes = find_processes(antigravity_root);
+    let Some(main) = main_processes(&processes).first().map(|process| Relaunch {
+        exe: PathBuf::from(&process.exe),
+        args: process.args.clone(),
+    }) else {
+        return Ok(None);
+    };
+
+    for process in main_processes(&processes) {
+        terminate(process.pid).map_err(|e| {
+            process_with(
+                locale,
+                "processBackend.errors.closeFailed",
+                &[("detail", e.to_string())],
+            )
+        })?;
+    }
+
+    let started = Instant::now();
+    while !find_processes(antigravity_root).is_empty() {
+        if started.elapsed() > CLOSE_TIMEOUT {
+            return Err(process_text(locale, "processBackend.errors.closeTimeout"));
+        }
+        std::thread::sleep(Duration::from_millis(300));
+    }
+    oplog::output(&format!("closed Antigravity: {}", main.exe.display()));
+
+    Ok(Some(main))
+}
+
+/// 重新启动关闭前的 Antigravity
+/// 补丁操作已完成，启动失败只记录到操作日志
+pub(super) fn relaunch(app: &Relaunch) {
+    let result = spawn_detached(app);
+    let message = match &result {
+        Ok(()) => format!("relaunched Antigravity: {}", app.exe.display()),
+        Err(e) => format!("failed to relaunch {}: {}", app.exe.display(), e),
+    };
+    oplog::output(&message);
+}
+
+#[cfg(target_os = "macos")]
+fn spawn_detached(app: &Relaunch) -> std::io::Result<()> {
+    // 通过 .app 包启动，保持 LaunchServices 的正常行为
+    let bundle = app
+        .exe
+        .ancestors()
+        .find(|path| path.extension().is_some_and(|ext| ext == "app"))
+        .unwrap_or(&app.exe);
+    Command::new("open")
+        .arg(bundle)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map(|_| ())
+}
+
+#[cfg(not(target_os = "macos"))]
+fn spawn_detached(app: &Relaunch) -> std::io::Result<()> {
+    Command::new(&app.exe)
+        .args(&app.args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map(|_| ())
+}
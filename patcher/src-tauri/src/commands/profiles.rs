@@ -0,0 +1,282 @@
+//! 配置方案模块
+//!
+//! 配置方案是命名的完整功能配置 (侧边栏 + Manager)，保存在应用配置中，
+//! 可一键切换并导出为独立 JSON 文件与他人共享
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+use super::config::{self, AppConfig};
+use super::features::{validate_features, FeatureConfig, ManagerFeatureConfig};
+use super::i18n::CommandError;
+use super::patch;
+
+type ProfileResult<T> = Result<T, CommandError>;
+
+/// 导出文件的格式标识
+const PROFILE_FORMAT: &str = "anti-power-profile";
+/// 方案名称最大长度
+const MAX_NAME_LENGTH: usize = 64;
+
+fn profile_text(_locale: Option<&str>, key: &'static str) -> CommandError {
+    CommandError::key(key)
+}
+
+fn profile_with(_locale: Option<&str>, key: &'static str, vars: &[(&str, String)]) -> CommandError {
+    CommandError::key_with(key, vars)
+}
+
+/// 配置方案
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Profile {
+    /// 方案名称 (唯一)
+    pub name: String,
+    /// 侧边栏功能配置
+    pub features: FeatureConfig,
+    /// Manager 功能配置
+    #[serde(rename = "managerFeatures")]
+    pub manager_features: ManagerFeatureConfig,
+}
+
+/// 配置方案列表及当前方案
+#[derive(Debug, Serialize)]
+pub struct ProfileList {
+    pub profiles: Vec<Profile>,
+    /// 当前启用的方案名称
+    pub active: Option<String>,
+}
+
+/// 导出的方案文件
+#[derive(Debug, Serialize, Deserialize)]
+struct ProfileFile {
+    format: String,
+    #[serde(flatten)]
+    profile: Profile,
+}
+
+fn profile_list(config: AppConfig) -> ProfileList {
+    ProfileList {
+        profiles: config.profiles,
+        active: config.active_profile,
+    }
+}
+
+fn normalize_name(name: &str, locale: Option<&str>) -> ProfileResult<String> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LENGTH {
+        return Err(profile_with(
+            locale,
+            "profileBackend.errors.invalidName",
+            &[("max", MAX_NAME_LENGTH.to_string())],
+        ));
+    }
+    Ok(name.to_string())
+}
+
+fn find_index(config: &AppConfig, name: &str, locale: Option<&str>) -> ProfileResult<usize> {
+    config
+        .profiles
+        .iter()
+        .position(|profile| profile.name == name)
+        .ok_or_else(|| {
+            profile_with(
+                locale,
+                "profileBackend.errors.notFound",
+                &[("name", name.to_string())],
+            )
+        })
+}
+
+/// 获取配置方案列表
+#[tauri::command]
+pub fn list_profiles() -> ProfileList {
+    profile_list(config::read_config())
+}
+
+/// 将当前功能配置保存为方案 (同名方案会被覆盖)
+#[tauri::command]
+pub fn save_profile(
+    name: String,
+    features: FeatureConfig,
+    manager_features: ManagerFeatureConfig,
+    locale: Option<String>,
+) -> Result<ProfileList, String> {
+    let locale_ref = locale.as_deref();
+    save_profile_internal(
+        Profile {
+            name,
+            features,
+            manager_features,
+        },
+        locale_ref,
+    )
+    .map_err(|err| err.to_message(locale_ref))
+}
+
+fn save_profile_internal(mut profile: Profile, locale: Option<&str>) -> ProfileResult<ProfileList> {
+    profile.name = normalize_name(&profile.name, locale)?;
+    validate_features(&profile.features, &profile.manager_features, locale)?;
+
+    let mut config = config::load_config_for_update(locale)?;
+    match config
+        .profiles
+        .iter_mut()
+        .find(|existing| existing.name == profile.name)
+    {
+        Some(existing) => *existing = profile,
+        None => config.profiles.push(profile),
+    }
+    config::save_config_internal(config, locale)?;
+
+    Ok(list_profiles())
+}
+
+/// 重命名配置方案
+#[tauri::command]
+pub fn rename_profile(
+    name: String,
+    new_name: String,
+    locale: Option<String>,
+) -> Result<ProfileList, String> {
+    let locale_ref = locale.as_deref();
+    rename_profile_internal(&name, &new_name, locale_ref).map_err(|err| err.to_message(locale_ref))
+}
+
+fn rename_profile_internal(
+    name: &str,
+    new_name: &str,
+    locale: Option<&str>,
+) -> ProfileResult<ProfileList> {
+    let new_name = normalize_name(new_name, locale)?;
+    let mut config = config::load_config_for_update(locale)?;
+    let index = find_index(&config, name, locale)?;
+
+    if new_name != name && config.profiles.iter().any(|p| p.name == new_name) {
+        return Err(profile_with(
+            locale,
+            "profileBackend.errors.exists",
+            &[("name", new_name)],
+        ));
+    }
+
+    if config.active_profile.as_deref() == Some(name) {
+        config.active_profile = Some(new_name.clone());
+    }
+    config.profiles[index].name = new_name;
+    config::save_config_internal(config, locale)?;
+
+    Ok(list_profiles())
+}
+
+/// 删除配置方案
+#[tauri::command]
+pub fn delete_profile(name: String, locale: Option<String>) -> Result<ProfileList, String> {
+    let locale_ref = locale.as_deref();
+    delete_profile_internal(&name, locale_ref).map_err(|err| err.to_message(locale_ref))
+}
+
+fn delete_profile_internal(name: &str, locale: Option<&str>) -> ProfileResult<ProfileList> {
+    let mut config = config::load_config_for_update(locale)?;
+    let index = find_index(&config, name, locale)?;
+
+    config.profiles.remove(index);
+    if config.active_profile.as_deref() == Some(name) {
+        config.active_profile = None;
+    }
+    config::save_config_internal(config, locale)?;
+
+    Ok(list_profiles())
+}
+
+/// 启用配置方案
+/// 传入 path 时通过 update_config 将方案应用到已安装的补丁
+#[tauri::command]
+pub fn activate_profile(
+    name: String,
+    path: Option<String>,
+    locale: Option<String>,
+) -> Result<Profile, String> {
+    let locale_ref = locale.as_deref();
+    let mut config =
+        config::load_config_for_update(locale_ref).map_err(|err| err.to_message(locale_ref))?;
+    let index = find_index(&config, &name, locale_ref).map_err(|err| err.to_message(locale_ref))?;
+    let profile = config.profiles[index].clone();
+
+    if let Some(path) = path {
+        patch::update_config(
+            path,
+            profile.features.clone(),
+            profile.manager_features.clone(),
+            locale.clone(),
+        )?;
+    }
+
+    config.active_profile = Some(name);
+    config::save_config_internal(config, locale_ref).map_err(|err| err.to_message(locale_ref))?;
+
+    Ok(profile)
+}
+
+/// 导出配置方案到 JSON 文件
+#[tauri::command]
+pub fn export_profile(
+    name: String,
+    dest_path: String,
+    locale: Option<String>,
+) -> Result<(), String> {
+    let locale_ref = locale.as_deref();
+    export_profile_internal(&name, &dest_path, locale_ref).map_err(|err| err.to_message(locale_ref))
+}
+
+fn export_profile_internal(name: &str, dest_path: &str, locale: Option<&str>) -> ProfileResult<()> {
+    let config = config::read_config();
+    let index = find_index(&config, name, locale)?;
+    let file = ProfileFile {
+        format: PROFILE_FORMAT.to_string(),
+        profile: config.profiles[index].clone(),
+    };
+
+    let content = serde_json::to_string_pretty(&file).map_err(|e| {
+        profile_with(
+            locale,
+            "profileBackend.errors.exportFailed",
+            &[("detail", e.to_string())],
+        )
+    })?;
+    fs::write(dest_path, content).map_err(|e| {
+        profile_with(
+            locale,
+            "profileBackend.errors.exportFailed",
+            &[("detail", e.to_string())],
+        )
+    })
+}
+
+/// 从 JSON 文件导入配置方案 (同名方案会被覆盖)
+#[tauri::command]
+pub fn import_profile(src_path: String, locale: Option<String>) -> Result<ProfileList, String> {
+    let locale_ref = locale.as_deref();
+    import_profile_internal(&src_path, locale_ref).map_err(|err| err.to_message(locale_ref))
+}
+
+fn import_profile_internal(src_path: &str, locale: Option<&str>) -> ProfileResult<ProfileList> {
+    let content = fs::read_to_string(src_path).map_err(|e| {
+        profile_with(
+            locale,
+            "profileBackend.errors.importFailed",
+            &[("detail", e.to_string())],
+        )
+    })?;
+    let file: ProfileFile = serde_json::from_str(&content).map_err(|e| {
+        profile_with(
+            locale,
+            "profileBackend.errors.importFailed",
+            &[("detail", e.to_string())],
+        )
+    })?;
+    if file.format != PROFILE_FORMAT {
+        return Err(profile_text(locale, "profileBackend.errors.invalidFile"));
+    }
+
+    save_profile_internal(file.profile, locale)
+}
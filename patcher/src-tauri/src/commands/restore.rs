@@ -0,0 +1,521 @@
+//! 还原点模块
+//!
+//! 安装、卸载、更新配置前将受影响的文件 (面板入口 HTML、面板目录及其 config.json、
+//! product.json 的 checksums 片段) 快照到应用数据目录，可回滚到任意还原点
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::appimage;
+use super::asar;
+use super::atomic::FileOp;
+use super::config;
+use super::i18n::CommandError;
+use super::oplog;
+use super::patch::{
+    apply_file_ops, commit_asar, map_embedded_error, resolve_antigravity_root, VENDOR_DIR,
+};
+use super::paths;
+use super::shadow;
+use crate::embedded;
+
+type RestoreResult<T> = Result<T, CommandError>;
+
+/// 还原点目录名 (位于应用数据目录)
+const RESTORE_DIR: &str = "restore-points";
+/// 还原点描述文件名
+const POINT_FILE: &str = "point.json";
+/// 快照文件目录名
+const FILES_DIR: &str = "files";
+/// 默认保留的还原点数量
+pub(super) const DEFAULT_RESTORE_POINT_LIMIT: usize = 10;
+/// 可设置的还原点数量上限
+const MAX_RESTORE_POINT_LIMIT: usize = 100;
+
+/// 快照的路径 (相对于 resources/app)
+const SNAPSHOT_PATHS: &[&str] = &[
+    "extensions/antigravity/cascade-panel.html",
+    "extensions/antigravity/cascade-panel",
+    "out/vs/code/electron-browser/workbench/workbench-jetski-agent.html",
+    "out/vs/code/electron-browser/workbench/manager-panel",
+];
+
+fn restore_with(_locale: Option<&str>, key: &'static str, vars: &[(&str, String)]) -> CommandError {
+    CommandError::key_with(key, vars)
+}
+
+/// 快照条目的状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EntryKind {
+    File,
+    /// 目录快照不含 vendor 资源，vendor 为 true 时回滚后重新写入
+    Dir {
+        vendor: bool,
+    },
+    /// 拍摄快照时不存在，回滚时删除
+    Missing,
+}
+
+/// 快照条目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotEntry {
+    /// 相对于 resources/app 的路径
+    pub path: String,
+    pub kind: EntryKind,
+}
+
+/// 还原点
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestorePoint {
+    pub id: String,
+    /// 创建时间 (Unix 毫秒)
+    pub timestamp: u64,
+    /// 创建还原点的操作 (install / uninstall / updateConfig / rollback)
+    pub operation: String,
+    /// Antigravity 安装目录
+    pub target: String,
+    pub entries: Vec<SnapshotEntry>,
+    /// product.json 的 checksums 片段 (product.json 不存在时为 None)
+    #[serde(rename = "productChecksums")]
+    pub product_checksums: Option<Value>,
+}
+
+/// 还原点列表及保留数量
+#[derive(Debug, Serialize)]
+pub struct RestorePointList {
+    /// 从新到旧
+    pub points: Vec<RestorePoint>,
+    pub limit: usize,
+}
+
+fn restore_dir() -> PathBuf {
+    config::get_app_data_dir().join(RESTORE_DIR)
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// 当前设置的保留数量
+fn restore_point_limit() -> usize {
+    config::read_config()
+        .restore_point_limit
+        .unwrap_or(DEFAULT_RESTORE_POINT_LIMIT)
+}
+
+fn capture_error(locale: Option<&str>, e: std::io::Error) -> CommandError {
+    restore_with(
+        locale,
+        "restoreBackend.errors.captureFailed",
+        &[("detail", e.to_string())],
+    )
+}
+
+/// 修改安装目录前创建还原点，保留数量为 0 时不创建
+pub(super) fn capture(
+    operation: &str,
+    antigravity_root: &Path,
+    locale: Option<&str>,
+) -> RestoreResult<()> {
+    let limit = restore_point_limit();
+    if limit == 0 {
+        return Ok(());
+    }
+    create_point(operation, antigravity_root).map_err(|e| capture_error(locale, e))?;
+    prune_points(limit).map_err(|e| capture_error(locale, e))
+}
+
+fn create_point(operation: &str, antigravity_root: &Path) -> std::io::Result<RestorePoint> {
+    let resources_root = asar::mount(antigravity_root, false)?;
+    let timestamp = now_millis();
+
+    // 同一毫秒内的多次操作使用递增后缀
+    let mut id = timestamp.to_string();
+    let mut suffix = 1;
+    while restore_dir().join(&id).exists() {
+        id = format!("{}-{}", timestamp, suffix);
+        suffix += 1;
+    }
+    let point_dir = restore_dir().join(&id);
+    let files_dir = point_dir.join(FILES_DIR);
+    fs::create_dir_all(&files_dir)?;
+
+    let result = snapshot_entries(&resources_root, &files_dir).and_then(|entries| {
+        let point = RestorePoint {
+            id,
+            timestamp,
+            operation: operation.to_string(),
+            target: antigravity_root.to_string_lossy().to_string(),
+            entries,
+            product_checksums: read_product_checksums(&resources_root)?,
+        };
+        let content = serde_json::to_string_pretty(&point)?;
+        fs::write(point_dir.join(POINT_FILE), content)?;
+        Ok(point)
+    });
+
+    // 不完整的还原点不保留
+    if result.is_err() {
+        let _ = fs::remove_dir_all(&point_dir);
+    }
+    result
+}
+
+fn snapshot_entries(
+    resources_root: &Path,
+    files_dir: &Path,
+) -> std::io::Result<Vec<SnapshotEntry>> {
+    let mut entries = Vec::new();
+    for relative in SNAPSHOT_PATHS {
+        let source = resources_root.join(relative);
+        let dest = files_dir.join(relative);
+        let kind = if source.is_dir() {
+            copy_dir(&source, &dest, true)?;
+            EntryKind::Dir {
+                vendor: source.join(VENDOR_DIR).is_dir(),
+            }
+        } else if source.is_file() {
+            if let Some(parent) = dest.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::copy(&source, &dest)?;
+            EntryKind::File
+        } else {
+            EntryKind::Missing
+        };
+        entries.push(SnapshotEntry {
+            path: relative.to_string(),
+            kind,
+        });
+    }
+    Ok(entries)
+}
+
+/// 复制目录，skip_vendor 为 true 时跳过顶层 vendor 目录 (可由内置资源重新生成)
+fn copy_dir(source: &Path, dest: &Path, skip_vendor: bool) -> std::io::Result<()> {
+    fs::create_dir_all(dest)?;
+    for entry in fs::read_dir(source)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        if skip_vendor && name == VENDOR_DIR {
+            continue;
+        }
+        let path = entry.path();
+        if path.is_dir() {
+            copy_dir(&path, &dest.join(&name), false)?;
+        } else {
+            fs::copy(&path, dest.join(&name))?;
+        }
+    }
+    Ok(())
+}
+
+fn read_product_checksums(resources_root: &Path) -> std::io::Result<Option<Value>> {
+    let product_json_path = resources_root.join("product.json");
+    if !product_json_path.exists() {
+        return Ok(None);
+    }
+    let content = fs::read(&product_json_path)?;
+    let json: Value = serde_json::from_slice(&content)?;
+    Ok(Some(json.get("checksums").cloned().unwrap_or(Value::Null)))
+}
+
+/// 删除超出保留数量的旧还原点
+fn prune_points(limit: usize) -> std::io::Result<()> {
+    for point in load_points().into_iter().skip(limit) {
+        fs::remove_dir_all(restore_dir().join(&point.id))?;
+    }
+    Ok(())
+}
+
+/// 读取所有还原点，从新到旧
+fn load_points() -> Vec<RestorePoint> {
+    let Ok(entries) = fs::read_dir(restore_dir()) else {
+        return Vec::new();
+    };
+    let mut points: Vec<RestorePoint> = entries
+        .flatten()
+        .filter_map(|entry| fs::read(entry.path().join(POINT_FILE)).ok())
+        .filter_map(|content| serde_json::from_slice(&content).ok())
+        .collect();
+    points.sort_by(|a, b| (b.timestamp, &b.id).cmp(&(a.timestamp, &a.id)));
+    points
+}
+
+/// 获取还原点列表，传入 path 时只返回该安装目录的还原点
+#[tauri::command]
+pub fn list_restore_points(path: Option<String>) -> RestorePointList {
+    let target = path
+        .as_deref()
+        .and_then(|path| resolve_antigravity_root(path, None).ok());
+    let points = load_points()
+        .into_iter()
+        .filter(|point| {
+            target
+                .as_ref()
+                .is_none_or(|target| Path::new(&point.target) == target)
+        })
+        .collect();
+
+    RestorePointList {
+        points,
+        limit: restore_point_limit(),
+    }
+}
+
+/// 设置保留的还原点数量 (0 表示不创建还原点)
+#[tauri::command]
+pub fn set_restore_point_limit(
+    limit: usize,
+    locale: Option<String>,
+) -> Result<RestorePointList, String> {
+    let locale_ref = locale.as_deref();
+    set_restore_point_limit_internal(limit, locale_ref)
+        .map_err(|err| err.to_message(locale_ref))?;
+    Ok(list_restore_points(None))
+}
+
+fn set_restore_point_limit_internal(limit: usize, locale: Option<&str>) -> RestoreResult<()> {
+    if limit > MAX_RESTORE_POINT_LIMIT {
+        return Err(restore_with(
+            locale,
+            "restoreBackend.errors.invalidLimit",
+            &[("max", MAX_RESTORE_POINT_LIMIT.to_string())],
+        ));
+    }
+
+    let mut config = config::load_config_for_update(locale)?;
+    config.restore_point_limit = Some(limit);
+    config::save_config_internal(config, locale)?;
+
+    prune_points(limit).map_err(|e| {
+        restore_with(
+            locale,
+            "restoreBackend.errors.pruneFailed",
+            &[("detail", e.to_string())],
+        )
+    })
+}
+
+/// 回滚到指定还原点
+/// 回滚前为当前状态创建新的还原点，回滚本身也可撤销
+#[tauri::command]
+pub fn rollback_restore_point(id: String, locale: Option<String>) -> Result<(), String> {
+    let locale_ref = locale.as_deref();
+    let point = load_points()
+        .into_iter()
+        .find(|point| point.id == id)
+        .ok_or_else(|| {
+            restore_with(
+                locale_ref,
+                "restoreBackend.errors.notFound",
+                &[("id", id.clone())],
+            )
+            .to_message(locale_ref)
+        })?;
+
+    oplog::record(
+        "rollback",
+        Some(&point.target),
+        Some(serde_json::json!({ "restorePoint": &point.id })),
+        || rollback_internal(&point, locale_ref).map_err(|err| err.to_message(locale_ref)),
+    )
+}
+
+fn rollback_internal(point: &RestorePoint, locale: Option<&str>) -> RestoreResult<()> {
+    let antigravity_root = Path::new(&point.target);
+    if !paths::resources_app_root(antigravity_root).exists()
+        && paths::resources_archive(antigravity_root).is_none()
+    {
+        return Err(restore_with(
+            locale,
+            "restoreBackend.errors.targetMissing",
+            &[("path", point.target.clone())],
+        ));
+    }
+    let resources_root = asar::mount(antigravity_root, true)
+        .map_err(|e| rollback_error(locale, antigravity_root, e))?;
+
+    // 先创建新还原点，回滚完成后再清理旧还原点，避免目标还原点被提前删除
+    let limit = restore_point_limit();
+    if limit > 0 {
+        create_point("rollback", antigravity_root).map_err(|e| capture_error(locale, e))?;
+    }
+
+    // 安装目录不可写时 (如 /Applications、/opt) 整体交给提权脚本执行
+    let files_dir = restore_dir().join(&point.id).join(FILES_DIR);
+    let mut ops = Vec::new();
+    for entry in &point.entries {
+        ops.extend(restore_entry_ops(
+            &resources_root,
+            &files_dir,
+            entry,
+            locale,
+        )?);
+    }
+    ops.extend(
+        product_checksums_op(&resources_root, point.product_checksums.as_ref())
+            .map_err(|e| rollback_error(locale, &resources_root.join("product.json"), e))?,
+    );
+    apply_file_ops(antigravity_root, &ops, locale)?;
+    commit_asar(antigravity_root, locale)?;
+    appimage::commit(antigravity_root).map_err(|e| rollback_error(locale, antigravity_root, e))?;
+    // 影子副本回滚后同样需要重新生成启动项
+    shadow::commit(antigravity_root).map_err(|e| rollback_error(locale, antigravity_root, e))?;
+
+    if limit > 0 {
+        prune_points(limit).map_err(|e| {
+            restore_with(
+                locale,
+                "restoreBackend.errors.pruneFailed",
+                &[("detail", e.to_string())],
+            )
+        })?;
+    }
+    Ok(())
+}
+
+fn rollback_error(locale: Option<&str>, path: &Path, e: std::io::Error) -> CommandError {
+    if e.kind() == ErrorKind::PermissionDenied {
+        return restore_with(
+            locale,
+            "restoreBackend.errors.permissionDenied",
+            &[("path", path.display().to_string())],
+        );
+    }
+    restore_with(
+        locale,
+        "restoreBackend.errors.rollbackFailed",
+        &[
+            ("path", path.display().to_string()),
+            ("detail", e.to_string()),
+        ],
+    )
+}
+
+/// 生成将快照条目恢复到安装目录的文件操作: 先删除当前文件，再从快照复制
+fn restore_entry_ops(
+    resources_root: &Path,
+    files_dir: &Path,
+    entry: &SnapshotEntry,
+    locale: Option<&str>,
+) -> RestoreResult<Vec<FileOp>> {
+    let target = resources_root.join(&entry.path);
+    let snapshot = files_dir.join(&entry.path);
+    let mut ops = Vec::new();
+
+    if target.is_dir() {
+        ops.push(FileOp::RemoveDir(target.clone()));
+    } else if target.exists() {
+        ops.push(FileOp::Remove(target.clone()));
+    }
+
+    match entry.kind {
+        EntryKind::File => ops.push(FileOp::Copy {
+            from: snapshot,
+            to: target,
+        }),
+        EntryKind::Dir { vendor } => {
+            copy_dir_ops(&snapshot, &target, &mut ops)
+                .map_err(|e| rollback_error(locale, &target, e))?;
+            if vendor {
+                let vendor_files = embedded::get_vendor_files_runtime()
+                    .map_err(|e| map_embedded_error(locale, e))?;
+                ops.extend(vendor_files.into_iter().map(|(relative_path, content)| {
+                    FileOp::Write {
+                        path: target.join(relative_path),
+                        content,
+                    }
+                }));
+            }
+        }
+        EntryKind::Missing => {}
+    }
+    Ok(ops)
+}
+
+/// 生成复制快照目录中所有文件的操作
+fn copy_dir_ops(source: &Path, dest: &Path, ops: &mut Vec<FileOp>) -> std::io::Result<()> {
+    for entry in fs::read_dir(source)? {
+        let entry = entry?;
+        let path = entry.path();
+        let target = dest.join(entry.file_name());
+        if path.is_dir() {
+            copy_dir_ops(&path, &target, ops)?;
+        } else {
+            ops.push(FileOp::Copy {
+                from: path,
+                to: target,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// 生成恢复 product.json checksums 片段的写入操作，内容未变化时为 None
+fn product_checksums_op(
+    resources_root: &Path,
+    checksums: Option<&Value>,
+) -> std::io::Result<Option<FileOp>> {
+    let product_json_path = resources_root.join("product.json");
+    let Some(checksums) = checksums else {
+        return Ok(None);
+    };
+    if !product_json_path.exists() {
+        return Ok(None);
+    }
+
+    let content = fs::read(&product_json_path)?;
+    let mut json: Value = serde_json::from_slice(&content)?;
+    let Some(map) = json.as_object_mut() else {
+        return Ok(None);
+    };
+    if map.get("checksums") == Some(checksums)
+        || (checksums.is_null() && !map.contains_key("checksums"))
+    {
+        return Ok(None);
+    }
+    if checksums.is_null() {
+        map.remove("checksums");
+    } else {
+        map.insert("checksums".to_string(), checksums.clone());
+    }
+
+    Ok(Some(FileOp::Write {
+        path: product_json_path,
+        content: serde_json::to_string_pretty(&json)?.into_bytes(),
+    }))
+}
+
+/// 删除还原点
+#[tauri::command]
+pub fn delete_restore_point(
+    id: String,
+    locale: Option<String>,
+) -> Result<RestorePointList, String> {
+    let locale_ref = locale.as_deref();
+    let point_dir = restore_dir().join(&id);
+    if id.contains(['/', '\\', '.']) || !point_dir.join(POINT_FILE).exists() {
+        return Err(
+            restore_with(locale_ref, "restoreBackend.errors.notFound", &[("id", id)])
+                .to_message(locale_ref),
+        );
+    }
+    fs::remove_dir_all(&point_dir).map_err(|e| {
+        restore_with(
+            locale_ref,
+            "restoreBackend.errors.deleteFailed",
+            &[("detail", e.to_string())],
+        )
+        .to_message(locale_ref)
+    })?;
+    Ok(list_restore_points(None))
+}
@@ -0,0 +1,431 @@
+//! 影子安装
+//!
+//! 不可修改的安装 (Nix store、不可变发行版、锁定的 /opt) 无法提权写入，
+//! 补丁操作改为作用于应用数据目录中的用户副本，并生成启动副本的 .desktop 文件。
+//! 副本中补丁涉及的路径逐个复制，其余文件尽量使用硬链接。源安装版本变化后重建副本
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use super::config;
+use super::i18n::CommandError;
+use super::oplog;
+use super::paths;
+
+/// 影子目录中记录源安装与当前副本的文件
+const SHADOW_FILE: &str = "shadow.json";
+
+/// 补丁会修改的路径 (相对安装根目录)，复制而不是硬链接，避免改动源安装
+const COPY_PATHS: &[&str] = &[
+    "resources/app/extensions/antigravity",
+    "resources/app/out/vs/code/electron-browser/workbench",
+    "resources/app/product.json",
+    "resources/app/package.json",
+    "resources/app.asar",
+];
+
+fn shadow_with(_locale: Option<&str>, key: &'static str, vars: &[(&str, String)]) -> CommandError {
+    CommandError::key_with(key, vars)
+}
+
+/// 影子目录状态
+#[derive(Debug, Serialize, Deserialize)]
+struct Shadow {
+    /// 源安装根目录
+    source: PathBuf,
+    /// 当前副本对应的源安装版本标记，同时是副本目录名
+    stamp: String,
+}
+
+impl Shadow {
+    fn read(dir: &Path) -> Option<Self> {
+        let content = fs::read(dir.join(SHADOW_FILE)).ok()?;
+        serde_json::from_slice(&content).ok()
+    }
+
+    fn write(&self, dir: &Path) -> io::Result<()> {
+        let content = serde_json::to_vec_pretty(self).map_err(io::Error::other)?;
+        fs::write(dir.join(SHADOW_FILE), content)
+    }
+}
+
+/// 同步结果
+pub(super) struct Synced {
+    /// 当前副本 (补丁操作使用的安装根目录)
+    pub root: PathBuf,
+    /// 源安装版本变化前的旧副本，需要按其中的配置重新安装补丁
+    pub previous: Option<PathBuf>,
+}
+
+/// 当前用户对安装目录的写入权限
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Access {
+    Writable,
+    /// 无权限写入 (root 所有的 /opt、/usr 等)，需要提权或影子副本
+    Denied,
+    /// Nix store 或只读文件系统，提权后同样无法写入
+    ReadOnly,
+}
+
+/// 是否对该安装使用影子副本 (仅 Linux): 安装位于只读位置，
+/// 或设置中开启且当前用户无法写入安装目录
+pub(super) fn is_enabled(source: &Path) -> bool {
+    cfg!(target_os = "linux")
+        && uses_shadow(write_access(source), config::read_config().shadow_install)
+}
+
+fn uses_shadow(access: Access, opted_in: bool) -> bool {
+    match access {
+        Access::Writable => false,
+        Access::Denied => opted_in,
+        Access::ReadOnly => true,
+    }
+}
+
+/// 在 resources 目录中创建测试文件检查写入权限
+fn write_access(source: &Path) -> Access {
+    let canonical = fs::canonicalize(source).unwrap_or_else(|_| source.to_path_buf());
+    if canonical.starts_with("/nix/store") {
+        return Access::ReadOnly;
+    }
+
+    let test_path = source.join("resources").join(".anti-power-write-test");
+    match fs::OpenOptions::new()
+        .create(true)
+        .truncate(true)
+        .write(true)
+        .open(&test_path)
+    {
+        Ok(_) => {
+            let _ = fs::remove_file(&test_path);
+            Access::Writable
+        }
+        Err(err) if err.kind() == ErrorKind::ReadOnlyFilesystem => Access::ReadOnly,
+        Err(_) => Access::Denied,
+    }
+}
+
+/// 源安装的影子目录 (应用数据目录/shadow/<源路径哈希>)
+/// 不解析符号链接，通过 profile 链接选择的 Nix 安装更新后仍对应同一目录
+fn work_dir(source: &Path) -> PathBuf {
+    config::get_app_data_dir()
+        .join("shadow")
+        .join(short_hash(source.to_string_lossy().as_bytes()))
+}
+
+fn short_hash(data: &[u8]) -> String {
+    Sha256::digest(data)[..8]
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// 源安装的版本标记: 版本号 + package.json (或 app.asar) 的大小与修改时间
+fn source_stamp(source: &Path) -> io::Result<String> {
+    let app_root = paths::resources_app_root(source);
+    let marker = paths::resources_archive(source).unwrap_or_else(|| app_root.join("package.json"));
+    let metadata = fs::metadata(&marker)?;
+    let modified = metadata
+        .modified()?
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_nanos())
+        .unwrap_or_default();
+    let version = paths::read_antigravity_version(&app_root).unwrap_or_else(|| "unknown".into());
+    let hash = short_hash(format!("{}:{}:{}", version, metadata.len(), modified).as_bytes());
+    Ok(format!("{}-{}", version, &hash[..8]))
+}
+
+/// 源安装当前的影子副本，不存在时返回 None (不创建、不同步)
+pub(super) fn current_root(source: &Path) -> Option<PathBuf> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    let dir = work_dir(source);
+    let shadow = Shadow::read(&dir)?;
+    Some(dir.join(shadow.stamp)).filter(|root| root.is_dir())
+}
+
+/// 确保影子副本与源安装版本一致，版本变化时重建
+/// 重建后需在补丁重新安装完成后调用 finish
+pub(super) fn sync(source: &Path) -> io::Result<Synced> {
+    let dir = work_dir(source);
+    let stamp = source_stamp(source)?;
+    let root = dir.join(&stamp);
+    let shadow = Shadow::read(&dir);
+    if root.is_dir() && shadow.as_ref().is_some_and(|shadow| shadow.stamp == stamp) {
+        return Ok(Synced {
+            root,
+            previous: None,
+        });
+    }
+
+    let previous = shadow
+        .map(|shadow| dir.join(shadow.stamp))
+        .filter(|previous| previous.is_dir() && *previous != root);
+
+    // 上次重建未完成时留下的副本
+    if root.exists() {
+        fs::remove_dir_all(&root)?;
+    }
+    let partial = dir.join(format!(".{}.partial", stamp));
+    if partial.exists() {
+        fs::remove_dir_all(&partial)?;
+    }
+    fs::create_dir_all(&dir)?;
+    if let Err(e) = clone_tree(source, &partial, Path::new("")) {
+        let _ = fs::remove_dir_all(&partial);
+        return Err(e);
+    }
+    fs::rename(&partial, &root)?;
+    oplog::file("write", &root);
+
+    // 首次创建副本时直接记录，版本变化时等补丁重新安装后再记录
+    if previous.is_none() {
+        finish(source, &root, None)?;
+    }
+    Ok(Synced { root, previous })
+}
+
+/// 完成副本重建: 记录当前副本，删除旧副本，更新已生成的启动项
+pub(super) fn finish(source: &Path, root: &Path, previous: Option<&Path>) -> io::Result<()> {
+    let dir = work_dir(source);
+    Shadow {
+        source: source.to_path_buf(),
+        stamp: root
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string(),
+    }
+    .write(&dir)?;
+
+    if let Some(previous) = previous {
+        fs::remove_dir_all(previous)?;
+        oplog::file("delete", previous);
+    }
+    if launcher_path(source).is_some_and(|launcher| launcher.is_file()) {
+        write_launcher(source, root)?;
+    }
+    Ok(())
+}
+
+/// 复制安装目录: 补丁涉及的路径复制，其余文件硬链接 (跨文件系统或无权限时退回复制)
+fn clone_tree(source: &Path, target: &Path, relative: &Path) -> io::Result<()> {
+    fs::create_dir(target)?;
+    for entry in fs::read_dir(source)? {
+        let entry = entry?;
+        let from = entry.path();
+        let to = target.join(entry.file_name());
+        let relative = relative.join(entry.file_name());
+        let file_type = entry.file_type()?;
+
+        if file_type.is_symlink() {
+            #[cfg(unix)]
+            std::os::unix::fs::symlink(fs::read_link(&from)?, &to)?;
+        } else if file_type.is_dir() {
+            clone_tree(&from, &to, &relative)?;
+        } else if must_copy(&relative) || fs::hard_link(&from, &to).is_err() {
+            copy_file(&from, &to)?;
+        }
+    }
+    Ok(())
+}
+
+fn must_copy(relative: &Path) -> bool {
+    COPY_PATHS.iter().any(|path| relative.starts_with(path))
+}
+
+/// 复制文件并保证副本可写 (Nix store 中的文件为只读)
+fn copy_file(from: &Path, to: &Path) -> io::Result<()> {
+    fs::copy(from, to)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mut permissions = fs::metadata(to)?.permissions();
+        permissions.set_mode(permissions.mode() | 0o200);
+        fs::set_permissions(to, permissions)?;
+    }
+    Ok(())
+}
+
+/// 影子副本对应的源安装，普通安装返回 None
+fn source_of(antigravity_root: &Path) -> Option<PathBuf> {
+    let dir = antigravity_root.parent()?;
+    let shadow = Shadow::read(dir)?;
+    (antigravity_root.file_name()? == shadow.stamp.as_str()).then_some(shadow.source)
+}
+
+/// 启动项: ~/.local/share/applications/anti-power-antigravity-<源路径哈希>.desktop
+fn launcher_path(source: &Path) -> Option<PathBuf> {
+    let name = format!(
+        "anti-power-antigravity-{}.desktop",
+        short_hash(source.to_string_lossy().as_bytes())
+    );
+    dirs::data_dir().map(|dir| dir.join("applications").join(name))
+}
+
+/// 补丁写入副本后生成启动项，普通安装不做处理
+pub(super) fn commit(antigravity_root: &Path) -> io::Result<()> {
+    match source_of(antigravity_root) {
+        Some(source) => write_launcher(&source, antigravity_root),
+        None => Ok(()),
+    }
+}
+
+fn write_launcher(source: &Path, antigravity_root: &Path) -> io::Result<()> {
+    let launcher = launcher_path(source).ok_or_else(|| io::Error::other("data dir not found"))?;
+    let executable = paths::antigravity_executable(antigravity_root)
+        .ok_or_else(|| io::Error::other("antigravity executable not found"))?;
+    let icon = paths::resources_app_root(antigravity_root)
+        .join("resources")
+        .join("linux")
+        .join("code.png");
+    let icon = if icon.is_file() {
+        icon.display().to_string()
+    } else {
+        "antigravity".to_string()
+    };
+
+    let content = format!(
+        "[Desktop Entry]\n\
+         Type=Application\n\
+         Name=Antigravity (Anti-Power)\n\
+         Comment=Antigravity with Anti-Power patches ({})\n\
+         Exec={} %F\n\
+         Icon={}\n\
+         Terminal=false\n\
+         Categories=Development;IDE;\n",
+        source.display(),
+        desktop_quote(&executable.display().to_string()),
+        icon
+    );
+    if let Some(parent) = launcher.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&launcher, content)?;
+    oplog::file("write", &launcher);
+    Ok(())
+}
+
+/// .desktop Exec 字段的引号转义
+fn desktop_quote(value: &str) -> String {
+    let mut quoted = String::from("\"");
+    for ch in value.chars() {
+        if matches!(ch, '"' | '`' | '$' | '\\') {
+            quoted.push('\\');
+        }
+        quoted.push(ch);
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// 卸载时删除影子副本与启动项，普通安装返回 false
+pub(super) fn remove(antigravity_root: &Path) -> io::Result<bool> {
+    match source_of(antigravity_root) {
+        Some(source) => remove_source(&source).map(|()| true),
+        None => Ok(false),
+    }
+}
+
+fn remove_source(source: &Path) -> io::Result<()> {
+    if let Some(launcher) = launcher_path(source).filter(|launcher| launcher.is_file()) {
+        fs::remove_file(&launcher)?;
+        oplog::file("delete", &launcher);
+    }
+    let dir = work_dir(source);
+    if dir.exists() {
+        fs::remove_dir_all(&dir)?;
+        oplog::file("delete", &dir);
+    }
+    Ok(())
+}
+
+/// 影子安装状态
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShadowStatus {
+    /// 是否使用影子副本
+    pub enabled: bool,
+    /// 安装位于只读位置，必须使用影子副本
+    pub read_only: bool,
+    /// 当前用户可直接写入安装目录，开启设置后仍直接修补
+    pub writable: bool,
+    /// 设置中开启了影子安装
+    pub opted_in: bool,
+    /// 当前副本目录
+    pub root: Option<String>,
+    /// 已生成的启动项
+    pub launcher: Option<String>,
+}
+
+/// 获取安装的影子副本状态
+#[tauri::command]
+pub fn get_shadow_status(path: String) -> ShadowStatus {
+    let Some(source) = paths::normalize_antigravity_root(Path::new(&path)) else {
+        return ShadowStatus {
+            enabled: false,
+            read_only: false,
+            writable: false,
+            opted_in: config::read_config().shadow_install,
+            root: None,
+            launcher: None,
+        };
+    };
+    let access = write_access(&source);
+    let opted_in = config::read_config().shadow_install;
+    ShadowStatus {
+        enabled: cfg!(target_os = "linux") && uses_shadow(access, opted_in),
+        read_only: cfg!(target_os = "linux") && access == Access::ReadOnly,
+        writable: access == Access::Writable,
+        opted_in,
+        root: current_root(&source).map(|root| root.display().to_string()),
+        launcher: launcher_path(&source)
+            .filter(|launcher| launcher.is_file())
+            .map(|launcher| launcher.display().to_string()),
+    }
+}
+
+/// 开启或关闭影子安装，关闭后删除不再使用副本的安装的副本与启动项
+#[tauri::command]
+pub fn set_shadow_install(enabled: bool, locale: Option<String>) -> Result<(), String> {
+    let locale_ref = locale.as_deref();
+    let mut app_config =
+        config::load_config_for_update(locale_ref).map_err(|err| err.to_message(locale_ref))?;
+    app_config.shadow_install = enabled;
+    config::save_config_internal(app_config, locale_ref)
+        .map_err(|err| err.to_message(locale_ref))?;
+
+    if !enabled {
+        remove_unused()
+            .map_err(|e| {
+                shadow_with(
+                    locale_ref,
+                    "shadowBackend.errors.removeFailed",
+                    &[("detail", e.to_string())],
+                )
+            })
+            .map_err(|err| err.to_message(locale_ref))?;
+    }
+    Ok(())
+}
+
+/// 删除所有不再启用影子安装的源安装的副本
+fn remove_unused() -> io::Result<()> {
+    let Ok(entries) = fs::read_dir(config::get_app_data_dir().join("shadow")) else {
+        return Ok(());
+    };
+    for entry in entries.flatten() {
+        let Some(shadow) = Shadow::read(&entry.path()) else {
+            continue;
+        };
+        if !is_enabled(&shadow.source) {
+            remove_source(&shadow.source)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,49 @@
+//! 补丁嵌入排除列表
+//!
+//! 解析 patches/.embed-exclude.txt，构建脚本 (build.rs) 与开发同步共用，
+//! 保证开发同步部署的文件与嵌入的补丁集一致
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+/// 排除列表文件名 (位于 patches 目录)
+pub const EXCLUDE_FILE: &str = ".embed-exclude.txt";
+
+/// 读取排除列表文件
+/// 返回不需要嵌入的文件路径集合
+pub fn read_exclude_list(path: &Path) -> HashSet<String> {
+    let mut entries = HashSet::new();
+    // 排除列表文件本身不需要嵌入
+    entries.insert(EXCLUDE_FILE.to_string());
+
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(_) => return entries,
+    };
+
+    for line in content.lines() {
+        let trimmed = line.trim();
+        // 跳过空行和注释
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        entries.insert(trimmed.replace('\\', "/"));
+    }
+
+    entries
+}
+
+/// 检查路径是否在排除列表中
+pub fn is_excluded(path: &str, exclude: &HashSet<String>) -> bool {
+    // 精确匹配
+    if exclude.contains(path) {
+        return true;
+    }
+
+    // 目录前缀匹配
+    exclude.iter().any(|entry| {
+        let entry = entry.trim_end_matches('/');
+        path == entry || path.starts_with(&format!("{}/", entry))
+    })
+}
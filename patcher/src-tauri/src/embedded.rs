@@ -1,29 +1,168 @@
 //! 嵌入的补丁资源模块
 //!
-//! 使用 include_str! 将文件内容在编译时嵌入到二进制中
+//! 使用 include_bytes! 将文件内容在编译时嵌入到二进制中
+//! 较大的文件在构建时经过 gzip 压缩，安装时解压
+//! 提供编译时生成的补丁集清单（版本号、文件哈希、整体摘要）
 //! 支持开发模式下从磁盘实时读取文件
 
+use flate2::read::GzDecoder;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::fs;
+use std::io::Read;
 use std::path::PathBuf;
 
+/// 离线渲染第三方库所在的子目录前缀
+const VENDOR_PREFIX: &str = "vendor/";
+
+/// 指定 patches 目录的环境变量，设置后不再从当前目录向上搜索
+pub const PATCHES_DIR_ENV: &str = "ANTI_POWER_PATCHES_DIR";
+
 #[derive(Debug)]
 pub enum EmbeddedError {
     PatchesDirNotFound,
     ReadPatchFileFailed { path: PathBuf, detail: String },
+    DecompressFailed { path: String, detail: String },
+}
+
+/// 编译时嵌入的单个文件
+pub struct EmbeddedFile {
+    /// 相对 patches 目录的路径
+    pub path: &'static str,
+    /// 文件内容（compressed 为 true 时为 gzip 数据）
+    pub content: &'static [u8],
+    /// 是否在构建时压缩
+    pub compressed: bool,
+    /// 原始内容的 SHA-256（十六进制）
+    pub sha256: &'static str,
+    /// 原始内容大小（字节）
+    pub size: usize,
+}
+
+impl EmbeddedFile {
+    /// 获取解压后的文件内容
+    pub fn bytes(&self) -> Result<Vec<u8>, EmbeddedError> {
+        if !self.compressed {
+            return Ok(self.content.to_vec());
+        }
+
+        let mut decoder = GzDecoder::new(self.content);
+        let mut content = Vec::new();
+        decoder
+            .read_to_end(&mut content)
+            .map_err(|e| EmbeddedError::DecompressFailed {
+                path: self.path.to_string(),
+                detail: e.to_string(),
+            })?;
+        Ok(content)
+    }
 }
 
-// 编译时生成的嵌入文件列表
+/// 补丁集清单
+/// 用于在安装目录、状态查询和问题反馈中标识所用的补丁构建
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PatchManifest {
+    /// 补丁集版本号
+    pub version: String,
+    /// 所有文件哈希的整体摘要
+    pub digest: String,
+    pub files: Vec<ManifestFile>,
+    /// 开发模式下磁盘内容与嵌入内容不一致的文件
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub modified_files: Vec<String>,
+    /// 第三方补丁包 ID (内置补丁为 None)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pack: Option<String>,
+}
+
+/// 清单中的单个文件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestFile {
+    pub path: String,
+    pub sha256: String,
+    pub size: usize,
+}
+
+// 编译时生成的嵌入文件列表与补丁集清单常量
 include!(concat!(env!("OUT_DIR"), "/embedded_patches.rs"));
 
-/// 获取所有嵌入的补丁文件列表
-/// 返回 (相对路径, 文件内容) 的元组列表
-pub fn get_all_files() -> Vec<(&'static str, &'static str)> {
-    EMBEDDED_FILES.to_vec()
+/// 计算 SHA-256 并返回十六进制字符串
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// 获取补丁集清单
+/// 仅包含满足过滤条件的文件，版本号与整体摘要始终对应完整补丁集
+pub fn manifest(filter: impl Fn(&str) -> bool) -> PatchManifest {
+    let files = EMBEDDED_FILES
+        .iter()
+        .filter(|file| filter(file.path))
+        .map(|file| ManifestFile {
+            path: file.path.to_string(),
+            sha256: file.sha256.to_string(),
+            size: file.size,
+        })
+        .collect();
+
+    PatchManifest {
+        version: PATCH_SET_VERSION.to_string(),
+        digest: PATCH_SET_DIGEST.to_string(),
+        files,
+        modified_files: modified_files_on_disk(),
+        pack: None,
+    }
+}
+
+/// 开发模式下检查磁盘上的补丁文件是否与嵌入内容一致
+/// 发布模式或找不到 patches 目录时返回空列表
+fn modified_files_on_disk() -> Vec<String> {
+    if !cfg!(debug_assertions) {
+        return Vec::new();
+    }
+
+    let Some(patches_dir) = find_patches_dir() else {
+        return Vec::new();
+    };
+
+    EMBEDDED_FILES
+        .iter()
+        .filter(|file| match fs::read(patches_dir.join(file.path)) {
+            Ok(content) => sha256_hex(&content) != file.sha256,
+            Err(_) => true,
+        })
+        .map(|file| file.path.to_string())
+        .collect()
+}
+
+/// 获取所有嵌入的补丁文件列表（不含离线渲染第三方库）
+pub fn get_all_files() -> Vec<&'static EmbeddedFile> {
+    EMBEDDED_FILES
+        .iter()
+        .filter(|file| !file.path.starts_with(VENDOR_PREFIX))
+        .collect()
+}
+
+/// 获取所有嵌入的离线渲染第三方库文件
+/// 未打包第三方库时为空
+pub fn get_vendor_files() -> Vec<&'static EmbeddedFile> {
+    EMBEDDED_FILES
+        .iter()
+        .filter(|file| file.path.starts_with(VENDOR_PREFIX))
+        .collect()
 }
 
 /// 查找 patches 目录
-/// 从当前目录向上搜索，最多查找 6 层
-fn find_patches_dir() -> Option<PathBuf> {
+/// 优先使用环境变量指定的目录，否则从当前目录向上搜索，最多查找 6 层
+pub fn find_patches_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os(PATCHES_DIR_ENV) {
+        let dir = PathBuf::from(dir);
+        return dir.is_dir().then_some(dir);
+    }
+
     let mut dir = std::env::current_dir().ok()?;
 
     for _ in 0..6 {
@@ -47,30 +186,56 @@ fn find_patches_dir() -> Option<PathBuf> {
     None
 }
 
-/// 运行时获取所有补丁文件
+/// 运行时读取文件内容
 ///
-/// 开发模式下从磁盘实时读取文件（便于热更新调试）
+/// 开发模式下从磁盘实时读取文件（便于热更新调试），
+/// 与嵌入内容不一致的文件记录在清单的 modified_files 中
 /// 发布模式下使用编译时嵌入的文件内容
-pub fn get_all_files_runtime() -> Result<Vec<(String, String)>, EmbeddedError> {
+fn read_files_runtime(
+    files: Vec<&'static EmbeddedFile>,
+) -> Result<Vec<(String, Vec<u8>)>, EmbeddedError> {
     // 开发模式：从磁盘读取
     if cfg!(debug_assertions) {
         let patches_dir = find_patches_dir().ok_or(EmbeddedError::PatchesDirNotFound)?;
-        let mut files = Vec::new();
-        for (relative_path, _) in get_all_files() {
-            let full_path = patches_dir.join(relative_path);
-            let content =
-                fs::read_to_string(&full_path).map_err(|e| EmbeddedError::ReadPatchFileFailed {
-                    path: full_path.clone(),
-                    detail: e.to_string(),
-                })?;
-            files.push((relative_path.to_string(), content));
+        let mut result = Vec::new();
+        for file in files {
+            let full_path = patches_dir.join(file.path);
+            let content = fs::read(&full_path).map_err(|e| EmbeddedError::ReadPatchFileFailed {
+                path: full_path.clone(),
+                detail: e.to_string(),
+            })?;
+            result.push((file.path.to_string(), content));
         }
-        return Ok(files);
+        return Ok(result);
     }
 
     // 发布模式：使用嵌入的文件
-    Ok(get_all_files()
+    files
         .into_iter()
-        .map(|(path, content)| (path.to_string(), content.to_string()))
-        .collect())
+        .map(|file| Ok((file.path.to_string(), file.bytes()?)))
+        .collect()
+}
+
+/// 运行时获取所有补丁文件
+/// 返回 (相对路径, 文件字节) 的元组列表
+pub fn get_all_files_runtime() -> Result<Vec<(String, Vec<u8>)>, EmbeddedError> {
+    read_files_runtime(get_all_files())
+}
+
+/// 运行时获取单个补丁文件，不存在时返回 None
+pub fn get_file_runtime(path: &str) -> Result<Option<Vec<u8>>, EmbeddedError> {
+    let files = EMBEDDED_FILES
+        .iter()
+        .filter(|file| file.path == path)
+        .collect();
+    Ok(read_files_runtime(files)?
+        .into_iter()
+        .next()
+        .map(|(_, content)| content))
+}
+
+/// 运行时获取离线渲染第三方库文件
+/// 返回 (相对路径, 文件字节) 的元组列表
+pub fn get_vendor_files_runtime() -> Result<Vec<(String, Vec<u8>)>, EmbeddedError> {
+    read_files_runtime(get_vendor_files())
 }
@@ -3,12 +3,20 @@
 //! 负责路径检测、补丁安装/卸载、配置读写等核心功能
 
 mod commands;
+mod embed_exclude;
 mod embedded;
 
 use commands::{
-    check_patch_status, detect_antigravity_path, get_config, install_patch,
-    normalize_antigravity_path, read_manager_patch_config, read_patch_config, run_anti_clean,
-    save_config, uninstall_patch, update_config,
+    activate_profile, add_trusted_key, check_patch_status, delete_profile, delete_restore_point,
+    detect_antigravity_path, discover_antigravity_installs, export_diagnostics, export_profile,
+    find_antigravity_processes, get_config, get_config_location, get_dev_sync_status,
+    get_operation_history, get_shadow_status, get_user_overlay_dir, import_patch_pack,
+    import_profile, install_patch, list_patch_packs, list_profiles, list_restore_points,
+    list_trusted_keys, normalize_antigravity_path, read_manager_patch_config, read_patch_config,
+    remove_patch_pack, remove_trusted_key, rename_profile, rollback_restore_point, run_anti_clean,
+    run_doctor, save_config, save_profile, set_restore_point_limit, set_shadow_install,
+    set_unsigned_pack_override, start_dev_sync, stop_dev_sync, uninstall_patch, update_config,
+    verify_live_patch,
 };
 use tauri::Manager;
 
@@ -42,8 +50,39 @@ pub fn run() {
             read_patch_config,
             read_manager_patch_config,
             get_config,
+            get_config_location,
+            get_operation_history,
             save_config,
-            run_anti_clean
+            get_user_overlay_dir,
+            run_anti_clean,
+            import_patch_pack,
+            list_patch_packs,
+            remove_patch_pack,
+            set_unsigned_pack_override,
+            list_trusted_keys,
+            add_trusted_key,
+            remove_trusted_key,
+            start_dev_sync,
+            stop_dev_sync,
+            get_dev_sync_status,
+            list_profiles,
+            save_profile,
+            rename_profile,
+            delete_profile,
+            activate_profile,
+            export_profile,
+            import_profile,
+            list_restore_points,
+            rollback_restore_point,
+            delete_restore_point,
+            set_restore_point_limit,
+            run_doctor,
+            export_diagnostics,
+            find_antigravity_processes,
+            verify_live_patch,
+            get_shadow_status,
+            set_shadow_install,
+            discover_antigravity_installs
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,213 @@
+/**
+ * 模拟 Antigravity 远程调试端口 (CDP), 用于验证安装器的运行时验证 (verify_live_patch)
+ * 和配置热重载 (update_config).
+ * 不依赖 ws, 只实现 /json/version, /json/list, Runtime.evaluate 和 SystemInfo.getProcessInfo.
+ *
+ * 使用方法:
+ *   node scripts/mock-cdp-server.js --install "<Antigravity安装目录>" [--port 9333] [--scenario ok] [--pid <进程ID>]
+ *
+ * pid: SystemInfo.getProcessInfo 报告的浏览器进程 ID (默认为本进程).
+ *      热重载只在该进程的可执行文件位于安装目录下时执行.
+ *
+ * scenario:
+ *   ok         面板脚本已加载, 配置与磁盘一致, DOM 钩子齐全
+ *   stale      配置为旧值 (补丁更新后未重新加载)
+ *   unpatched  页面仍是未打补丁的版本
+ *   noManager  Manager 窗口未打开
+ */
+
+const crypto = require('crypto');
+const fs = require('fs');
+const http = require('http');
+const path = require('path');
+
+const args = process.argv.slice(2);
+const option = (name, fallback) => {
+    const index = args.indexOf(`--${name}`);
+    return index >= 0 && args[index + 1] ? args[index + 1] : fallback;
+};
+
+const PORT = Number(option('port', 9333));
+const SCENARIO = option('scenario', 'ok');
+const INSTALL = option('install', '');
+const PID = Number(option('pid', process.pid));
+
+const readConfig = (...segments) => {
+    try {
+        return JSON.parse(fs.readFileSync(path.join(INSTALL, 'resources', 'app', ...segments), 'utf8'));
+    } catch {
+        return {};
+    }
+};
+
+const PANELS = {
+    cascade: {
+        url: 'vscode-file://vscode-app/resources/app/extensions/antigravity/cascade-panel.html',
+        type: 'webview',
+        reload: () => readConfig('extensions', 'antigravity', 'cascade-panel', 'config.json'),
+    },
+    manager: {
+        url: 'vscode-file://vscode-app/resources/app/out/vs/code/electron-browser/workbench/workbench-jetski-agent.html',
+        type: 'page',
+        reload: () => readConfig('out', 'vs', 'code', 'electron-browser', 'workbench', 'manager-panel', 'config.json'),
+    },
+};
+for (const panel of Object.values(PANELS)) {
+    panel.config = panel.reload();
+}
+
+const targets = () => Object.entries(PANELS)
+    .filter(([id]) => !(SCENARIO === 'noManager' && id === 'manager'))
+    .map(([id, panel]) => ({
+        id,
+        type: panel.type,
+        title: id,
+        url: panel.url,
+        webSocketDebuggerUrl: `ws://127.0.0.1:${PORT}/devtools/page/${id}`,
+    }));
+
+/**
+ * 根据表达式中请求的钩子构造返回值
+ */
+const evaluate = (panel, expression) => {
+    // 配置热重载: 未打补丁的页面没有热重载函数
+    if (expression.includes('_RELOAD_CONFIG__')) {
+        if (SCENARIO === 'unpatched') return false;
+        panel.config = panel.reload();
+        return true;
+    }
+    if (SCENARIO === 'unpatched') {
+        return { scriptTag: false, config: null, hooks: {} };
+    }
+    const config = SCENARIO === 'stale'
+        ? Object.fromEntries(Object.entries(panel.config).map(([key, value]) => [
+            key,
+            typeof value === 'number' ? value + 1 : value,
+        ]))
+        : panel.config;
+    const hooks = {};
+    for (const match of expression.matchAll(/"(\w+)": /g)) {
+        hooks[match[1]] = true;
+    }
+    return { scriptTag: true, config, hooks };
+};
+
+const sendFrame = (socket, text) => {
+    const payload = Buffer.from(text);
+    let header;
+    if (payload.length < 126) {
+        header = Buffer.from([0x81, payload.length]);
+    } else if (payload.length < 65536) {
+        header = Buffer.alloc(4);
+        header.writeUInt8(0x81, 0);
+        header.writeUInt8(126, 1);
+        header.writeUInt16BE(payload.length, 2);
+    } else {
+        header = Buffer.alloc(10);
+        header.writeUInt8(0x81, 0);
+        header.writeUInt8(127, 1);
+        header.writeBigUInt64BE(BigInt(payload.length), 2);
+    }
+    socket.write(Buffer.concat([header, payload]));
+};
+
+/**
+ * 解析客户端帧 (客户端帧总是带掩码)
+ */
+const readFrames = (buffer, onFrame) => {
+    while (buffer.length >= 2) {
+        const opcode = buffer[0] & 0x0f;
+        let length = buffer[1] & 0x7f;
+        let offset = 2;
+        if (length === 126) {
+            if (buffer.length < 4) break;
+            length = buffer.readUInt16BE(2);
+            offset = 4;
+        } else if (length === 127) {
+            if (buffer.length < 10) break;
+            length = Number(buffer.readBigUInt64BE(2));
+            offset = 10;
+        }
+        if (buffer.length < offset + 4 + length) break;
+        const mask = buffer.subarray(offset, offset + 4);
+        const payload = Buffer.from(buffer.subarray(offset + 4, offset + 4 + length));
+        for (let i = 0; i < payload.length; i++) payload[i] ^= mask[i % 4];
+        onFrame(opcode, payload);
+        buffer = buffer.subarray(offset + 4 + length);
+    }
+    return buffer;
+};
+
+const server = http.createServer((req, res) => {
+    const routes = {
+        '/json/version': {
+            Browser: 'MockChrome/1.0',
+            'Protocol-Version': '1.3',
+            webSocketDebuggerUrl: `ws://127.0.0.1:${PORT}/devtools/browser/mock`,
+        },
+        '/json/list': targets(),
+        '/json': targets(),
+    };
+    const body = routes[req.url];
+    if (!body) {
+        res.writeHead(404);
+        res.end();
+        return;
+    }
+    const data = JSON.stringify(body);
+    res.writeHead(200, { 'Content-Type': 'application/json', 'Content-Length': Buffer.byteLength(data) });
+    res.end(data);
+});
+
+server.on('upgrade', (req, socket) => {
+    const id = req.url.split('/').pop();
+    const panel = PANELS[id];
+    const browser = req.url.startsWith('/devtools/browser/');
+    if (!panel && !browser) {
+        socket.destroy();
+        return;
+    }
+    const accept = crypto
+        .createHash('sha1')
+        .update(`${req.headers['sec-websocket-key']}258EAFA5-E914-47DA-95CA-C5AB0DC85B11`)
+        .digest('base64');
+    socket.write([
+        'HTTP/1.1 101 Switching Protocols',
+        'Upgrade: websocket',
+        'Connection: Upgrade',
+        `Sec-WebSocket-Accept: ${accept}`,
+        '',
+        '',
+    ].join('\r\n'));
+
+    let pending = Buffer.alloc(0);
+    socket.on('data', (chunk) => {
+        pending = readFrames(Buffer.concat([pending, chunk]), (opcode, payload) => {
+            if (opcode === 0x8) {
+                socket.end(Buffer.from([0x88, 0]));
+                return;
+            }
+            if (opcode !== 0x1) return;
+            const message = JSON.parse(payload.toString());
+            console.log(`[mock-cdp] ${id} ${message.method}`);
+            // 先发送一个事件, 客户端应忽略不匹配 id 的消息
+            sendFrame(socket, JSON.stringify({ method: 'Runtime.consoleAPICalled', params: {} }));
+            if (browser && message.method === 'SystemInfo.getProcessInfo') {
+                const processInfo = [{ type: 'browser', id: PID, cpuTime: 0 }];
+                sendFrame(socket, JSON.stringify({ id: message.id, result: { processInfo } }));
+                return;
+            }
+            if (browser || message.method !== 'Runtime.evaluate') {
+                sendFrame(socket, JSON.stringify({ id: message.id, error: { message: 'not supported' } }));
+                return;
+            }
+            const value = evaluate(panel, message.params.expression);
+            sendFrame(socket, JSON.stringify({ id: message.id, result: { result: { type: 'object', value } } }));
+        });
+    });
+    socket.on('error', () => {});
+});
+
+server.listen(PORT, '127.0.0.1', () => {
+    console.log(`[mock-cdp] listening on 127.0.0.1:${PORT} (scenario: ${SCENARIO})`);
+});
@@ -0,0 +1,93 @@
+/**
+ * 将 Antigravity 安装目录中的 resources/app 打包为 app.asar, 用于测试安装器的 app.asar 支持.
+ * 不依赖 @electron/asar, 格式与其输出一致 (含文件完整性信息).
+ *
+ * 使用方法:
+ *   node scripts/pack-asar.js "<Antigravity安装目录>" [--unpack node_modules]
+ *
+ * 打包后 resources/app 重命名为 resources/app.dir, 删除 app.asar 后改回即可恢复.
+ * --unpack 指定的目录放到 app.asar.unpacked 中 (模拟带原生模块的构建).
+ */
+
+const crypto = require('crypto');
+const fs = require('fs');
+const path = require('path');
+
+const BLOCK_SIZE = 4 * 1024 * 1024;
+
+const args = process.argv.slice(2);
+const unpackIndex = args.indexOf('--unpack');
+const unpack = unpackIndex >= 0 ? args[unpackIndex + 1] : null;
+const install = args.find((arg, index) => !arg.startsWith('--') && index !== unpackIndex + 1);
+if (!install) {
+    console.error('usage: node scripts/pack-asar.js "<Antigravity安装目录>" [--unpack <目录>]');
+    process.exit(1);
+}
+
+const resources = ['Resources', 'resources']
+    .map((name) => path.join(install, name))
+    .find((dir) => fs.existsSync(path.join(dir, 'app')));
+if (!resources) {
+    console.error(`resources/app not found in ${install}`);
+    process.exit(1);
+}
+const appDir = path.join(resources, 'app');
+
+const sha256 = (data) => crypto.createHash('sha256').update(data).digest('hex');
+
+const integrity = (data) => {
+    const blocks = [];
+    for (let start = 0; start < data.length || blocks.length === 0; start += BLOCK_SIZE) {
+        blocks.push(sha256(data.subarray(start, start + BLOCK_SIZE)));
+    }
+    return { algorithm: 'SHA256', hash: sha256(data), blockSize: BLOCK_SIZE, blocks };
+};
+
+const chunks = [];
+let offset = 0;
+
+const walk = (dir, relative) => {
+    const files = {};
+    for (const name of fs.readdirSync(dir).sort()) {
+        const full = path.join(dir, name);
+        const rel = relative ? `${relative}/${name}` : name;
+        const stat = fs.lstatSync(full);
+        if (stat.isSymbolicLink()) {
+            files[name] = { link: path.relative(appDir, path.resolve(dir, fs.readlinkSync(full))) };
+        } else if (stat.isDirectory()) {
+            files[name] = walk(full, rel);
+            if (unpack && rel === unpack) files[name].unpacked = true;
+        } else {
+            const data = fs.readFileSync(full);
+            const entry = { size: data.length, integrity: integrity(data) };
+            if (unpack && rel.startsWith(`${unpack}/`)) {
+                entry.unpacked = true;
+                const dest = path.join(resources, 'app.asar.unpacked', rel);
+                fs.mkdirSync(path.dirname(dest), { recursive: true });
+                fs.copyFileSync(full, dest);
+            } else {
+                entry.offset = String(offset);
+                offset += data.length;
+                chunks.push(data);
+            }
+            if (stat.mode & 0o100) entry.executable = true;
+            files[name] = entry;
+        }
+    }
+    return { files };
+};
+
+const header = Buffer.from(JSON.stringify(walk(appDir, '')));
+const padded = Math.ceil(header.length / 4) * 4;
+const prefix = Buffer.alloc(16);
+prefix.writeUInt32LE(4, 0);
+prefix.writeUInt32LE(padded + 8, 4);
+prefix.writeUInt32LE(padded + 4, 8);
+prefix.writeUInt32LE(header.length, 12);
+
+fs.writeFileSync(
+    path.join(resources, 'app.asar'),
+    Buffer.concat([prefix, header, Buffer.alloc(padded - header.length), ...chunks]),
+);
+fs.renameSync(appDir, path.join(resources, 'app.dir'));
+console.log(`[pack-asar] ${path.join(resources, 'app.asar')} (header sha256 ${sha256(header)})`);